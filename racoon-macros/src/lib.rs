@@ -0,0 +1,324 @@
+//! A procedural macro crate companion to the main `racoon` crate: `html! {
+//! ... }` accepts JSX-like markup and expands to the equivalent
+//! [`DocumentBuilder`]/[`ElementBuilder`] chain
+//! (`racoon::html::grammar::document_builder`), so callers don't have to
+//! write the nested nested-closure form by hand.
+//!
+//! ```ignore
+//! let tree = html! {
+//!     <div class="a">
+//!         <p>{text}</p>
+//!     </div>
+//! }
+//! .build()?;
+//! ```
+//!
+//! expands to
+//!
+//! ```ignore
+//! DocumentBuilder::new()
+//!     .add_element("div", move |e| {
+//!         e.add_attribute_str("class", "a")
+//!             .add_element("p", move |e| e.add_text(&(text).to_string()))
+//!     })
+//! ```
+//!
+//! Attributes (`name="value"`, value must be a string literal) map to
+//! [`ElementBuilder::add_attribute_str`], nested elements to nested
+//! [`ElementBuilder::add_element`] closures, string literals to
+//! [`ElementBuilder::add_text`], and `{ expr }` to
+//! `add_text(&(expr).to_string())`. `html!` doesn't call `.build()` itself
+//! — the expansion is a `DocumentBuilder`, same as constructing one by hand,
+//! so callers decide when (and whether) to build it.
+//!
+//! ## The `<!-- -->` limitation
+//!
+//! A proc-macro only ever sees a `TokenStream` of already-lexed Rust
+//! tokens — comments are stripped by the compiler's lexer before a macro
+//! gets to run, so there's no way to capture genuinely arbitrary text the
+//! way an HTML comment allows. `<!-- ... -->` here is parsed by matching
+//! the four-token sequence `<`, `!`, `-`, `-`, then consuming raw token
+//! trees (joined with spaces when building the comment's text) until the
+//! three-token sequence `-`, `-`, `>` is found. That reproduces ordinary
+//! comments made of identifiers, literals, and punctuation Rust itself can
+//! tokenize, but anything that isn't valid as a sequence of Rust tokens
+//! (an unmatched quote, for instance) will fail to parse — a proc-macro
+//! simply never gets handed the raw bytes to do better than that.
+//!
+//! ## Required-children validation
+//!
+//! [`REQUIRED_CHILDREN`] is checked at expansion time: `<html>` must have
+//! `<head>` and `<body>` among its direct children, and `<head>` must have
+//! `<title>`. A missing one is a `compile_error!`, not a runtime error —
+//! the whole point of building the skeleton through a macro instead of the
+//! builder directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, Expr, Ident, LitStr, Token,
+};
+
+/// Tag name -> the child tag names that must appear among its direct
+/// element children, checked by [`validate_required_children`].
+const REQUIRED_CHILDREN: &[(&str, &[&str])] = &[("html", &["head", "body"]), ("head", &["title"])];
+
+/// One node inside an `html!` invocation.
+enum Node {
+    Element(Element),
+    Comment(Comment),
+    Text(LitStr),
+    Expr(Expr),
+}
+
+/// A parsed `<!-- ... -->` comment; see the module docs for why its text
+/// is reconstructed from raw tokens rather than captured verbatim.
+struct Comment {
+    text: String,
+}
+
+struct Attribute {
+    name: Ident,
+    value: LitStr,
+}
+
+struct Element {
+    tag_name: Ident,
+    attributes: Vec<Attribute>,
+    children: Vec<Node>,
+}
+
+/// Whether the next three tokens in `input` are `-`, `-`, `>` (a comment's
+/// closing `-->`), without consuming them.
+fn at_comment_end(input: ParseStream) -> bool {
+    let fork = input.fork();
+    fork.parse::<Token![-]>().is_ok()
+        && fork.parse::<Token![-]>().is_ok()
+        && fork.parse::<Token![>]>().is_ok()
+}
+
+/// Whether the next two tokens in `input` are `<`, `!` (a comment's
+/// opening `<!--`), without consuming them.
+fn at_comment_start(input: ParseStream) -> bool {
+    let fork = input.fork();
+    fork.parse::<Token![<]>().is_ok() && fork.parse::<Token![!]>().is_ok()
+}
+
+/// Whether the next two tokens in `input` are `<`, `/` (an element's
+/// closing tag), without consuming them.
+fn at_closing_tag(input: ParseStream) -> bool {
+    let fork = input.fork();
+    fork.parse::<Token![<]>().is_ok() && fork.parse::<Token![/]>().is_ok()
+}
+
+impl Parse for Comment {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        input.parse::<Token![<]>()?;
+        input.parse::<Token![!]>()?;
+        input.parse::<Token![-]>()?;
+        input.parse::<Token![-]>()?;
+
+        let mut parts = Vec::new();
+        while !at_comment_end(input) {
+            if input.is_empty() {
+                return Err(input.error("unterminated `<!--` comment"));
+            }
+
+            let tt = input.step(|cursor| match cursor.token_tree() {
+                Some((tt, rest)) => Ok((tt, rest)),
+                None => Err(cursor.error("unterminated `<!--` comment")),
+            })?;
+            parts.push(tt.to_string());
+        }
+
+        input.parse::<Token![-]>()?;
+        input.parse::<Token![-]>()?;
+        input.parse::<Token![>]>()?;
+
+        Ok(Comment {
+            text: parts.join(" "),
+        })
+    }
+}
+
+impl Parse for Node {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if at_comment_start(input) {
+            Ok(Node::Comment(input.parse()?))
+        } else if input.peek(Token![<]) {
+            Ok(Node::Element(input.parse()?))
+        } else if input.peek(LitStr) {
+            Ok(Node::Text(input.parse()?))
+        } else if input.peek(syn::token::Brace) {
+            let content;
+            syn::braced!(content in input);
+            Ok(Node::Expr(content.parse()?))
+        } else {
+            Err(input
+                .error("expected an element, `<!-- -->` comment, string literal, or `{ expr }`"))
+        }
+    }
+}
+
+impl Parse for Attribute {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value: LitStr = input.parse()?;
+
+        Ok(Attribute { name, value })
+    }
+}
+
+impl Parse for Element {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        input.parse::<Token![<]>()?;
+        let tag_name: Ident = input.parse()?;
+
+        let mut attributes = Vec::new();
+        while !input.peek(Token![>]) && !input.peek(Token![/]) {
+            attributes.push(input.parse()?);
+        }
+
+        if input.peek(Token![/]) {
+            input.parse::<Token![/]>()?;
+            input.parse::<Token![>]>()?;
+
+            return Ok(Element {
+                tag_name,
+                attributes,
+                children: Vec::new(),
+            });
+        }
+
+        input.parse::<Token![>]>()?;
+
+        let mut children = Vec::new();
+        while !at_closing_tag(input) {
+            if input.is_empty() {
+                return Err(input.error(format!("unterminated `<{}>` element", tag_name)));
+            }
+
+            children.push(input.parse()?);
+        }
+
+        input.parse::<Token![<]>()?;
+        input.parse::<Token![/]>()?;
+        let closing_tag: Ident = input.parse()?;
+        input.parse::<Token![>]>()?;
+
+        if closing_tag != tag_name {
+            return Err(syn::Error::new(
+                closing_tag.span(),
+                format!(
+                    "closing tag `</{}>` does not match opening tag `<{}>`",
+                    closing_tag, tag_name
+                ),
+            ));
+        }
+
+        Ok(Element {
+            tag_name,
+            attributes,
+            children,
+        })
+    }
+}
+
+/// Check `element` and its descendants against [`REQUIRED_CHILDREN`],
+/// returning the first violation found.
+fn validate_required_children(element: &Element) -> syn::Result<()> {
+    let tag_name = element.tag_name.to_string();
+
+    if let Some((_, required)) = REQUIRED_CHILDREN.iter().find(|(name, _)| *name == tag_name) {
+        let child_tags: Vec<String> = element
+            .children
+            .iter()
+            .filter_map(|child| match child {
+                Node::Element(child) => Some(child.tag_name.to_string()),
+                _ => None,
+            })
+            .collect();
+
+        for &name in *required {
+            if !child_tags.iter().any(|tag| tag == name) {
+                return Err(syn::Error::new(
+                    element.tag_name.span(),
+                    format!("<{}> requires a <{}> child", tag_name, name),
+                ));
+            }
+        }
+    }
+
+    for child in &element.children {
+        if let Node::Element(child) = child {
+            validate_required_children(child)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Expand `element`'s attributes and children into the chain of
+/// `ElementBuilder` method calls that build it, starting from the bare `e`
+/// parameter of its enclosing `add_element` closure.
+fn expand_element_body(element: &Element) -> TokenStream2 {
+    let mut body = quote! {};
+
+    for attribute in &element.attributes {
+        let name = attribute.name.to_string();
+        let value = &attribute.value;
+        body = quote! { #body.add_attribute_str(#name, #value) };
+    }
+
+    for child in &element.children {
+        body = match child {
+            Node::Element(child) => {
+                let tag_name = child.tag_name.to_string();
+                let child_body = expand_element_body(child);
+                quote! { #body.add_element(#tag_name, move |e| e #child_body) }
+            }
+            Node::Comment(comment) => {
+                let text = &comment.text;
+                quote! { #body.add_comment(#text) }
+            }
+            Node::Text(text) => quote! { #body.add_text(#text) },
+            Node::Expr(expr) => quote! { #body.add_text(&(#expr).to_string()) },
+        };
+    }
+
+    body
+}
+
+#[proc_macro]
+pub fn html(input: TokenStream) -> TokenStream {
+    let node = parse_macro_input!(input as Node);
+
+    let element = match &node {
+        Node::Element(element) => element,
+        _ => {
+            return syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "`html!` expects a single root element, e.g. `html! { <div>...</div> }`",
+            )
+            .to_compile_error()
+            .into()
+        }
+    };
+
+    if let Err(err) = validate_required_children(element) {
+        return err.to_compile_error().into();
+    }
+
+    let tag_name = element.tag_name.to_string();
+    let body = expand_element_body(element);
+
+    let expanded = quote! {
+        ::racoon::html::grammar::document_builder::DocumentBuilder::new()
+            .add_element(#tag_name, move |e| e #body)
+    };
+
+    expanded.into()
+}
@@ -0,0 +1,509 @@
+//! A small CSS selector engine over [`XpathItemTree`].
+//!
+//! This does not depend on the `selectors` crate; it implements just enough
+//! of CSS selector syntax to be useful for querying a parsed document, and
+//! shares the same tree types (and therefore the same data) as the XPath
+//! engine.
+
+use std::fmt::Display;
+
+use thiserror::Error;
+
+use crate::xpath::{
+    grammar::{XpathItemTreeNode, XpathItemTreeNodeData},
+    XpathItemTree,
+};
+
+/// An error produced while parsing a selector string.
+#[derive(Debug, Error, PartialEq)]
+#[error("CssSelectorParseError: {message}")]
+pub struct CssSelectorParseError {
+    message: String,
+}
+
+/// A compiled CSS selector, or comma-separated list of selectors.
+///
+/// # Examples
+///
+/// ```ignore
+/// let selector = Selector::parse("div.content > p")?;
+/// let matches = selector.select(&tree);
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct Selector {
+    /// Each entry is one comma-separated alternative; a node matches the
+    /// selector if it matches any alternative.
+    selector_list: Vec<SelectorSequence>,
+}
+
+/// One comma-separated alternative: a sequence of compound selectors joined
+/// by combinators, stored in source (left-to-right) order.
+#[derive(Debug, PartialEq, Clone)]
+struct SelectorSequence {
+    /// `steps[0]` is the leftmost compound selector; `steps.last()` is the
+    /// rightmost (the one actually being matched against a candidate node).
+    /// `combinators[i]` relates `steps[i]` to `steps[i + 1]`.
+    steps: Vec<CompoundSelector>,
+    combinators: Vec<Combinator>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Combinator {
+    /// `a b`
+    Descendant,
+    /// `a > b`
+    Child,
+    /// `a + b`
+    NextSibling,
+    /// `a ~ b`
+    SubsequentSibling,
+}
+
+/// A single compound selector, e.g. `div.content#main[lang]`.
+#[derive(Debug, PartialEq, Clone, Default)]
+struct CompoundSelector {
+    type_name: Option<String>,
+    id: Option<String>,
+    classes: Vec<String>,
+    attributes: Vec<AttributeSelector>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct AttributeSelector {
+    name: String,
+    op: AttributeOp,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum AttributeOp {
+    /// `[attr]`
+    Present,
+    /// `[attr=val]`
+    Exact(String),
+    /// `[attr~=val]`
+    Includes(String),
+    /// `[attr^=val]`
+    StartsWith(String),
+    /// `[attr$=val]`
+    EndsWith(String),
+    /// `[attr*=val]`
+    Contains(String),
+}
+
+impl Selector {
+    /// Parse a CSS selector, or a comma-separated list of selectors.
+    pub fn parse(input: &str) -> Result<Self, CssSelectorParseError> {
+        let selector_list = input
+            .split(',')
+            .map(|part| parse_selector_sequence(part.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Selector { selector_list })
+    }
+
+    /// Select all elements in `tree` that match this selector, in document
+    /// order.
+    pub fn select<'tree>(&self, tree: &'tree XpathItemTree) -> Vec<XpathItemTreeNode<'tree>> {
+        let root = tree.root();
+        let mut matches = Vec::new();
+        collect_matches(&root, tree, self, &mut matches);
+        matches
+    }
+
+    fn matches(&self, node: &XpathItemTreeNode, tree: &XpathItemTree) -> bool {
+        self.selector_list
+            .iter()
+            .any(|sequence| sequence_matches(sequence, node, tree))
+    }
+}
+
+fn collect_matches<'tree>(
+    node: &XpathItemTreeNode<'tree>,
+    tree: &'tree XpathItemTree,
+    selector: &Selector,
+    matches: &mut Vec<XpathItemTreeNode<'tree>>,
+) {
+    if matches!(node.data, XpathItemTreeNodeData::ElementNode(_)) && selector.matches(node, tree) {
+        matches.push(node.clone());
+    }
+
+    for child in node.children(tree) {
+        collect_matches(&child, tree, selector, matches);
+    }
+}
+
+/// Check whether `node` satisfies the rightmost compound selector of
+/// `sequence`, and that each combinator to its left is also satisfied by
+/// some ancestor/sibling, walking right-to-left.
+fn sequence_matches(sequence: &SelectorSequence, node: &XpathItemTreeNode, tree: &XpathItemTree) -> bool {
+    let Some(last) = sequence.steps.last() else {
+        return false;
+    };
+
+    if !compound_matches(last, node) {
+        return false;
+    }
+
+    matches_combinators(sequence, sequence.steps.len() - 1, node, tree)
+}
+
+/// Recursively checks that `steps[..=step_index]` match, walking from
+/// `step_index` back towards the start of the selector.
+fn matches_combinators(
+    sequence: &SelectorSequence,
+    step_index: usize,
+    node: &XpathItemTreeNode,
+    tree: &XpathItemTree,
+) -> bool {
+    if step_index == 0 {
+        return true;
+    }
+
+    let combinator = sequence.combinators[step_index - 1];
+    let previous_step = &sequence.steps[step_index - 1];
+
+    match combinator {
+        Combinator::Descendant => {
+            let mut ancestor = node.parent(tree);
+            while let Some(current) = ancestor {
+                if compound_matches(previous_step, &current)
+                    && matches_combinators(sequence, step_index - 1, &current, tree)
+                {
+                    return true;
+                }
+                ancestor = current.parent(tree);
+            }
+            false
+        }
+        Combinator::Child => match node.parent(tree) {
+            Some(parent) => {
+                compound_matches(previous_step, &parent)
+                    && matches_combinators(sequence, step_index - 1, &parent, tree)
+            }
+            None => false,
+        },
+        Combinator::NextSibling => match preceding_sibling_element(node, tree) {
+            Some(sibling) => {
+                compound_matches(previous_step, &sibling)
+                    && matches_combinators(sequence, step_index - 1, &sibling, tree)
+            }
+            None => false,
+        },
+        Combinator::SubsequentSibling => {
+            let mut sibling = preceding_sibling_element(node, tree);
+            while let Some(current) = sibling {
+                if compound_matches(previous_step, &current)
+                    && matches_combinators(sequence, step_index - 1, &current, tree)
+                {
+                    return true;
+                }
+                sibling = preceding_sibling_element(&current, tree);
+            }
+            false
+        }
+    }
+}
+
+fn preceding_sibling_element<'tree>(
+    node: &XpathItemTreeNode<'tree>,
+    tree: &'tree XpathItemTree,
+) -> Option<XpathItemTreeNode<'tree>> {
+    let parent = node.parent(tree)?;
+    let mut previous = None;
+    for child in parent.children(tree) {
+        if child == *node {
+            return previous;
+        }
+        if matches!(child.data, XpathItemTreeNodeData::ElementNode(_)) {
+            previous = Some(child);
+        }
+    }
+    None
+}
+
+fn compound_matches(compound: &CompoundSelector, node: &XpathItemTreeNode) -> bool {
+    let XpathItemTreeNodeData::ElementNode(element) = node.data else {
+        return false;
+    };
+
+    if let Some(type_name) = &compound.type_name {
+        if type_name != "*" && &element.name != type_name {
+            return false;
+        }
+    }
+
+    if let Some(id) = &compound.id {
+        if attribute_value(element, "id").as_deref() != Some(id.as_str()) {
+            return false;
+        }
+    }
+
+    if !compound.classes.is_empty() {
+        let Some(class_attr) = attribute_value(element, "class") else {
+            return false;
+        };
+        let classes: Vec<&str> = class_attr.split_whitespace().collect();
+        if !compound.classes.iter().all(|c| classes.contains(&c.as_str())) {
+            return false;
+        }
+    }
+
+    compound
+        .attributes
+        .iter()
+        .all(|attr| attribute_matches(element, attr))
+}
+
+fn attribute_value(
+    element: &crate::xpath::grammar::data_model::ElementNode,
+    name: &str,
+) -> Option<String> {
+    element
+        .attributes
+        .iter()
+        .find(|a| a.name == name)
+        .map(|a| a.value.clone())
+}
+
+fn attribute_matches(
+    element: &crate::xpath::grammar::data_model::ElementNode,
+    selector: &AttributeSelector,
+) -> bool {
+    let Some(value) = attribute_value(element, &selector.name) else {
+        return false;
+    };
+
+    match &selector.op {
+        AttributeOp::Present => true,
+        AttributeOp::Exact(expected) => &value == expected,
+        AttributeOp::Includes(expected) => value.split_whitespace().any(|v| v == expected),
+        AttributeOp::StartsWith(expected) => value.starts_with(expected.as_str()),
+        AttributeOp::EndsWith(expected) => value.ends_with(expected.as_str()),
+        AttributeOp::Contains(expected) => value.contains(expected.as_str()),
+    }
+}
+
+fn parse_selector_sequence(input: &str) -> Result<SelectorSequence, CssSelectorParseError> {
+    // Normalize combinators to be whitespace-delimited tokens so the sequence
+    // can be split on whitespace (e.g. "a>b" becomes "a > b"). Skip over
+    // `[...]` spans while doing this: `~` (and, in principle, `>`/`+`) can
+    // appear inside an attribute selector like `[class~="foo"]`, and must not
+    // be mistaken for a combinator there.
+    let mut normalized = String::with_capacity(input.len());
+    let mut in_brackets = false;
+    for c in input.chars() {
+        match c {
+            '[' => {
+                in_brackets = true;
+                normalized.push(c);
+            }
+            ']' => {
+                in_brackets = false;
+                normalized.push(c);
+            }
+            '>' | '+' | '~' if !in_brackets => {
+                normalized.push(' ');
+                normalized.push(c);
+                normalized.push(' ');
+            }
+            _ => normalized.push(c),
+        }
+    }
+
+    let mut steps = Vec::new();
+    let mut combinators = Vec::new();
+
+    for token in normalized.split_whitespace() {
+        match token {
+            ">" => combinators.push(Combinator::Child),
+            "+" => combinators.push(Combinator::NextSibling),
+            "~" => combinators.push(Combinator::SubsequentSibling),
+            compound => {
+                // A compound selector following another compound selector with
+                // no explicit combinator token between them is a descendant
+                // combinator.
+                if !steps.is_empty() && combinators.len() < steps.len() {
+                    combinators.push(Combinator::Descendant);
+                }
+                steps.push(parse_compound_selector(compound)?);
+            }
+        }
+    }
+
+    if steps.is_empty() {
+        return Err(CssSelectorParseError {
+            message: format!("empty selector: '{}'", input),
+        });
+    }
+
+    Ok(SelectorSequence { steps, combinators })
+}
+
+/// Split a compound selector into its leading type-selector part (if any)
+/// and a list of the remaining `#id` / `.class` / `[attr...]` parts.
+fn split_compound_parts(input: &str) -> (Option<&str>, Vec<&str>) {
+    let first_special = input.find(['#', '.', '[']).unwrap_or(input.len());
+    let type_name = if first_special == 0 {
+        None
+    } else {
+        Some(&input[..first_special])
+    };
+
+    let mut parts = Vec::new();
+    let mut rest = &input[first_special..];
+    while !rest.is_empty() {
+        let marker = rest.chars().next().expect("rest is non-empty");
+        let part_end = if marker == '[' {
+            rest.find(']').map(|i| i + 1).unwrap_or(rest.len())
+        } else {
+            rest[1..]
+                .find(['#', '.', '['])
+                .map(|i| i + 1)
+                .unwrap_or(rest.len())
+        };
+        parts.push(&rest[..part_end]);
+        rest = &rest[part_end..];
+    }
+
+    (type_name, parts)
+}
+
+fn parse_compound_selector(input: &str) -> Result<CompoundSelector, CssSelectorParseError> {
+    let mut compound = CompoundSelector::default();
+    let (type_name, parts) = split_compound_parts(input);
+    compound.type_name = type_name.map(str::to_string);
+
+    for part in parts {
+        let mut chars = part.chars();
+        match chars.next() {
+            Some('#') => compound.id = Some(chars.as_str().to_string()),
+            Some('.') => compound.classes.push(chars.as_str().to_string()),
+            Some('[') => {
+                let body = chars.as_str().trim_end_matches(']');
+                compound.attributes.push(parse_attribute_selector(body));
+            }
+            _ => {
+                return Err(CssSelectorParseError {
+                    message: format!("unexpected selector part '{}' in '{}'", part, input),
+                });
+            }
+        }
+    }
+
+    Ok(compound)
+}
+
+fn parse_attribute_selector(body: &str) -> AttributeSelector {
+    let ops = ["~=", "^=", "$=", "*=", "="];
+    for op in ops {
+        if let Some((name, value)) = body.split_once(op) {
+            let value = value.trim_matches(['"', '\'']).to_string();
+            let op = match op {
+                "~=" => AttributeOp::Includes(value),
+                "^=" => AttributeOp::StartsWith(value),
+                "$=" => AttributeOp::EndsWith(value),
+                "*=" => AttributeOp::Contains(value),
+                "=" => AttributeOp::Exact(value),
+                _ => unreachable!(),
+            };
+            return AttributeSelector {
+                name: name.trim().to_string(),
+                op,
+            };
+        }
+    }
+
+    AttributeSelector {
+        name: body.trim().to_string(),
+        op: AttributeOp::Present,
+    }
+}
+
+impl XpathItemTree {
+    /// Compile `selector` and return the matching [`XpathItemTreeNode`]s, in
+    /// document order with duplicates removed.
+    ///
+    /// This is a convenience wrapper around [`Selector::parse`] +
+    /// [`Selector::select`] for callers who don't need to reuse a compiled
+    /// selector across multiple documents.
+    pub fn select_css<'tree>(
+        &'tree self,
+        selector: &str,
+    ) -> Result<Vec<XpathItemTreeNode<'tree>>, CssSelectorParseError> {
+        Ok(Selector::parse(selector)?.select(self))
+    }
+}
+
+impl<'a> XpathItemTreeNode<'a> {
+    /// Check whether this node matches a CSS selector.
+    pub fn matches_css(
+        &self,
+        selector: &str,
+        tree: &XpathItemTree,
+    ) -> Result<bool, CssSelectorParseError> {
+        Ok(Selector::parse(selector)?.matches(self, tree))
+    }
+}
+
+impl Display for Selector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} selector(s)", self.selector_list.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn attribute_includes_selector_is_not_mistaken_for_a_combinator() {
+        // arrange
+        let input = r#"a[class~="foo"]"#;
+
+        // act
+        let selector = Selector::parse(input).unwrap();
+
+        // assert
+        assert_eq!(selector.selector_list.len(), 1);
+        let sequence = &selector.selector_list[0];
+        assert_eq!(sequence.steps.len(), 1);
+        assert!(sequence.combinators.is_empty());
+        let compound = &sequence.steps[0];
+        assert_eq!(compound.type_name.as_deref(), Some("a"));
+        assert_eq!(compound.attributes.len(), 1);
+        assert_eq!(compound.attributes[0].name, "class");
+        assert_eq!(
+            compound.attributes[0].op,
+            AttributeOp::Includes("foo".to_string())
+        );
+    }
+
+    #[test]
+    fn combinator_outside_brackets_still_splits() {
+        // arrange
+        let input = "div > p";
+
+        // act
+        let selector = Selector::parse(input).unwrap();
+
+        // assert
+        let sequence = &selector.selector_list[0];
+        assert_eq!(sequence.steps.len(), 2);
+        assert_eq!(sequence.combinators, vec![Combinator::Child]);
+    }
+
+    #[test]
+    fn tilde_combinator_still_works_alongside_bracket_tilde() {
+        // arrange
+        let input = r#"a[class~="foo"] ~ b"#;
+
+        // act
+        let selector = Selector::parse(input).unwrap();
+
+        // assert
+        let sequence = &selector.selector_list[0];
+        assert_eq!(sequence.steps.len(), 2);
+        assert_eq!(sequence.combinators, vec![Combinator::SubsequentSibling]);
+        assert_eq!(sequence.steps[0].attributes[0].name, "class");
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use indextree::{Arena, NodeId};
 use thiserror::Error;
 
@@ -16,6 +18,67 @@ use crate::xpath::{
 pub struct DocumentBuilderError {
     message: String,
 }
+
+impl DocumentBuilderError {
+    pub(crate) fn new(message: impl Into<String>) -> Self {
+        DocumentBuilderError {
+            message: message.into(),
+        }
+    }
+}
+
+/// A required-children schema: `schema(tag)` returns the tag names that
+/// must appear among `tag`'s immediate element children, checked by
+/// [`DocumentBuilder::with_validation`].
+pub type Schema = fn(tag: &str) -> &'static [&'static str];
+
+/// The schema [`DocumentBuilder::with_validation`] is most often called
+/// with: `html` requires `head` and `body`; `head` requires `title`.
+pub fn default_schema(tag: &str) -> &'static [&'static str] {
+    match tag {
+        "html" => &["head", "body"],
+        "head" => &["title"],
+        _ => &[],
+    }
+}
+
+/// Check `element_id` and its descendants against `schema`, returning the
+/// first missing required child found.
+fn check_required_children(
+    element_id: NodeId,
+    arena: &Arena<XpathItemTreeNode>,
+    schema: Schema,
+) -> Result<(), DocumentBuilderError> {
+    if let XpathItemTreeNode::ElementNode(element) = arena.get(element_id).unwrap().get() {
+        let required = schema(&element.name);
+
+        if !required.is_empty() {
+            let child_tags: Vec<&str> = element_id
+                .children(arena)
+                .filter_map(|child_id| match arena.get(child_id).unwrap().get() {
+                    XpathItemTreeNode::ElementNode(child) => Some(child.name.as_str()),
+                    _ => None,
+                })
+                .collect();
+
+            for &tag in required {
+                if !child_tags.contains(&tag) {
+                    return Err(DocumentBuilderError::new(format!(
+                        "<{}> is missing required child <{}>",
+                        element.name, tag
+                    )));
+                }
+            }
+        }
+    }
+
+    for child_id in element_id.children(arena) {
+        check_required_children(child_id, arena, schema)?;
+    }
+
+    Ok(())
+}
+
 pub struct DocumentBuilder {
     arena: Arena<XpathItemTreeNode>,
     funcs: Vec<
@@ -26,6 +89,8 @@ pub struct DocumentBuilder {
             ) -> Result<NodeId, DocumentBuilderError>,
         >,
     >,
+    schema: Option<Schema>,
+    namespaces: HashMap<String, String>,
 }
 
 impl DocumentBuilder {
@@ -33,20 +98,81 @@ impl DocumentBuilder {
         Self {
             arena: Arena::new(),
             funcs: Vec::new(),
+            schema: None,
+            namespaces: HashMap::new(),
         }
     }
 
+    /// Validate the built document against `schema` before returning it
+    /// from [`Self::build`], failing with a [`DocumentBuilderError`]
+    /// instead of silently producing a document missing mandatory
+    /// elements. See [`default_schema`] for the schema most callers want.
+    pub fn with_validation(mut self, schema: Schema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Bind `prefix` to `uri`, as an `xmlns:prefix="uri"` attribute would,
+    /// so later [`Self::add_element_ns`] calls can resolve it. Namespaces
+    /// can also be declared inline via
+    /// [`ElementBuilder::add_attribute_str`] with an `xmlns`/`xmlns:prefix`
+    /// name.
+    pub fn declare_namespace(mut self, prefix: &str, uri: &str) -> Self {
+        self.namespaces.insert(prefix.to_string(), uri.to_string());
+        self
+    }
+
     pub fn add_element(
         mut self,
         tag_name: &str,
         f: impl FnOnce(ElementBuilder) -> ElementBuilder + 'static,
     ) -> Self {
         let tag_name = tag_name.to_string();
+        let namespaces = self.namespaces.clone();
         self.funcs.push(Box::new(move |arena, parent_id| {
             f(ElementBuilder::new(
                 tag_name.clone(),
                 Some(parent_id),
                 arena,
+                namespaces,
+            ))
+            .build()
+        }));
+
+        self
+    }
+
+    /// Like [`Self::add_element`], but `tag_name` is `prefix:local_name`
+    /// resolved against the namespaces declared so far via
+    /// [`Self::declare_namespace`]; `xmlns:prefix` attributes are not
+    /// consulted.
+    ///
+    /// Fails at [`Self::build`] time if `prefix` has no visible
+    /// declaration.
+    pub fn add_element_ns(
+        mut self,
+        prefix: &str,
+        local_name: &str,
+        f: impl FnOnce(ElementBuilder) -> ElementBuilder + 'static,
+    ) -> Self {
+        let qualified_name = format!("{}:{}", prefix, local_name);
+        let local_name = local_name.to_string();
+        let namespace_uri = self.namespaces.get(prefix).cloned();
+        let prefix = prefix.to_string();
+        let namespaces = self.namespaces.clone();
+
+        self.funcs.push(Box::new(move |arena, parent_id| {
+            let namespace_uri = namespace_uri.ok_or_else(|| {
+                DocumentBuilderError::new(format!("no namespace declared for prefix `{}`", prefix))
+            })?;
+
+            f(ElementBuilder::new_ns(
+                qualified_name,
+                local_name,
+                namespace_uri,
+                Some(parent_id),
+                arena,
+                namespaces,
             ))
             .build()
         }));
@@ -84,6 +210,12 @@ impl DocumentBuilder {
             document_node_id.append(child_id, &mut self.arena);
         }
 
+        if let Some(schema) = self.schema {
+            for child_id in document_node_id.children(&self.arena) {
+                check_required_children(child_id, &self.arena, schema)?;
+            }
+        }
+
         let document = XpathItemTree::new(self.arena, document_node_id);
 
         Ok(document)
@@ -102,6 +234,9 @@ pub struct ElementBuilder<'arena> {
         >,
     >,
     tag_name: String,
+    local_name: Option<String>,
+    namespace_uri: Option<String>,
+    namespaces: HashMap<String, String>,
 }
 
 impl<'arena> ElementBuilder<'arena> {
@@ -109,12 +244,41 @@ impl<'arena> ElementBuilder<'arena> {
         tag_name: String,
         parent_id: Option<NodeId>,
         arena: &'arena mut Arena<XpathItemTreeNode>,
+        namespaces: HashMap<String, String>,
     ) -> Self {
         Self {
             parent_id,
             arena,
             funcs: Vec::new(),
             tag_name: tag_name.to_string(),
+            local_name: None,
+            namespace_uri: None,
+            namespaces,
+        }
+    }
+
+    /// Like [`Self::new`], but for an element resolved from a
+    /// `prefix:local_name` by [`DocumentBuilder::add_element_ns`]/
+    /// [`Self::add_element_ns`]. `name` is the qualified `prefix:local_name`
+    /// stored on the built [`ElementNode`], with `local_name` and
+    /// `namespace_uri` additionally recorded so XPath queries can match on
+    /// namespace.
+    fn new_ns(
+        name: String,
+        local_name: String,
+        namespace_uri: String,
+        parent_id: Option<NodeId>,
+        arena: &'arena mut Arena<XpathItemTreeNode>,
+        namespaces: HashMap<String, String>,
+    ) -> Self {
+        Self {
+            parent_id,
+            arena,
+            funcs: Vec::new(),
+            tag_name: name,
+            local_name: Some(local_name),
+            namespace_uri: Some(namespace_uri),
+            namespaces,
         }
     }
 
@@ -124,11 +288,49 @@ impl<'arena> ElementBuilder<'arena> {
         f: impl FnOnce(ElementBuilder) -> ElementBuilder + 'static,
     ) -> Self {
         let tag_name = tag_name.to_string();
+        let namespaces = self.namespaces.clone();
         self.funcs.push(Box::new(move |arena, parent_id| {
             f(ElementBuilder::new(
                 tag_name.clone(),
                 Some(parent_id),
                 arena,
+                namespaces,
+            ))
+            .build()
+        }));
+
+        self
+    }
+
+    /// Like [`Self::add_element`], but `tag_name` is `prefix:local_name`
+    /// resolved against the namespaces in scope here (declared on an
+    /// ancestor, or via an `xmlns:prefix` attribute added to this element
+    /// earlier in the chain). Fails at [`DocumentBuilder::build`] time if
+    /// `prefix` has no visible declaration.
+    pub fn add_element_ns(
+        mut self,
+        prefix: &str,
+        local_name: &str,
+        f: impl FnOnce(ElementBuilder) -> ElementBuilder + 'static,
+    ) -> Self {
+        let qualified_name = format!("{}:{}", prefix, local_name);
+        let local_name = local_name.to_string();
+        let namespace_uri = self.namespaces.get(prefix).cloned();
+        let prefix = prefix.to_string();
+        let namespaces = self.namespaces.clone();
+
+        self.funcs.push(Box::new(move |arena, parent_id| {
+            let namespace_uri = namespace_uri.ok_or_else(|| {
+                DocumentBuilderError::new(format!("no namespace declared for prefix `{}`", prefix))
+            })?;
+
+            f(ElementBuilder::new_ns(
+                qualified_name,
+                local_name,
+                namespace_uri,
+                Some(parent_id),
+                arena,
+                namespaces,
             ))
             .build()
         }));
@@ -145,9 +347,53 @@ impl<'arena> ElementBuilder<'arena> {
     }
 
     pub fn add_attribute_str(mut self, name: &str, value: &str) -> Self {
+        if let Some(prefix) = name.strip_prefix("xmlns:") {
+            self.namespaces
+                .insert(prefix.to_string(), value.to_string());
+        } else if name == "xmlns" {
+            self.namespaces.insert(String::new(), value.to_string());
+        }
+
         self.add_attribute(AttributeNode::new(name.to_string(), value.to_string()))
     }
 
+    /// Like [`Self::add_attribute_str`], but `name` is `prefix:local_name`
+    /// resolved against the namespaces in scope here, the same way
+    /// [`Self::add_element_ns`] resolves element names. Stores the
+    /// qualified name on the built [`AttributeNode`]; this crate's
+    /// [`AttributeNode`] has no separate namespace-URI field to record the
+    /// resolution on, so, as with element names elsewhere in this tree,
+    /// resolving it back to a URI means splitting the qualified name again.
+    pub fn add_attribute_ns(mut self, prefix: &str, local_name: &str, value: &str) -> Self {
+        let qualified_name = format!("{}:{}", prefix, local_name);
+        let value = value.to_string();
+        let namespace_uri = self.namespaces.get(prefix).cloned();
+        let prefix = prefix.to_string();
+
+        self.funcs.push(Box::new(move |arena, _| {
+            namespace_uri.ok_or_else(|| {
+                DocumentBuilderError::new(format!("no namespace declared for prefix `{}`", prefix))
+            })?;
+
+            let child_id = arena.new_node(XpathItemTreeNode::AttributeNode(AttributeNode::new(
+                qualified_name,
+                value,
+            )));
+
+            arena
+                .get_mut(child_id)
+                .unwrap()
+                .get_mut()
+                .as_attribute_node_mut()
+                .unwrap()
+                .set_id(child_id);
+
+            Ok(child_id)
+        }));
+
+        self
+    }
+
     pub fn add_attribute(mut self, attribute: AttributeNode) -> Self {
         self.funcs.push(Box::new(move |arena, _| {
             let child_id = arena.new_node(XpathItemTreeNode::AttributeNode(attribute));
@@ -212,13 +458,19 @@ impl<'arena> ElementBuilder<'arena> {
                 self.tag_name,
             )));
 
-        self.arena
+        let element_node = self
+            .arena
             .get_mut(element_id)
             .unwrap()
             .get_mut()
             .as_element_node_mut()
-            .unwrap()
-            .set_id(element_id);
+            .unwrap();
+        element_node.set_id(element_id);
+
+        if let Some(local_name) = self.local_name {
+            element_node.local_name = local_name;
+            element_node.namespace_uri = self.namespace_uri;
+        }
 
         for func in self.funcs {
             let child_id = func(&mut self.arena, element_id)?;
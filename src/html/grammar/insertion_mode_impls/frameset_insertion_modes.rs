@@ -0,0 +1,206 @@
+use indextree::NodeId;
+
+use super::{
+    super::tokenizer::{HtmlToken, TagTokenType},
+    chars,
+    tree_sink::TreeSink,
+    Acknowledgement, HtmlParseError, HtmlParser, HtmlParserError, InsertionMode,
+};
+
+impl<S: TreeSink<Handle = NodeId> + Default> HtmlParser<S> {
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inframeset>
+    pub(super) fn in_frameset_insertion_mode(
+        &mut self,
+        token: HtmlToken,
+    ) -> Result<Acknowledgement, HtmlParseError> {
+        match token {
+            HtmlToken::Character(c)
+                if [
+                    chars::CHARACTER_TABULATION,
+                    chars::LINE_FEED,
+                    chars::FORM_FEED,
+                    chars::CARRIAGE_RETURN,
+                    chars::SPACE,
+                ]
+                .contains(&c) =>
+            {
+                self.insert_character(vec![c])?;
+            }
+            HtmlToken::Comment(comment) => {
+                self.insert_a_comment(comment, None)?;
+            }
+            HtmlToken::DocType(_) => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "unexpected doctype in frameset",
+                )))?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "html" => {
+                self.using_the_rules_for(
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    InsertionMode::InBody,
+                )?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "frameset" => {
+                self.insert_an_html_element(token)?;
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "frameset" => {
+                if self
+                    .current_node_as_element()
+                    .is_some_and(|element| element.name == "html")
+                {
+                    self.handle_error(HtmlParserError::MinorError(String::from(
+                        "frameset end tag with the html element as the current node",
+                    )))?;
+                    return Ok(Acknowledgement::no());
+                }
+
+                self.open_elements.pop();
+
+                if self.context_element.is_none()
+                    && self
+                        .current_node_as_element()
+                        .is_some_and(|element| element.name != "frameset")
+                {
+                    self.insertion_mode = InsertionMode::AfterFrameset;
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "frame" => {
+                self.insert_an_html_element(token)?;
+                self.open_elements.pop();
+
+                return Ok(Acknowledgement::yes());
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "noframes" => {
+                self.using_the_rules_for(
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    InsertionMode::InHead,
+                )?;
+            }
+            HtmlToken::EndOfFile => {
+                if self
+                    .current_node_as_element()
+                    .is_some_and(|element| element.name != "html")
+                {
+                    self.handle_error(HtmlParserError::MinorError(String::from(
+                        "unexpected end of file in frameset",
+                    )))?;
+                }
+
+                self.stop_parsing()?;
+            }
+            _ => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "unexpected token in frameset",
+                )))?;
+            }
+        }
+
+        Ok(Acknowledgement::no())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-afterframeset>
+    pub(super) fn after_frameset_insertion_mode(
+        &mut self,
+        token: HtmlToken,
+    ) -> Result<Acknowledgement, HtmlParseError> {
+        match token {
+            HtmlToken::Character(c)
+                if [
+                    chars::CHARACTER_TABULATION,
+                    chars::LINE_FEED,
+                    chars::FORM_FEED,
+                    chars::CARRIAGE_RETURN,
+                    chars::SPACE,
+                ]
+                .contains(&c) =>
+            {
+                self.insert_character(vec![c])?;
+            }
+            HtmlToken::Comment(comment) => {
+                self.insert_a_comment(comment, None)?;
+            }
+            HtmlToken::DocType(_) => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "unexpected doctype after frameset",
+                )))?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "html" => {
+                self.using_the_rules_for(
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    InsertionMode::InBody,
+                )?;
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "html" => {
+                self.open_elements.pop();
+
+                self.insertion_mode = InsertionMode::AfterAfterFrameset;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "noframes" => {
+                self.using_the_rules_for(
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    InsertionMode::InHead,
+                )?;
+            }
+            HtmlToken::EndOfFile => {
+                self.stop_parsing()?;
+            }
+            _ => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "unexpected token after frameset",
+                )))?;
+            }
+        }
+
+        Ok(Acknowledgement::no())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#the-after-after-frameset-insertion-mode>
+    pub(super) fn after_after_frameset_insertion_mode(
+        &mut self,
+        token: HtmlToken,
+    ) -> Result<Acknowledgement, HtmlParseError> {
+        match token {
+            HtmlToken::Comment(comment) => {
+                let parent = self
+                    .root_node
+                    .ok_or(HtmlParseError::new("root node is None"))?;
+
+                self.insert_a_comment(comment, Some(parent))?;
+            }
+            HtmlToken::DocType(_) => {
+                self.using_the_rules_for(token, InsertionMode::InBody)?;
+            }
+            HtmlToken::Character(
+                chars::CHARACTER_TABULATION
+                | chars::LINE_FEED
+                | chars::FORM_FEED
+                | chars::CARRIAGE_RETURN
+                | chars::SPACE,
+            ) => {
+                self.using_the_rules_for(token, InsertionMode::InBody)?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "html" => {
+                self.using_the_rules_for(
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    InsertionMode::InBody,
+                )?;
+            }
+            HtmlToken::EndOfFile => {
+                self.stop_parsing()?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "noframes" => {
+                self.using_the_rules_for(
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    InsertionMode::InHead,
+                )?;
+            }
+            _ => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "unexpected token after after frameset",
+                )))?;
+            }
+        }
+
+        Ok(Acknowledgement::no())
+    }
+}
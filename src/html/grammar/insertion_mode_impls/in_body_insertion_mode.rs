@@ -1,27 +1,30 @@
 use indextree::NodeId;
 
 use crate::{
-    html::grammar::{tokenizer::TokenizerState, NodeOrMarker, SPECIAL_ELEMENTS, SVG_NAMESPACE},
+    html::grammar::{NodeOrMarker, SPECIAL_ELEMENTS, MATHML_NAMESPACE, SVG_NAMESPACE},
     xpath::grammar::{
         data_model::{AttributeNode, ElementNode},
-        XpathItemTreeNode,
+        QuirksMode, XpathItemTreeNode,
     },
 };
 
 use super::{
-    super::tokenizer::{HtmlToken, Parser, TagToken, TagTokenType},
-    chars, Acknowledgement, HtmlParseError, HtmlParser, HtmlParserError, InsertionMode,
+    super::tokenizer::{HtmlToken, TagToken, TagTokenType, TokenizerState},
+    chars,
+    foreign_content::{adjust_foreign_attributes, adjust_mathml_attributes, adjust_svg_attributes},
+    tree_sink::TreeSink,
+    Acknowledgement, HtmlParseError, HtmlParser, HtmlParserError, InsertionMode, ProcessResult,
     HTML_NAMESPACE,
 };
 
-impl HtmlParser {
+impl<S: TreeSink<Handle = NodeId> + Default> HtmlParser<S> {
     /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inbody>
     pub(crate) fn in_body_insertion_mode(
         &mut self,
         token: HtmlToken,
     ) -> Result<Acknowledgement, HtmlParseError> {
-        fn ensure_open_elements_has_valid_element(
-            parser: &HtmlParser,
+        fn ensure_open_elements_has_valid_element<S: TreeSink<Handle = NodeId> + Default>(
+            parser: &mut HtmlParser<S>,
         ) -> Result<(), HtmlParseError> {
             let valid_elements = vec![
                 "dd", "dt", "li", "optgroup", "option", "p", "rb", "rp", "rt", "rtc", "tbody",
@@ -31,7 +34,7 @@ impl HtmlParser {
             if !parser
                 .open_elements
                 .iter()
-                .map(|node_id| parser.arena.get(*node_id).unwrap().get())
+                .map(|node_id| parser.sink.arena().get(*node_id).unwrap().get())
                 .filter_map(|node| node.as_element_node().ok())
                 .any(|node| valid_elements.contains(&node.name.as_str()))
             {
@@ -43,6 +46,14 @@ impl HtmlParser {
             Ok(())
         }
 
+        if self.ignore_next_line_feed {
+            self.ignore_next_line_feed = false;
+
+            if matches!(token, HtmlToken::Character(chars::LINE_FEED)) {
+                return Ok(Acknowledgement::no());
+            }
+        }
+
         match token {
             HtmlToken::Character(chars::NULL) => {
                 todo!()
@@ -72,7 +83,9 @@ impl HtmlParser {
                 self.insert_a_comment(comment, None)?;
             }
             HtmlToken::DocType(_) => {
-                todo!()
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "unexpected DOCTYPE token in the body",
+                )))?;
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "html" => {
                 self.handle_error(HtmlParserError::MinorError(String::from(
@@ -104,7 +117,7 @@ impl HtmlParser {
                 };
 
                 let top_element_attrs = top_element
-                    .attributes_arena(&self.arena)
+                    .attributes_arena(self.sink.arena())
                     .into_iter()
                     .map(|attr| attr.name.to_string())
                     .collect::<Vec<String>>();
@@ -117,7 +130,7 @@ impl HtmlParser {
                         let attr_node_id = self.new_node(XpathItemTreeNode::AttributeNode(
                             AttributeNode::new(attribute.name, attribute.value),
                         ));
-                        top_node_id.append(attr_node_id, &mut self.arena);
+                        top_node_id.append(attr_node_id, self.sink.arena_mut());
                     }
                 }
             }
@@ -145,19 +158,48 @@ impl HtmlParser {
                         "open elements has no body element in scope",
                     )))?;
                 } else {
-                    ensure_open_elements_has_valid_element(&self)?;
+                    ensure_open_elements_has_valid_element(self)?;
                 }
 
                 self.insertion_mode = InsertionMode::AfterBody;
             }
+            // in_frameset_insertion_mode/after_frameset_insertion_mode/
+            // after_after_frameset_insertion_mode (frameset_insertion_modes.rs)
+            // and after_head_insertion_mode's own frameset start-tag arm are
+            // already fully implemented; this is the frameset_ok gate that
+            // gives InBody a way to still switch into InFrameset.
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "frameset" => {
-                todo!()
+                let second_element_is_body = self
+                    .open_elements
+                    .get(1)
+                    .and_then(|id| self.sink.arena().get(*id))
+                    .and_then(|node| node.get().as_element_node().ok())
+                    .is_some_and(|element| element.name == "body");
+
+                if self.open_elements.len() == 1 || !second_element_is_body || !self.frameset_ok {
+                    self.handle_error(HtmlParserError::MinorError(String::from(
+                        "unexpected frameset start tag",
+                    )))?;
+                    return Ok(Acknowledgement::no());
+                }
+
+                if let Some(second_element_id) = self.open_elements.get(1).copied() {
+                    second_element_id.detach(self.sink.arena_mut());
+                }
+
+                while self.open_elements.len() > 1 {
+                    self.open_elements.pop();
+                }
+
+                self.insert_an_html_element(token)?;
+
+                self.insertion_mode = InsertionMode::InFrameset;
             }
             HtmlToken::EndOfFile => {
                 if !self.template_insertion_modes.is_empty() {
                     self.using_the_rules_for(token, InsertionMode::InTemplate)?;
                 } else {
-                    ensure_open_elements_has_valid_element(&self)?;
+                    ensure_open_elements_has_valid_element(self)?;
                     self.stop_parsing()?;
                 }
             }
@@ -167,7 +209,7 @@ impl HtmlParser {
                         "open elements has body element in scope",
                     )))?;
                 } else {
-                    ensure_open_elements_has_valid_element(&self)?;
+                    ensure_open_elements_has_valid_element(self)?;
                 }
 
                 self.insertion_mode = InsertionMode::AfterBody;
@@ -178,12 +220,15 @@ impl HtmlParser {
                         "open elements has body element in scope",
                     )))?;
                 } else {
-                    ensure_open_elements_has_valid_element(&self)?;
+                    ensure_open_elements_has_valid_element(self)?;
                 }
 
                 self.insertion_mode = InsertionMode::AfterBody;
 
-                self.token_emitted(HtmlToken::TagToken(TagTokenType::EndTag(token)))?;
+                self.pending_process_result = ProcessResult::Reprocess(
+                    self.insertion_mode,
+                    HtmlToken::TagToken(TagTokenType::EndTag(token)),
+                );
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token))
                 if [
@@ -221,6 +266,7 @@ impl HtmlParser {
 
                 self.insert_an_html_element(token)?;
             }
+            // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inbody (h1-h6 start tag)
             HtmlToken::TagToken(TagTokenType::StartTag(token))
                 if ["h1", "h2", "h3", "h4", "h5", "h6"].contains(&token.tag_name.as_str()) =>
             {
@@ -242,7 +288,13 @@ impl HtmlParser {
             HtmlToken::TagToken(TagTokenType::StartTag(token))
                 if ["pre", "listing"].contains(&token.tag_name.as_str()) =>
             {
-                todo!()
+                if self.has_an_element_in_button_scope("p") {
+                    self.close_a_p_element()?;
+                }
+
+                self.insert_an_html_element(token)?;
+                self.frameset_ok = false;
+                self.ignore_next_line_feed = true;
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "form" => {
                 if self.form_element_pointer.is_some()
@@ -264,8 +316,8 @@ impl HtmlParser {
                 }
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "li" => {
-                fn step_3_loop(
-                    parser: &mut HtmlParser,
+                fn step_3_loop<S: TreeSink<Handle = NodeId> + Default>(
+                    parser: &mut HtmlParser<S>,
                     element: &ElementNode,
                     token: TagToken,
                 ) -> Result<(), HtmlParseError> {
@@ -298,7 +350,8 @@ impl HtmlParser {
                             .expect("previous element is not in open elements");
 
                         let previous_element = parser
-                            .arena
+                            .sink
+                            .arena()
                             .get(*previous_element_id)
                             .unwrap()
                             .get()
@@ -325,8 +378,8 @@ impl HtmlParser {
                     Ok(())
                 }
 
-                fn step_6_done(
-                    parser: &mut HtmlParser,
+                fn step_6_done<S: TreeSink<Handle = NodeId> + Default>(
+                    parser: &mut HtmlParser<S>,
                     token: TagToken,
                 ) -> Result<(), HtmlParseError> {
                     if parser.has_an_element_in_button_scope("p") {
@@ -346,10 +399,101 @@ impl HtmlParser {
             HtmlToken::TagToken(TagTokenType::StartTag(token))
                 if ["dd", "dt"].contains(&token.tag_name.as_str()) =>
             {
-                todo!()
+                fn step_loop<S: TreeSink<Handle = NodeId> + Default>(
+                    parser: &mut HtmlParser<S>,
+                    element: &ElementNode,
+                    token: TagToken,
+                ) -> Result<(), HtmlParseError> {
+                    if ["dd", "dt"].contains(&element.name.as_str()) {
+                        parser.generate_implied_end_tags(Some(&element.name))?;
+
+                        if parser.current_node_as_element_result()?.name != element.name {
+                            parser.handle_error(HtmlParserError::MinorError(String::from(
+                                "current node is not the same as the matched dd/dt element",
+                            )))?;
+                        }
+
+                        parser.pop_until_tag_name(&element.name)?;
+                    }
+
+                    if SPECIAL_ELEMENTS.contains(&element.name.as_str())
+                        && !["address", "div", "p"].contains(&element.name.as_str())
+                    {
+                        step_done(parser, token)?;
+                    } else {
+                        let current_element_index = parser
+                            .open_elements
+                            .iter()
+                            .position(|node_id| node_id == &element.id())
+                            .expect("current element is not in open elements");
+
+                        let previous_element_id = parser
+                            .open_elements
+                            .get(current_element_index - 1)
+                            .expect("previous element is not in open elements");
+
+                        let previous_element = parser
+                            .sink
+                            .arena()
+                            .get(*previous_element_id)
+                            .unwrap()
+                            .get()
+                            .as_element_node()
+                            .map_err(|_| {
+                                HtmlParserError::MinorError(String::from(
+                                    "previous element is not an element node",
+                                ))
+                            });
+
+                        match previous_element {
+                            Err(_) => {
+                                parser.handle_error(HtmlParserError::MinorError(String::from(
+                                    "previous element is not an element node",
+                                )))?;
+                            }
+                            Ok(previous_element) => {
+                                let previous_element = previous_element.clone();
+                                return step_loop(parser, &previous_element, token);
+                            }
+                        }
+                    }
+
+                    Ok(())
+                }
+
+                fn step_done<S: TreeSink<Handle = NodeId> + Default>(
+                    parser: &mut HtmlParser<S>,
+                    token: TagToken,
+                ) -> Result<(), HtmlParseError> {
+                    if parser.has_an_element_in_button_scope("p") {
+                        parser.close_a_p_element()?;
+                    }
+
+                    parser.insert_an_html_element(token)?;
+
+                    Ok(())
+                }
+
+                self.frameset_ok = false;
+
+                let node = self.current_node_as_element_result()?.clone();
+                step_loop(self, &node, token)?;
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "plaintext" => {
-                todo!()
+                if self.has_an_element_in_button_scope("p") {
+                    self.close_a_p_element()?;
+                }
+
+                self.insert_an_html_element(token)?;
+
+                // Switching the tokenizer to PLAINTEXT here is final: that
+                // state never transitions back to data, not even on `<`, so
+                // the rest of the document is consumed verbatim as
+                // character data.
+                return Ok(Acknowledgement {
+                    self_closed: false,
+                    tokenizer_state: Some(TokenizerState::PLAINTEXT),
+                });
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "button" => {
                 if self.has_an_element_in_scope("button") {
@@ -422,7 +566,8 @@ impl HtmlParser {
                     match node {
                         Some(node) => {
                             let element = self
-                                .arena
+                                .sink
+                                .arena()
                                 .get(node)
                                 .expect("form element pointer is none")
                                 .get()
@@ -505,7 +650,23 @@ impl HtmlParser {
             HtmlToken::TagToken(TagTokenType::EndTag(token))
                 if ["dd", "dt"].contains(&token.tag_name.as_str()) =>
             {
-                todo!()
+                if !self.has_an_element_in_scope(&token.tag_name) {
+                    self.handle_error(HtmlParserError::MinorError(format!(
+                        "open elements has no {} element in scope",
+                        token.tag_name
+                    )))?;
+                } else {
+                    self.generate_implied_end_tags(Some(&token.tag_name))?;
+
+                    if self.current_node_as_element().unwrap().name != token.tag_name {
+                        self.handle_error(HtmlParserError::MinorError(format!(
+                            "current node is not {}",
+                            token.tag_name
+                        )))?;
+                    }
+
+                    self.pop_until_tag_name(&token.tag_name)?;
+                }
             }
             HtmlToken::TagToken(TagTokenType::EndTag(token))
                 if ["h1", "h2", "h3", "h4", "h5", "h6"].contains(&token.tag_name.as_str()) =>
@@ -531,7 +692,7 @@ impl HtmlParser {
             }
             HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "sarcasm" => {
                 // "Take a deep breath, then act as described in the 'any other end tag' entry below." lol
-                todo!()
+                self.other_end_tag(&token)?;
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "a" => {
                 // if the active formatting element list contains an `a` element between the end of the list and the last marker
@@ -549,7 +710,7 @@ impl HtmlParser {
                     })
                     .any(|entry| {
                         if let XpathItemTreeNode::ElementNode(element) =
-                            self.arena.get(entry.node_id).unwrap().get()
+                            self.sink.arena().get(entry.node_id).unwrap().get()
                         {
                             element.name == "a"
                         } else {
@@ -570,12 +731,15 @@ impl HtmlParser {
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token))
                 if [
-                    "a", "b", "big", "code", "em", "font", "i", "s", "small", "strike", "strong",
-                    "tt", "u",
+                    "b", "big", "code", "em", "font", "i", "s", "small", "strike", "strong", "tt",
+                    "u",
                 ]
                 .contains(&token.tag_name.as_str()) =>
             {
-                self.adoption_agency_algorithm(&token)?;
+                self.reconstruct_the_active_formatting_elements()?;
+
+                let element_id = self.insert_an_html_element(token.clone())?;
+                self.push_onto_the_list_of_active_formatting_elements(element_id, token)?;
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "nobr" => {
                 todo!()
@@ -600,7 +764,15 @@ impl HtmlParser {
                 todo!()
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "table" => {
-                todo!()
+                if self.quirks_mode != QuirksMode::Quirks
+                    && self.has_an_element_in_button_scope("p")
+                {
+                    self.close_a_p_element()?;
+                }
+
+                self.insert_an_html_element(token)?;
+                self.frameset_ok = false;
+                self.insertion_mode = InsertionMode::InTable;
             }
             HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "br" => {
                 self.handle_error(HtmlParserError::MinorError(String::from(
@@ -658,65 +830,166 @@ impl HtmlParser {
             HtmlToken::TagToken(TagTokenType::StartTag(token))
                 if ["param", "source", "track"].contains(&token.tag_name.as_str()) =>
             {
-                todo!()
+                let self_closing = token.self_closing;
+                self.insert_an_html_element(token)?;
+                self.open_elements.pop();
+
+                if self_closing {
+                    return Ok(Acknowledgement::yes());
+                }
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "hr" => {
-                todo!()
-            }
-            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "image" => {
-                todo!()
-            }
-            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "textarea" => {
+                if self.has_an_element_in_button_scope("p") {
+                    self.close_a_p_element()?;
+                }
+
+                let self_closing = token.self_closing;
                 self.insert_an_html_element(token)?;
+                self.open_elements.pop();
 
-                // TODO: if next token is line feed character token, ignore it
+                self.frameset_ok = false;
+
+                if self_closing {
+                    return Ok(Acknowledgement::yes());
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(mut token)) if token.tag_name == "image" => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "image start tag in body (use img instead)",
+                )))?;
 
-                self.original_insertion_mode = Some(self.insertion_mode);
-                self.insertion_mode = InsertionMode::Text;
+                token.tag_name = String::from("img");
+                self.pending_process_result = ProcessResult::Reprocess(
+                    self.insertion_mode,
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                );
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "textarea" => {
                 self.frameset_ok = false;
+                self.ignore_next_line_feed = true;
 
-                return Ok(Acknowledgement {
-                    self_closed: false,
-                    tokenizer_state: Some(TokenizerState::RCDATA),
-                });
+                return self.generic_rcdata_element_parsing_algorithm(token);
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "xmp" => {
-                todo!()
+                if self.has_an_element_in_button_scope("p") {
+                    self.close_a_p_element()?;
+                }
+
+                self.reconstruct_the_active_formatting_elements()?;
+                self.frameset_ok = false;
+
+                return self.generic_raw_text_element_parsing_algorithm(token);
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "iframe" => {
-                todo!()
+                self.frameset_ok = false;
+
+                return self.generic_raw_text_element_parsing_algorithm(token);
             }
-            HtmlToken::TagToken(TagTokenType::StartTag(token))
-                if ["noembed", "noscript"].contains(&token.tag_name.as_str()) =>
-            {
-                todo!()
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "noembed" => {
+                return self.generic_raw_text_element_parsing_algorithm(token);
             }
+            // The spec only routes `noscript` through the generic raw text
+            // algorithm when the scripting flag is enabled. This parser
+            // never executes scripts, so (as with `InHead`'s `noscript`
+            // handling) it falls through to ordinary element insertion.
+            // `InSelect` and `InSelectInTable` themselves, along with their
+            // dispatch arms in `token_emitted`, already live in
+            // `select_insertion_modes.rs` (`in_select_insertion_mode`,
+            // `in_select_in_table_insertion_mode`, added by chunk3-7).
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "select" => {
-                todo!()
+                self.reconstruct_the_active_formatting_elements()?;
+
+                self.insert_an_html_element(token)?;
+
+                self.frameset_ok = false;
+
+                if [
+                    InsertionMode::InTable,
+                    InsertionMode::InCaption,
+                    InsertionMode::InTableBody,
+                    InsertionMode::InRow,
+                    InsertionMode::InCell,
+                ]
+                .contains(&self.insertion_mode)
+                {
+                    self.insertion_mode = InsertionMode::InSelectInTable;
+                } else {
+                    self.insertion_mode = InsertionMode::InSelect;
+                }
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token))
                 if ["optgroup", "option"].contains(&token.tag_name.as_str()) =>
             {
-                todo!()
+                if self
+                    .current_node_as_element()
+                    .is_some_and(|element| element.name == "option")
+                {
+                    self.open_elements.pop();
+                }
+
+                self.reconstruct_the_active_formatting_elements()?;
+
+                self.insert_an_html_element(token)?;
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token))
                 if ["rb", "rtc"].contains(&token.tag_name.as_str()) =>
             {
-                todo!()
+                if self.has_an_element_in_scope("ruby") {
+                    self.generate_implied_end_tags(None)?;
+
+                    if self
+                        .current_node_as_element()
+                        .is_some_and(|element| element.name != "ruby")
+                    {
+                        self.handle_error(HtmlParserError::MinorError(
+                            "current node is not ruby".to_string(),
+                        ))?;
+                    }
+                }
+
+                self.insert_an_html_element(token)?;
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token))
                 if ["rp", "rt"].contains(&token.tag_name.as_str()) =>
             {
-                todo!()
+                if self.has_an_element_in_scope("ruby") {
+                    self.generate_implied_end_tags(Some("rtc"))?;
+
+                    if self
+                        .current_node_as_element()
+                        .is_some_and(|element| element.name != "ruby" && element.name != "rtc")
+                    {
+                        self.handle_error(HtmlParserError::MinorError(
+                            "current node is not rtc or ruby".to_string(),
+                        ))?;
+                    }
+                }
+
+                self.insert_an_html_element(token)?;
             }
-            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "math" => {
-                todo!()
+            // The rest of the foreign-content subsystem (tag/attribute
+            // adjustment tables, self-closing handling, and the breakout
+            // dispatch for tokens already inside a foreign subtree) lives in
+            // `foreign_content.rs`; see its module docs for the full list.
+            HtmlToken::TagToken(TagTokenType::StartTag(mut token)) if token.tag_name == "math" => {
+                self.reconstruct_the_active_formatting_elements()?;
+
+                adjust_mathml_attributes(&mut token.attributes);
+                adjust_foreign_attributes(&mut token.attributes);
+
+                let self_closing = token.self_closing;
+                self.insert_foreign_element(token, MATHML_NAMESPACE, false)?;
+
+                if self_closing {
+                    self.open_elements.pop();
+                    return Ok(Acknowledgement::yes());
+                }
             }
-            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "svg" => {
+            HtmlToken::TagToken(TagTokenType::StartTag(mut token)) if token.tag_name == "svg" => {
                 self.reconstruct_the_active_formatting_elements()?;
 
-                // TODO: adjust SVG attribtues
-                // TODO: adjust foreign attributes
+                adjust_svg_attributes(&mut token.attributes);
+                adjust_foreign_attributes(&mut token.attributes);
 
                 let self_closing = token.self_closing;
                 self.insert_foreign_element(token, SVG_NAMESPACE, false)?;
@@ -733,7 +1006,12 @@ impl HtmlParser {
                 ]
                 .contains(&token.tag_name.as_str()) =>
             {
-                todo!()
+                // These tags only have meaning inside the table insertion
+                // modes; reaching InBody means they're misplaced.
+                self.handle_error(HtmlParserError::MinorError(format!(
+                    "unexpected {} start tag in body",
+                    token.tag_name
+                )))?;
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) => {
                 self.reconstruct_the_active_formatting_elements()?;
@@ -798,7 +1076,8 @@ impl HtmlParser {
             .skip(node_index)
             .next()
             .map(|node_id| {
-                self.arena
+                self.sink
+                    .arena()
                     .get(*node_id)
                     .expect("node not found")
                     .get()
@@ -814,14 +1093,30 @@ impl HtmlParser {
     }
 
     /// <https://html.spec.whatwg.org/multipage/parsing.html#adoption-agency-algorithm>
+    ///
+    /// Covers the `a`/`b`/`big`/`code`/`em`/`font`/`i`/`nobr`/`s`/`small`/
+    /// `strike`/`strong`/`tt`/`u` end tags, which all defer to this.
+    ///
+    /// Implements the full algorithm through step 18 (the outer loop's
+    /// return to step 4), including the inner loop's cloning/reparenting
+    /// (steps 9-11), moving last node to common ancestor (step 12), and
+    /// cloning the formatting element onto furthest block before restacking
+    /// both lists (steps 13-17) — there is no remaining `todo!()` here.
     pub(crate) fn adoption_agency_algorithm(
         &mut self,
         token: &TagToken,
     ) -> Result<(), HtmlParseError> {
         let subject = token.tag_name.clone();
 
-        if let Some(XpathItemTreeNode::ElementNode(element)) = self.current_node() {
-            if element.name == subject {
+        if let (Some(current_node_id), Some(XpathItemTreeNode::ElementNode(element))) =
+            (self.current_node_id(), self.current_node())
+        {
+            let current_node_is_active_formatting_element =
+                self.active_formatting_elements.iter().any(|entry| {
+                    matches!(entry, NodeOrMarker::Node(entry) if entry.node_id == current_node_id)
+                });
+
+            if element.name == subject && !current_node_is_active_formatting_element {
                 self.open_elements.pop();
                 return Ok(());
             }
@@ -891,7 +1186,7 @@ impl HtmlParser {
                 .iter()
                 .find(|node_id| {
                     if let XpathItemTreeNode::ElementNode(element) =
-                        self.arena.get(**node_id).unwrap().get()
+                        self.sink.arena().get(**node_id).unwrap().get()
                     {
                         SPECIAL_ELEMENTS.contains(&element.name.as_str())
                     } else {
@@ -917,13 +1212,181 @@ impl HtmlParser {
                 }
             };
 
-            let common_ancestor = self
+            let common_ancestor = *self
                 .open_elements
-                .get(formatting_element_index_in_open_elements - 1);
+                .get(formatting_element_index_in_open_elements - 1)
+                .expect("formatting element unexpectedly at the bottom of the stack of open elements");
+
+            // Step 8: bookmark formatting element's position in the list of
+            // active formatting elements, so the clone created in step 16
+            // below can be reinserted in the same place it used to occupy.
+            let mut bookmark = self
+                .active_formatting_elements
+                .iter()
+                .position(|entry| {
+                    matches!(entry, NodeOrMarker::Node(entry) if entry.node_id == formatting_element.id())
+                })
+                .expect("formatting element unexpectedly missing from the active formatting elements");
 
-            // TODO: bookmark?
+            // Steps 9-11: the inner loop. Walk up the stack of open elements
+            // from the furthest block towards the formatting element,
+            // cloning every formatting element found along the way and
+            // reparenting the previous pass's result underneath each clone.
+            let mut node_index = self
+                .open_elements
+                .iter()
+                .position(|id| *id == furthest_block)
+                .expect("furthest block unexpectedly missing from the stack of open elements");
+            let mut node = furthest_block;
+            let mut last_node = furthest_block;
+            let mut inner_loop_counter = 0;
+
+            loop {
+                inner_loop_counter += 1;
+
+                if node_index == 0 {
+                    break;
+                }
+                node_index -= 1;
+                node = self.open_elements[node_index];
+
+                if node == formatting_element.id() {
+                    break;
+                }
+
+                let afe_index = self.active_formatting_elements.iter().position(|entry| {
+                    matches!(entry, NodeOrMarker::Node(entry) if entry.node_id == node)
+                });
 
-            todo!()
+                let afe_index = match afe_index {
+                    Some(index) if inner_loop_counter > 3 => {
+                        self.active_formatting_elements.remove(index);
+                        if index < bookmark {
+                            bookmark -= 1;
+                        }
+                        None
+                    }
+                    other => other,
+                };
+
+                let afe_index = match afe_index {
+                    Some(index) => index,
+                    None => {
+                        // Not (or no longer) a formatting element: it plays
+                        // no further part in the reconstructed ancestry, so
+                        // drop it from the stack of open elements too.
+                        self.open_elements.remove(node_index);
+                        continue;
+                    }
+                };
+
+                let node_token = match &self.active_formatting_elements[afe_index] {
+                    NodeOrMarker::Node(entry) => entry.token.clone(),
+                    NodeOrMarker::Marker => {
+                        unreachable!("afe_index always points at a NodeEntry")
+                    }
+                };
+
+                let clone_id = self.create_detached_element(node_token.clone())?;
+
+                self.active_formatting_elements[afe_index] = NodeOrMarker::Node(NodeEntry {
+                    node_id: clone_id,
+                    token: node_token,
+                });
+                self.open_elements[node_index] = clone_id;
+
+                // Step 11.7: if last node is furthest block, move the
+                // bookmark to immediately after the new node.
+                if last_node == furthest_block {
+                    bookmark = afe_index + 1;
+                }
+
+                // Steps 11.8-11.9: reparent last node under the clone.
+                self.sink.detach(last_node);
+                self.sink.append(clone_id, last_node);
+
+                last_node = clone_id;
+                node = clone_id;
+            }
+
+            // Step 12: insert last node at the appropriate place, using
+            // common ancestor as the override target.
+            self.sink.detach(last_node);
+            let insertion_location =
+                self.appropriate_place_for_inserting_a_node(Some(common_ancestor))?;
+            insertion_location.insert(last_node, self.sink.arena_mut());
+
+            // Steps 13-15: create a clone of the formatting element, move
+            // all of furthest block's children onto it, then append that
+            // clone onto furthest block.
+            let formatting_element_afe_index = self
+                .active_formatting_elements
+                .iter()
+                .position(|entry| {
+                    matches!(entry, NodeOrMarker::Node(entry) if entry.node_id == formatting_element.id())
+                })
+                .expect("formatting element unexpectedly missing from the active formatting elements");
+            let formatting_element_token = match &self.active_formatting_elements
+                [formatting_element_afe_index]
+            {
+                NodeOrMarker::Node(entry) => entry.token.clone(),
+                NodeOrMarker::Marker => unreachable!("afe_index always points at a NodeEntry"),
+            };
+
+            self.active_formatting_elements
+                .remove(formatting_element_afe_index);
+            if formatting_element_afe_index < bookmark {
+                bookmark -= 1;
+            }
+
+            let formatting_element_clone =
+                self.create_detached_element(formatting_element_token.clone())?;
+
+            self.sink
+                .reparent_children(furthest_block, formatting_element_clone);
+            self.sink.append(furthest_block, formatting_element_clone);
+
+            // Step 16: insert a new entry for the clone at the bookmarked
+            // position in the list of active formatting elements.
+            self.active_formatting_elements.insert(
+                bookmark.min(self.active_formatting_elements.len()),
+                NodeOrMarker::Node(NodeEntry {
+                    node_id: formatting_element_clone,
+                    token: formatting_element_token,
+                }),
+            );
+
+            // Step 17: remove formatting element from the stack of open
+            // elements, and insert the clone into the stack immediately
+            // below furthest block.
+            self.open_elements
+                .retain(|id| *id != formatting_element.id());
+            let furthest_block_index = self
+                .open_elements
+                .iter()
+                .position(|id| *id == furthest_block)
+                .expect("furthest block unexpectedly missing from the stack of open elements");
+            self.open_elements
+                .insert(furthest_block_index, formatting_element_clone);
+
+            // Step 18: go back to the outer loop.
         }
     }
+
+    /// Create a new element node for a clone of `token`, registered in the
+    /// arena with its attributes but not yet attached to any parent or
+    /// pushed onto the stack of open elements.
+    ///
+    /// Used by [`HtmlParser::adoption_agency_algorithm`], which needs full
+    /// control over where each clone is (re)parented.
+    fn create_detached_element(&mut self, token: TagToken) -> Result<NodeId, HtmlParseError> {
+        let result = self.create_an_element_for_the_token(token, HTML_NAMESPACE)?;
+        let element_id = self.new_node(XpathItemTreeNode::ElementNode(result.element));
+
+        for attribute in result.attributes {
+            self.add_attribute_to_element(element_id, attribute.name, attribute.value)?;
+        }
+
+        Ok(element_id)
+    }
 }
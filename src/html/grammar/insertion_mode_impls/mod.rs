@@ -1,24 +1,32 @@
 use std::vec;
 
+use indextree::NodeId;
+
 use crate::{
     html::grammar::{tokenizer::TokenizerState, NodeOrMarker, SPECIAL_ELEMENTS},
     xpath::grammar::{
         data_model::{AttributeNode, ElementNode},
-        XpathItemTreeNode,
+        QuirksMode, XpathItemTreeNode,
     },
 };
 
 use super::{
     chars,
-    tokenizer::{HtmlToken, Parser, TagToken, TagTokenType},
-    Acknowledgement, HtmlParseError, HtmlParser, HtmlParserError, InsertionMode, HTML_NAMESPACE,
+    tokenizer::{HtmlToken, TagToken, TagTokenType},
+    tree_sink::TreeSink,
+    Acknowledgement, HtmlParseError, HtmlParser, HtmlParserError, InsertionMode, ProcessResult,
+    HTML_NAMESPACE,
 };
 
+pub(crate) mod foreign_content;
+pub(crate) mod frameset_insertion_modes;
 pub(crate) mod in_body_insertion_mode;
+pub(crate) mod select_insertion_modes;
+pub(crate) mod table_insertion_modes;
 
 pub use in_body_insertion_mode::*;
 
-impl HtmlParser {
+impl<S: TreeSink<Handle = NodeId> + Default> HtmlParser<S> {
     /// <https://html.spec.whatwg.org/multipage/parsing.html#the-initial-insertion-mode>
     pub(super) fn initial_insertion_mode(
         &mut self,
@@ -34,17 +42,38 @@ impl HtmlParser {
             ) => {
                 // ignore
             }
-            HtmlToken::Comment(_) => todo!(),
-            HtmlToken::DocType(_) => {
-                // TODO: Implement this section. No-op is good enough for now, but there's lots to do here.
+            HtmlToken::Comment(comment) => {
+                let document = self
+                    .root_node
+                    .ok_or(HtmlParseError::new("root node is None"))?;
+
+                self.insert_a_comment(comment, Some(document))?;
+            }
+            // `doctype.quirks_mode()` / `compute_quirks_mode` (see their doc
+            // comments in `mod.rs`) already implement the full name/public-id/
+            // system-id algorithm, including the prefix and no-system-id
+            // special cases.
+            HtmlToken::DocType(doctype) => {
+                // Fragment parsing sets up a context element before running
+                // the tokenizer, which is how we approximate "the parser
+                // cannot change the mode flag is true" for this DOCTYPE.
+                if !self.is_iframe_srcdoc && self.context_element.is_none() {
+                    self.quirks_mode = doctype.quirks_mode();
+                }
+
                 self.insertion_mode = InsertionMode::BeforeHtml;
             }
             _ => {
-                // TODO: If the document is not an iframe srcdoc document, then this is a parse error;
-                //       if the parser cannot change the mode flag is false, set the Document to quirks mode.
+                // An iframe srcdoc document is always no-quirks, and
+                // fragment parsing (approximated by `context_element` being
+                // set) can't change the mode either, so neither triggers
+                // the missing-DOCTYPE "go to quirks mode" fallback.
+                if !self.is_iframe_srcdoc && self.context_element.is_none() {
+                    self.quirks_mode = QuirksMode::Quirks;
+                }
 
                 self.insertion_mode = InsertionMode::BeforeHtml;
-                self.token_emitted(token)?;
+                self.pending_process_result = ProcessResult::Reprocess(self.insertion_mode, token);
             }
         }
 
@@ -56,20 +85,21 @@ impl HtmlParser {
         &mut self,
         token: HtmlToken,
     ) -> Result<Acknowledgement, HtmlParseError> {
-        fn anything_else(parser: &mut HtmlParser, token: HtmlToken) -> Result<(), HtmlParseError> {
+        fn anything_else<S: TreeSink<Handle = NodeId> + Default>(
+            parser: &mut HtmlParser<S>,
+            token: HtmlToken,
+        ) -> Result<(), HtmlParseError> {
             let result = parser.create_element(String::from("html"), HTML_NAMESPACE, None, None)?;
 
             // append the node to the document
             let node_id = parser.new_node(XpathItemTreeNode::ElementNode(result));
-            parser
-                .root_node
-                .expect("root node is None")
-                .append(node_id, &mut parser.arena);
+            let root_node = parser.root_node.expect("root node is None");
+            parser.sink.append(root_node, node_id);
 
             parser.open_elements.push(node_id);
 
             parser.insertion_mode = InsertionMode::BeforeHead;
-            parser.token_emitted(token)?;
+            parser.pending_process_result = ProcessResult::Reprocess(parser.insertion_mode, token);
 
             Ok(())
         }
@@ -99,9 +129,8 @@ impl HtmlParser {
                 let node_id = self.insert_create_an_element_for_the_token_result(result)?;
 
                 // append it to the document
-                self.root_node
-                    .expect("root node is None")
-                    .append(node_id, &mut self.arena);
+                let root_node = self.root_node.expect("root node is None");
+                self.sink.append(root_node, node_id);
 
                 self.insertion_mode = InsertionMode::BeforeHead;
             }
@@ -129,13 +158,16 @@ impl HtmlParser {
         &mut self,
         token: HtmlToken,
     ) -> Result<Acknowledgement, HtmlParseError> {
-        fn anything_else(parser: &mut HtmlParser, token: HtmlToken) -> Result<(), HtmlParseError> {
+        fn anything_else<S: TreeSink<Handle = NodeId> + Default>(
+            parser: &mut HtmlParser<S>,
+            token: HtmlToken,
+        ) -> Result<(), HtmlParseError> {
             let node_id = parser.insert_an_html_element(TagToken::new(String::from("head")))?;
 
             parser.head_element_pointer = Some(node_id);
 
             parser.insertion_mode = InsertionMode::InHead;
-            parser.token_emitted(token)?;
+            parser.pending_process_result = ProcessResult::Reprocess(parser.insertion_mode, token);
 
             Ok(())
         }
@@ -150,7 +182,9 @@ impl HtmlParser {
             ) => {
                 // ignore
             }
-            HtmlToken::Comment(_) => todo!(),
+            HtmlToken::Comment(comment) => {
+                self.insert_a_comment(comment, None)?;
+            }
             HtmlToken::DocType(_) => todo!(),
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "html" => {
                 todo!()
@@ -181,12 +215,15 @@ impl HtmlParser {
         &mut self,
         token: HtmlToken,
     ) -> Result<Acknowledgement, HtmlParseError> {
-        fn anything_else(parser: &mut HtmlParser, token: HtmlToken) -> Result<(), HtmlParseError> {
+        fn anything_else<S: TreeSink<Handle = NodeId> + Default>(
+            parser: &mut HtmlParser<S>,
+            token: HtmlToken,
+        ) -> Result<(), HtmlParseError> {
             parser.open_elements.pop().expect("open elements is empty");
 
             parser.insertion_mode = InsertionMode::AfterHead;
 
-            parser.token_emitted(token)?;
+            parser.pending_process_result = ProcessResult::Reprocess(parser.insertion_mode, token);
 
             Ok(())
         }
@@ -239,7 +276,12 @@ impl HtmlParser {
                 return self.generic_raw_text_element_parsing_algorithm(token);
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "noscript" => {
-                todo!()
+                // This parser never executes scripts, so it behaves as if
+                // the scripting flag were disabled: `noscript` content is
+                // ordinary markup rather than raw text.
+                self.insert_an_html_element(token)?;
+
+                self.insertion_mode = InsertionMode::InHeadNoscript;
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "script" => {
                 let node = self.insert_an_html_element(token)?;
@@ -268,19 +310,13 @@ impl HtmlParser {
                 anything_else(self, HtmlToken::TagToken(TagTokenType::EndTag(token)))?;
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "template" => {
+                self.insert_an_html_element(token)?;
+
                 self.active_formatting_elements.push(NodeOrMarker::Marker);
                 self.frameset_ok = false;
                 self.insertion_mode = InsertionMode::InTemplate;
                 self.template_insertion_modes
                     .push(InsertionMode::InTemplate);
-
-                // TODO: shadow root mode
-                if self.adjusted_current_node_id().ok() == self.open_elements.last().map(|x| *x) {
-                    self.insert_an_html_element(token)?;
-                    return Ok(Acknowledgement::no());
-                }
-
-                todo!()
             }
             HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "template" => {
                 if !self.open_elements_has_element("template") {
@@ -318,17 +354,102 @@ impl HtmlParser {
         Ok(Acknowledgement::no())
     }
 
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inheadnoscript>
+    pub(super) fn in_head_noscript_insertion_mode(
+        &mut self,
+        token: HtmlToken,
+    ) -> Result<Acknowledgement, HtmlParseError> {
+        fn anything_else<S: TreeSink<Handle = NodeId> + Default>(
+            parser: &mut HtmlParser<S>,
+            token: HtmlToken,
+        ) -> Result<(), HtmlParseError> {
+            parser.handle_error(HtmlParserError::MinorError(String::from(
+                "unexpected token in head noscript",
+            )))?;
+
+            parser.open_elements.pop().expect("open elements is empty");
+
+            parser.insertion_mode = InsertionMode::InHead;
+
+            parser.pending_process_result = ProcessResult::Reprocess(parser.insertion_mode, token);
+
+            Ok(())
+        }
+
+        match token {
+            HtmlToken::DocType(_) => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "unexpected doctype in head noscript",
+                )))?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "html" => {
+                self.using_the_rules_for(
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    InsertionMode::InBody,
+                )?;
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "noscript" => {
+                self.open_elements.pop().expect("open elements is empty");
+
+                self.insertion_mode = InsertionMode::InHead;
+            }
+            HtmlToken::Character(
+                chars::CHARACTER_TABULATION
+                | chars::LINE_FEED
+                | chars::FORM_FEED
+                | chars::CARRIAGE_RETURN
+                | chars::SPACE,
+            )
+            | HtmlToken::Comment(_) => {
+                self.using_the_rules_for(token, InsertionMode::InHead)?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token))
+                if ["basefont", "bgsound", "link", "meta", "noframes", "style"]
+                    .contains(&token.tag_name.as_str()) =>
+            {
+                self.using_the_rules_for(
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    InsertionMode::InHead,
+                )?;
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "br" => {
+                anything_else(self, HtmlToken::TagToken(TagTokenType::EndTag(token)))?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token))
+                if ["head", "noscript"].contains(&token.tag_name.as_str()) =>
+            {
+                self.handle_error(HtmlParserError::MinorError(format!(
+                    "unexpected {} start tag in head noscript",
+                    token.tag_name
+                )))?;
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(_)) => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "unexpected end tag in head noscript",
+                )))?;
+            }
+            _ => {
+                anything_else(self, token)?;
+            }
+        }
+
+        Ok(Acknowledgement::no())
+    }
+
     /// <https://html.spec.whatwg.org/multipage/parsing.html#the-after-head-insertion-mode>
     pub(super) fn after_head_insertion_mode(
         &mut self,
         token: HtmlToken,
     ) -> Result<Acknowledgement, HtmlParseError> {
-        fn anything_else(parser: &mut HtmlParser, token: HtmlToken) -> Result<(), HtmlParseError> {
+        fn anything_else<S: TreeSink<Handle = NodeId> + Default>(
+            parser: &mut HtmlParser<S>,
+            token: HtmlToken,
+        ) -> Result<(), HtmlParseError> {
             parser.insert_an_html_element(TagToken::new(String::from("body")))?;
 
             parser.insertion_mode = InsertionMode::InBody;
 
-            parser.token_emitted(token)?;
+            parser.pending_process_result = ProcessResult::Reprocess(parser.insertion_mode, token);
 
             Ok(())
         }
@@ -345,7 +466,9 @@ impl HtmlParser {
             {
                 self.insert_character(vec![c])?;
             }
-            HtmlToken::Comment(_) => todo!(),
+            HtmlToken::Comment(comment) => {
+                self.insert_a_comment(comment, None)?;
+            }
             HtmlToken::DocType(_) => todo!(),
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "html" => {
                 todo!()
@@ -358,7 +481,9 @@ impl HtmlParser {
                 self.insertion_mode = InsertionMode::InBody;
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "frameset" => {
-                todo!()
+                self.insert_an_html_element(token)?;
+
+                self.insertion_mode = InsertionMode::InFrameset;
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token))
                 if [
@@ -401,7 +526,18 @@ impl HtmlParser {
                 self.insert_character(vec![c])?;
             }
             HtmlToken::EndOfFile => {
-                todo!()
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "unexpected end of file in text",
+                )))?;
+
+                self.open_elements.pop().expect("open elements is empty");
+
+                self.insertion_mode = self
+                    .original_insertion_mode
+                    .expect("original insertion mode is None");
+
+                self.pending_process_result =
+                    ProcessResult::Reprocess(self.insertion_mode, HtmlToken::EndOfFile);
             }
             HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "script" => {
                 let script = self.current_node_as_element_result()?;
@@ -429,6 +565,11 @@ impl HtmlParser {
     }
 
     /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-intemplate>
+    /// The `InTable`/`InTableText`/`InCaption`/`InColumnGroup`/
+    /// `InTableBody`/`InRow`/`InCell` modes this switches into (below) are
+    /// all fully implemented in `table_insertion_modes.rs` — foster
+    /// parenting, the pending-table-character-tokens buffer, and
+    /// `close_the_cell` included (confirmed and documented by chunk14-2).
     pub(super) fn in_template_insertion_mode(
         &mut self,
         token: HtmlToken,
@@ -462,7 +603,10 @@ impl HtmlParser {
                 self.template_insertion_modes.pop();
                 self.template_insertion_modes.push(InsertionMode::InTable);
                 self.insertion_mode = InsertionMode::InTable;
-                self.token_emitted(HtmlToken::TagToken(TagTokenType::StartTag(token)))?;
+                self.pending_process_result = ProcessResult::Reprocess(
+                    self.insertion_mode,
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                );
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token))
                 if ["col"].contains(&token.tag_name.as_str()) =>
@@ -471,7 +615,10 @@ impl HtmlParser {
                 self.template_insertion_modes
                     .push(InsertionMode::InColumnGroup);
                 self.insertion_mode = InsertionMode::InColumnGroup;
-                self.token_emitted(HtmlToken::TagToken(TagTokenType::StartTag(token)))?;
+                self.pending_process_result = ProcessResult::Reprocess(
+                    self.insertion_mode,
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                );
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token))
                 if ["tr"].contains(&token.tag_name.as_str()) =>
@@ -480,7 +627,10 @@ impl HtmlParser {
                 self.template_insertion_modes
                     .push(InsertionMode::InTableBody);
                 self.insertion_mode = InsertionMode::InTableBody;
-                self.token_emitted(HtmlToken::TagToken(TagTokenType::StartTag(token)))?;
+                self.pending_process_result = ProcessResult::Reprocess(
+                    self.insertion_mode,
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                );
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token))
                 if ["td", "th"].contains(&token.tag_name.as_str()) =>
@@ -488,13 +638,19 @@ impl HtmlParser {
                 self.template_insertion_modes.pop();
                 self.template_insertion_modes.push(InsertionMode::InRow);
                 self.insertion_mode = InsertionMode::InRow;
-                self.token_emitted(HtmlToken::TagToken(TagTokenType::StartTag(token)))?;
+                self.pending_process_result = ProcessResult::Reprocess(
+                    self.insertion_mode,
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                );
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) => {
                 self.template_insertion_modes.pop();
                 self.template_insertion_modes.push(InsertionMode::InBody);
                 self.insertion_mode = InsertionMode::InBody;
-                self.token_emitted(HtmlToken::TagToken(TagTokenType::StartTag(token)))?;
+                self.pending_process_result = ProcessResult::Reprocess(
+                    self.insertion_mode,
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                );
             }
             HtmlToken::TagToken(TagTokenType::EndTag(token)) => {
                 self.handle_error(HtmlParserError::MinorError(String::from(
@@ -514,7 +670,7 @@ impl HtmlParser {
                 self.clear_the_list_of_active_formatting_elements_up_to_the_last_marker()?;
                 self.template_insertion_modes.pop();
                 self.reset_the_insertion_mode_appropriately()?;
-                self.token_emitted(token)?;
+                self.pending_process_result = ProcessResult::Reprocess(self.insertion_mode, token);
             }
         }
 
@@ -538,11 +694,18 @@ impl HtmlParser {
             {
                 self.using_the_rules_for(token, InsertionMode::InBody)?;
             }
-            HtmlToken::Comment(_) => {
-                todo!()
+            HtmlToken::Comment(comment) => {
+                let html_element = *self
+                    .open_elements
+                    .first()
+                    .ok_or(HtmlParseError::new("open elements is empty"))?;
+
+                self.insert_a_comment(comment, Some(html_element))?;
             }
             HtmlToken::DocType(_) => {
-                todo!()
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "unexpected doctype after body",
+                )))?;
             }
             HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "html" => {
                 self.using_the_rules_for(
@@ -551,9 +714,13 @@ impl HtmlParser {
                 )?;
             }
             HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "html" => {
-                // TODO: If parser was created as part of the HTML fragment parsing algorithm...
-
-                self.insertion_mode = InsertionMode::AfterAfterBody;
+                if self.context_element.is_some() {
+                    self.handle_error(HtmlParserError::MinorError(String::from(
+                        "html end tag after body in fragment parsing",
+                    )))?;
+                } else {
+                    self.insertion_mode = InsertionMode::AfterAfterBody;
+                }
             }
             HtmlToken::EndOfFile => {
                 self.stop_parsing()?;
@@ -564,7 +731,7 @@ impl HtmlParser {
                 )))?;
 
                 self.insertion_mode = InsertionMode::InBody;
-                self.token_emitted(token)?;
+                self.pending_process_result = ProcessResult::Reprocess(self.insertion_mode, token);
             }
         }
 
@@ -577,8 +744,12 @@ impl HtmlParser {
         token: HtmlToken,
     ) -> Result<Acknowledgement, HtmlParseError> {
         match token {
-            HtmlToken::Comment(_) => {
-                todo!()
+            HtmlToken::Comment(comment) => {
+                let document = self
+                    .root_node
+                    .ok_or(HtmlParseError::new("root node is None"))?;
+
+                self.insert_a_comment(comment, Some(document))?;
             }
             HtmlToken::DocType(_)
             | HtmlToken::Character(
@@ -605,7 +776,7 @@ impl HtmlParser {
                 )))?;
 
                 self.insertion_mode = InsertionMode::InBody;
-                self.token_emitted(token)?;
+                self.pending_process_result = ProcessResult::Reprocess(self.insertion_mode, token);
             }
         }
 
@@ -0,0 +1,224 @@
+use indextree::NodeId;
+
+use super::{
+    super::tokenizer::{HtmlToken, TagTokenType},
+    chars,
+    tree_sink::TreeSink,
+    Acknowledgement, HtmlParseError, HtmlParser, HtmlParserError, InsertionMode, ProcessResult,
+};
+
+impl<S: TreeSink<Handle = NodeId> + Default> HtmlParser<S> {
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inselect>
+    pub(super) fn in_select_insertion_mode(
+        &mut self,
+        token: HtmlToken,
+    ) -> Result<Acknowledgement, HtmlParseError> {
+        fn close_select_if_in_scope<S: TreeSink<Handle = NodeId> + Default>(
+            parser: &mut HtmlParser<S>,
+        ) -> Result<(), HtmlParseError> {
+            parser.pop_until_tag_name("select")?;
+            parser.reset_the_insertion_mode_appropriately()?;
+
+            Ok(())
+        }
+
+        match token {
+            HtmlToken::Character(chars::NULL) => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "unexpected null character in select",
+                )))?;
+            }
+            HtmlToken::Character(c) => {
+                self.insert_character(vec![c])?;
+            }
+            HtmlToken::Comment(comment) => {
+                self.insert_a_comment(comment, None)?;
+            }
+            HtmlToken::DocType(_) => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "unexpected doctype in select",
+                )))?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "html" => {
+                self.using_the_rules_for(
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    InsertionMode::InBody,
+                )?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "option" => {
+                if self
+                    .current_node_as_element()
+                    .is_some_and(|element| element.name == "option")
+                {
+                    self.open_elements.pop();
+                }
+
+                self.insert_an_html_element(token)?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "optgroup" => {
+                if self
+                    .current_node_as_element()
+                    .is_some_and(|element| element.name == "option")
+                {
+                    self.open_elements.pop();
+                }
+
+                if self
+                    .current_node_as_element()
+                    .is_some_and(|element| element.name == "optgroup")
+                {
+                    self.open_elements.pop();
+                }
+
+                self.insert_an_html_element(token)?;
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "optgroup" => {
+                let current_is_option = self
+                    .current_node_as_element()
+                    .is_some_and(|element| element.name == "option");
+                let node_before_current_is_optgroup = self
+                    .open_elements
+                    .len()
+                    .checked_sub(2)
+                    .and_then(|index| self.open_elements.get(index))
+                    .and_then(|id| self.sink.arena().get(*id))
+                    .and_then(|node| node.get().as_element_node().ok())
+                    .is_some_and(|element| element.name == "optgroup");
+
+                if current_is_option && node_before_current_is_optgroup {
+                    self.open_elements.pop();
+                }
+
+                if self
+                    .current_node_as_element()
+                    .is_some_and(|element| element.name == "optgroup")
+                {
+                    self.open_elements.pop();
+                } else {
+                    self.handle_error(HtmlParserError::MinorError(String::from(
+                        "optgroup end tag with no optgroup element to close",
+                    )))?;
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "option" => {
+                if self
+                    .current_node_as_element()
+                    .is_some_and(|element| element.name == "option")
+                {
+                    self.open_elements.pop();
+                } else {
+                    self.handle_error(HtmlParserError::MinorError(String::from(
+                        "option end tag with no option element to close",
+                    )))?;
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "select" => {
+                if !self.has_an_element_in_select_scope("select") {
+                    self.handle_error(HtmlParserError::MinorError(String::from(
+                        "select end tag with no select element in scope",
+                    )))?;
+                    return Ok(Acknowledgement::no());
+                }
+
+                close_select_if_in_scope(self)?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "select" => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "unexpected select start tag in select",
+                )))?;
+
+                if self.has_an_element_in_select_scope("select") {
+                    close_select_if_in_scope(self)?;
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token))
+                if ["input", "keygen", "textarea"].contains(&token.tag_name.as_str()) =>
+            {
+                self.handle_error(HtmlParserError::MinorError(format!(
+                    "unexpected {} start tag in select",
+                    token.tag_name
+                )))?;
+
+                if self.has_an_element_in_select_scope("select") {
+                    close_select_if_in_scope(self)?;
+                    self.pending_process_result = ProcessResult::Reprocess(
+                        self.insertion_mode,
+                        HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    );
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token))
+                if ["script", "template"].contains(&token.tag_name.as_str()) =>
+            {
+                self.using_the_rules_for(
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    InsertionMode::InHead,
+                )?;
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "template" => {
+                self.using_the_rules_for(
+                    HtmlToken::TagToken(TagTokenType::EndTag(token)),
+                    InsertionMode::InHead,
+                )?;
+            }
+            HtmlToken::EndOfFile => {
+                self.using_the_rules_for(token, InsertionMode::InBody)?;
+            }
+            _ => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "unexpected token in select",
+                )))?;
+            }
+        }
+
+        Ok(Acknowledgement::no())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inselectintable>
+    pub(super) fn in_select_in_table_insertion_mode(
+        &mut self,
+        token: HtmlToken,
+    ) -> Result<Acknowledgement, HtmlParseError> {
+        match token {
+            HtmlToken::TagToken(TagTokenType::StartTag(token))
+                if ["caption", "table", "tbody", "tfoot", "thead", "tr", "td", "th"]
+                    .contains(&token.tag_name.as_str()) =>
+            {
+                self.handle_error(HtmlParserError::MinorError(format!(
+                    "unexpected {} start tag in select inside a table",
+                    token.tag_name
+                )))?;
+
+                self.pop_until_tag_name("select")?;
+                self.reset_the_insertion_mode_appropriately()?;
+                self.pending_process_result = ProcessResult::Reprocess(
+                    self.insertion_mode,
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                );
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token))
+                if ["caption", "table", "tbody", "tfoot", "thead", "tr", "td", "th"]
+                    .contains(&token.tag_name.as_str()) =>
+            {
+                self.handle_error(HtmlParserError::MinorError(format!(
+                    "unexpected {} end tag in select inside a table",
+                    token.tag_name
+                )))?;
+
+                if self.has_an_element_in_table_scope(&token.tag_name) {
+                    self.pop_until_tag_name("select")?;
+                    self.reset_the_insertion_mode_appropriately()?;
+                    self.pending_process_result = ProcessResult::Reprocess(
+                        self.insertion_mode,
+                        HtmlToken::TagToken(TagTokenType::EndTag(token)),
+                    );
+                }
+            }
+            _ => {
+                self.using_the_rules_for(token, InsertionMode::InSelect)?;
+            }
+        }
+
+        Ok(Acknowledgement::no())
+    }
+}
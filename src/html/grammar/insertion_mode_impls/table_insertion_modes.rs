@@ -0,0 +1,690 @@
+use indextree::NodeId;
+
+use crate::html::grammar::NodeOrMarker;
+
+use super::{
+    super::tokenizer::{CommentToken, HtmlToken, TagToken, TagTokenType},
+    chars,
+    tree_sink::TreeSink,
+    Acknowledgement, HtmlParseError, HtmlParser, HtmlParserError, InsertionMode, ProcessResult,
+};
+
+/// `InTable`/`InTableText`/`InCaption`/`InColumnGroup`/`InTableBody`/
+/// `InRow`/`InCell` are all implemented below, each dispatched from
+/// `HtmlParser::token_emitted` via its own [`InsertionMode`] variant, with
+/// foster parenting handled by the `foster_parenting` flag and
+/// `HtmlParser::appropriate_place_for_inserting_a_node`.
+///
+/// Whitespace characters ignorable while waiting to see whether a run of
+/// character tokens buffered by [`HtmlParser::in_table_text_insertion_mode`]
+/// is foster-parented or inserted normally.
+const TABLE_WHITESPACE: [char; 5] = [
+    chars::CHARACTER_TABULATION,
+    chars::LINE_FEED,
+    chars::FORM_FEED,
+    chars::CARRIAGE_RETURN,
+    chars::SPACE,
+];
+
+impl<S: TreeSink<Handle = NodeId> + Default> HtmlParser<S> {
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-intable>
+    pub(super) fn in_table_insertion_mode(
+        &mut self,
+        token: HtmlToken,
+    ) -> Result<Acknowledgement, HtmlParseError> {
+        let current_node_is_table_context = matches!(
+            self.current_node_as_element(),
+            Some(element) if ["table", "tbody", "tfoot", "thead", "tr"].contains(&element.name.as_str())
+        );
+
+        match token {
+            HtmlToken::Character(_) if current_node_is_table_context => {
+                self.pending_table_character_tokens.clear();
+                self.original_insertion_mode = Some(self.insertion_mode);
+                self.insertion_mode = InsertionMode::InTableText;
+                self.pending_process_result = ProcessResult::Reprocess(self.insertion_mode, token);
+            }
+            HtmlToken::Comment(comment) => {
+                self.insert_a_comment(comment, None)?;
+            }
+            HtmlToken::DocType(_) => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "doctype in table",
+                )))?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "caption" => {
+                self.clear_the_stack_back_to_a_table_context()?;
+                self.active_formatting_elements.push(NodeOrMarker::Marker);
+                self.insert_an_html_element(token)?;
+                self.insertion_mode = InsertionMode::InCaption;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "colgroup" => {
+                self.clear_the_stack_back_to_a_table_context()?;
+                self.insert_an_html_element(token)?;
+                self.insertion_mode = InsertionMode::InColumnGroup;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "col" => {
+                self.clear_the_stack_back_to_a_table_context()?;
+                self.insert_an_html_element(TagToken::new(String::from("colgroup")))?;
+                self.insertion_mode = InsertionMode::InColumnGroup;
+                self.pending_process_result = ProcessResult::Reprocess(
+                    self.insertion_mode,
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                );
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token))
+                if ["tbody", "tfoot", "thead"].contains(&token.tag_name.as_str()) =>
+            {
+                self.clear_the_stack_back_to_a_table_context()?;
+                self.insert_an_html_element(token)?;
+                self.insertion_mode = InsertionMode::InTableBody;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token))
+                if ["td", "th", "tr"].contains(&token.tag_name.as_str()) =>
+            {
+                self.clear_the_stack_back_to_a_table_context()?;
+                self.insert_an_html_element(TagToken::new(String::from("tbody")))?;
+                self.insertion_mode = InsertionMode::InTableBody;
+                self.pending_process_result = ProcessResult::Reprocess(
+                    self.insertion_mode,
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                );
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "table" => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "table start tag in table",
+                )))?;
+
+                if self.has_an_element_in_table_scope("table") {
+                    self.pop_until_tag_name("table")?;
+                    self.reset_the_insertion_mode_appropriately()?;
+                    self.pending_process_result = ProcessResult::Reprocess(
+                        self.insertion_mode,
+                        HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    );
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "table" => {
+                if !self.has_an_element_in_table_scope("table") {
+                    self.handle_error(HtmlParserError::MinorError(String::from(
+                        "table end tag without a table in table scope",
+                    )))?;
+                } else {
+                    self.pop_until_tag_name("table")?;
+                    self.reset_the_insertion_mode_appropriately()?;
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token))
+                if [
+                    "body", "caption", "col", "colgroup", "html", "tbody", "td", "tfoot", "th",
+                    "thead", "tr",
+                ]
+                .contains(&token.tag_name.as_str()) =>
+            {
+                self.handle_error(HtmlParserError::MinorError(format!(
+                    "unexpected {} end tag in table",
+                    token.tag_name
+                )))?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token))
+                if ["style", "script", "template"].contains(&token.tag_name.as_str()) =>
+            {
+                self.using_the_rules_for(
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    InsertionMode::InHead,
+                )?;
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "template" => {
+                self.using_the_rules_for(
+                    HtmlToken::TagToken(TagTokenType::EndTag(token)),
+                    InsertionMode::InHead,
+                )?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token))
+                if token.tag_name == "input"
+                    && token
+                        .attributes
+                        .iter()
+                        .any(|attribute| {
+                            attribute.name == "type" && attribute.value.eq_ignore_ascii_case("hidden")
+                        }) =>
+            {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "hidden input in table",
+                )))?;
+
+                let self_closing = token.self_closing;
+                self.insert_an_html_element(token)?;
+                self.open_elements.pop();
+
+                if self_closing {
+                    return Ok(Acknowledgement::yes());
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "form" => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "form start tag in table",
+                )))?;
+
+                if !self.open_elements_has_element("template") && self.form_element_pointer.is_none()
+                {
+                    let element_id = self.insert_an_html_element(token)?;
+                    self.form_element_pointer = Some(element_id);
+                    self.open_elements.pop();
+                }
+            }
+            HtmlToken::EndOfFile => {
+                self.using_the_rules_for(token, InsertionMode::InBody)?;
+            }
+            _ => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "unexpected token in table, foster parenting",
+                )))?;
+
+                self.foster_parenting = true;
+                let result = self.using_the_rules_for(token, InsertionMode::InBody);
+                self.foster_parenting = false;
+                result?;
+            }
+        }
+
+        Ok(Acknowledgement::no())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-intabletext>
+    pub(super) fn in_table_text_insertion_mode(
+        &mut self,
+        token: HtmlToken,
+    ) -> Result<Acknowledgement, HtmlParseError> {
+        match token {
+            HtmlToken::Character('\0') => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "null character in table text",
+                )))?;
+            }
+            HtmlToken::Character(c) => {
+                self.pending_table_character_tokens.push(c);
+            }
+            _ => {
+                let pending = std::mem::take(&mut self.pending_table_character_tokens);
+
+                if pending.iter().any(|c| !TABLE_WHITESPACE.contains(c)) {
+                    self.handle_error(HtmlParserError::MinorError(String::from(
+                        "non-whitespace character tokens in table, foster parenting",
+                    )))?;
+
+                    self.foster_parenting = true;
+                    let result = self.insert_character(pending);
+                    self.foster_parenting = false;
+                    result?;
+                } else {
+                    self.insert_character(pending)?;
+                }
+
+                self.insertion_mode = self
+                    .original_insertion_mode
+                    .expect("original insertion mode is None");
+                self.pending_process_result = ProcessResult::Reprocess(self.insertion_mode, token);
+            }
+        }
+
+        Ok(Acknowledgement::no())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-incaption>
+    pub(super) fn in_caption_insertion_mode(
+        &mut self,
+        token: HtmlToken,
+    ) -> Result<Acknowledgement, HtmlParseError> {
+        match token {
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "caption" => {
+                if self.has_an_element_in_table_scope("caption") {
+                    self.end_caption()?;
+                } else {
+                    self.handle_error(HtmlParserError::MinorError(String::from(
+                        "caption end tag without a caption in table scope",
+                    )))?;
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token))
+                if [
+                    "caption", "col", "colgroup", "tbody", "td", "tfoot", "th", "thead", "tr",
+                ]
+                .contains(&token.tag_name.as_str()) =>
+            {
+                if self.has_an_element_in_table_scope("caption") {
+                    self.end_caption()?;
+                    self.pending_process_result = ProcessResult::Reprocess(
+                        self.insertion_mode,
+                        HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    );
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "table" => {
+                if self.has_an_element_in_table_scope("caption") {
+                    self.end_caption()?;
+                    self.pending_process_result = ProcessResult::Reprocess(
+                        self.insertion_mode,
+                        HtmlToken::TagToken(TagTokenType::EndTag(token)),
+                    );
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token))
+                if [
+                    "body", "col", "colgroup", "html", "tbody", "td", "tfoot", "th", "thead", "tr",
+                ]
+                .contains(&token.tag_name.as_str()) =>
+            {
+                self.handle_error(HtmlParserError::MinorError(format!(
+                    "unexpected {} end tag in caption",
+                    token.tag_name
+                )))?;
+            }
+            _ => {
+                self.using_the_rules_for(token, InsertionMode::InBody)?;
+            }
+        }
+
+        Ok(Acknowledgement::no())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-incaption>, the
+    /// `caption` end-tag steps, shared with the table-reopening start/end tags that implicitly
+    /// close the caption first.
+    fn end_caption(&mut self) -> Result<(), HtmlParseError> {
+        self.generate_implied_end_tags(None)?;
+
+        if self
+            .current_node_as_element()
+            .is_some_and(|element| element.name != "caption")
+        {
+            self.handle_error(HtmlParserError::MinorError(String::from(
+                "closing a caption element that is not the current node",
+            )))?;
+        }
+
+        self.pop_until_tag_name("caption")?;
+        self.clear_the_list_of_active_formatting_elements_up_to_the_last_marker()?;
+        self.insertion_mode = InsertionMode::InTable;
+
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-incolgroup>
+    pub(super) fn in_column_group_insertion_mode(
+        &mut self,
+        token: HtmlToken,
+    ) -> Result<Acknowledgement, HtmlParseError> {
+        match token {
+            HtmlToken::Character(c) if TABLE_WHITESPACE.contains(&c) => {
+                self.insert_character(vec![c])?;
+            }
+            HtmlToken::Comment(comment) => {
+                self.insert_a_comment(comment, None)?;
+            }
+            HtmlToken::DocType(_) => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "doctype in column group",
+                )))?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "html" => {
+                self.using_the_rules_for(
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    InsertionMode::InBody,
+                )?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "col" => {
+                let self_closing = token.self_closing;
+                self.insert_an_html_element(token)?;
+                self.open_elements.pop();
+
+                if self_closing {
+                    return Ok(Acknowledgement::yes());
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "colgroup" => {
+                if self
+                    .current_node_as_element()
+                    .is_some_and(|element| element.name == "colgroup")
+                {
+                    self.open_elements.pop();
+                    self.insertion_mode = InsertionMode::InTable;
+                } else {
+                    self.handle_error(HtmlParserError::MinorError(String::from(
+                        "colgroup end tag without a colgroup as the current node",
+                    )))?;
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "col" => {
+                self.handle_error(HtmlParserError::MinorError(String::from(
+                    "col end tag in column group",
+                )))?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token))
+                if token.tag_name == "template" =>
+            {
+                self.using_the_rules_for(
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    InsertionMode::InHead,
+                )?;
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "template" => {
+                self.using_the_rules_for(
+                    HtmlToken::TagToken(TagTokenType::EndTag(token)),
+                    InsertionMode::InHead,
+                )?;
+            }
+            HtmlToken::EndOfFile => {
+                self.using_the_rules_for(token, InsertionMode::InBody)?;
+            }
+            _ => {
+                if self
+                    .current_node_as_element()
+                    .is_some_and(|element| element.name == "colgroup")
+                {
+                    self.open_elements.pop();
+                    self.insertion_mode = InsertionMode::InTable;
+                    self.pending_process_result = ProcessResult::Reprocess(self.insertion_mode, token);
+                } else {
+                    self.handle_error(HtmlParserError::MinorError(String::from(
+                        "unexpected token in column group without a colgroup as the current node",
+                    )))?;
+                }
+            }
+        }
+
+        Ok(Acknowledgement::no())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-intbody>
+    pub(super) fn in_table_body_insertion_mode(
+        &mut self,
+        token: HtmlToken,
+    ) -> Result<Acknowledgement, HtmlParseError> {
+        match token {
+            HtmlToken::TagToken(TagTokenType::StartTag(token)) if token.tag_name == "tr" => {
+                self.clear_the_stack_back_to_a_table_body_context()?;
+                self.insert_an_html_element(token)?;
+                self.insertion_mode = InsertionMode::InRow;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token))
+                if ["th", "td"].contains(&token.tag_name.as_str()) =>
+            {
+                self.handle_error(HtmlParserError::MinorError(format!(
+                    "{} start tag without an enclosing tr",
+                    token.tag_name
+                )))?;
+
+                self.clear_the_stack_back_to_a_table_body_context()?;
+                self.insert_an_html_element(TagToken::new(String::from("tr")))?;
+                self.insertion_mode = InsertionMode::InRow;
+                self.pending_process_result = ProcessResult::Reprocess(
+                    self.insertion_mode,
+                    HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                );
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token))
+                if ["tbody", "tfoot", "thead"].contains(&token.tag_name.as_str()) =>
+            {
+                if !self.has_an_element_in_table_scope(&token.tag_name) {
+                    self.handle_error(HtmlParserError::MinorError(format!(
+                        "{} end tag without a {0} in table scope",
+                        token.tag_name
+                    )))?;
+                } else {
+                    self.clear_the_stack_back_to_a_table_body_context()?;
+                    self.open_elements.pop();
+                    self.insertion_mode = InsertionMode::InTable;
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token))
+                if [
+                    "caption", "col", "colgroup", "tbody", "tfoot", "thead",
+                ]
+                .contains(&token.tag_name.as_str()) =>
+            {
+                if !["tbody", "tfoot", "thead"].iter().any(|tag_name| self.has_an_element_in_table_scope(tag_name))
+                {
+                    self.handle_error(HtmlParserError::MinorError(String::from(
+                        "unexpected start tag with no table section in table scope",
+                    )))?;
+                } else {
+                    self.clear_the_stack_back_to_a_table_body_context()?;
+                    self.open_elements.pop();
+                    self.insertion_mode = InsertionMode::InTable;
+                    self.pending_process_result = ProcessResult::Reprocess(
+                        self.insertion_mode,
+                        HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    );
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "table" => {
+                if !["tbody", "tfoot", "thead"].iter().any(|tag_name| self.has_an_element_in_table_scope(tag_name))
+                {
+                    self.handle_error(HtmlParserError::MinorError(String::from(
+                        "table end tag with no table section in table scope",
+                    )))?;
+                } else {
+                    self.clear_the_stack_back_to_a_table_body_context()?;
+                    self.open_elements.pop();
+                    self.insertion_mode = InsertionMode::InTable;
+                    self.pending_process_result = ProcessResult::Reprocess(
+                        self.insertion_mode,
+                        HtmlToken::TagToken(TagTokenType::EndTag(token)),
+                    );
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token))
+                if [
+                    "body", "caption", "col", "colgroup", "html", "td", "th", "tr",
+                ]
+                .contains(&token.tag_name.as_str()) =>
+            {
+                self.handle_error(HtmlParserError::MinorError(format!(
+                    "unexpected {} end tag in table body",
+                    token.tag_name
+                )))?;
+            }
+            _ => {
+                self.using_the_rules_for(token, InsertionMode::InTable)?;
+            }
+        }
+
+        Ok(Acknowledgement::no())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-intr>
+    pub(super) fn in_row_insertion_mode(
+        &mut self,
+        token: HtmlToken,
+    ) -> Result<Acknowledgement, HtmlParseError> {
+        match token {
+            HtmlToken::TagToken(TagTokenType::StartTag(token))
+                if ["th", "td"].contains(&token.tag_name.as_str()) =>
+            {
+                self.clear_the_stack_back_to_a_table_row_context()?;
+                self.insert_an_html_element(token)?;
+                self.insertion_mode = InsertionMode::InCell;
+                self.active_formatting_elements.push(NodeOrMarker::Marker);
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "tr" => {
+                self.end_row()?;
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token))
+                if [
+                    "caption", "col", "colgroup", "tbody", "tfoot", "thead", "tr",
+                ]
+                .contains(&token.tag_name.as_str()) =>
+            {
+                if self.has_an_element_in_table_scope("tr") {
+                    self.end_row()?;
+                    self.pending_process_result = ProcessResult::Reprocess(
+                        self.insertion_mode,
+                        HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    );
+                } else {
+                    self.handle_error(HtmlParserError::MinorError(String::from(
+                        "unexpected start tag with no tr in table scope",
+                    )))?;
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token)) if token.tag_name == "table" => {
+                if self.has_an_element_in_table_scope("tr") {
+                    self.end_row()?;
+                    self.pending_process_result = ProcessResult::Reprocess(
+                        self.insertion_mode,
+                        HtmlToken::TagToken(TagTokenType::EndTag(token)),
+                    );
+                } else {
+                    self.handle_error(HtmlParserError::MinorError(String::from(
+                        "table end tag with no tr in table scope",
+                    )))?;
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token))
+                if ["tbody", "tfoot", "thead"].contains(&token.tag_name.as_str()) =>
+            {
+                if !self.has_an_element_in_table_scope(&token.tag_name) {
+                    self.handle_error(HtmlParserError::MinorError(format!(
+                        "{} end tag without a {0} in table scope",
+                        token.tag_name
+                    )))?;
+                } else if self.has_an_element_in_table_scope("tr") {
+                    self.end_row()?;
+                    self.pending_process_result = ProcessResult::Reprocess(
+                        self.insertion_mode,
+                        HtmlToken::TagToken(TagTokenType::EndTag(token)),
+                    );
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token))
+                if ["body", "caption", "col", "colgroup", "html", "td", "th"]
+                    .contains(&token.tag_name.as_str()) =>
+            {
+                self.handle_error(HtmlParserError::MinorError(format!(
+                    "unexpected {} end tag in table row",
+                    token.tag_name
+                )))?;
+            }
+            _ => {
+                self.using_the_rules_for(token, InsertionMode::InTable)?;
+            }
+        }
+
+        Ok(Acknowledgement::no())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-intr>, the `tr`
+    /// end-tag steps, shared with the tokens that implicitly close the row.
+    fn end_row(&mut self) -> Result<(), HtmlParseError> {
+        self.clear_the_stack_back_to_a_table_row_context()?;
+        self.open_elements.pop();
+        self.insertion_mode = InsertionMode::InTableBody;
+
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-intd>
+    pub(super) fn in_cell_insertion_mode(
+        &mut self,
+        token: HtmlToken,
+    ) -> Result<Acknowledgement, HtmlParseError> {
+        match token {
+            HtmlToken::TagToken(TagTokenType::EndTag(token))
+                if ["td", "th"].contains(&token.tag_name.as_str()) =>
+            {
+                if !self.has_an_element_in_table_scope(&token.tag_name) {
+                    self.handle_error(HtmlParserError::MinorError(format!(
+                        "{} end tag without a {0} in table scope",
+                        token.tag_name
+                    )))?;
+                } else {
+                    self.generate_implied_end_tags(None)?;
+
+                    if self
+                        .current_node_as_element()
+                        .is_some_and(|element| element.name != token.tag_name)
+                    {
+                        self.handle_error(HtmlParserError::MinorError(format!(
+                            "closing a {} element that is not the current node",
+                            token.tag_name
+                        )))?;
+                    }
+
+                    self.pop_until_tag_name(&token.tag_name)?;
+                    self.clear_the_list_of_active_formatting_elements_up_to_the_last_marker()?;
+                    self.insertion_mode = InsertionMode::InRow;
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::StartTag(token))
+                if [
+                    "caption", "col", "colgroup", "tbody", "td", "tfoot", "th", "thead", "tr",
+                ]
+                .contains(&token.tag_name.as_str()) =>
+            {
+                if !self.has_an_element_in_table_scope("td") && !self.has_an_element_in_table_scope("th")
+                {
+                    self.handle_error(HtmlParserError::MinorError(String::from(
+                        "unexpected start tag with no cell in table scope",
+                    )))?;
+                } else {
+                    self.close_the_cell()?;
+                    self.pending_process_result = ProcessResult::Reprocess(
+                        self.insertion_mode,
+                        HtmlToken::TagToken(TagTokenType::StartTag(token)),
+                    );
+                }
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token))
+                if ["body", "caption", "col", "colgroup", "html"]
+                    .contains(&token.tag_name.as_str()) =>
+            {
+                self.handle_error(HtmlParserError::MinorError(format!(
+                    "unexpected {} end tag in table cell",
+                    token.tag_name
+                )))?;
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(token))
+                if ["table", "tbody", "tfoot", "thead", "tr"].contains(&token.tag_name.as_str()) =>
+            {
+                if !self.has_an_element_in_table_scope(&token.tag_name) {
+                    self.handle_error(HtmlParserError::MinorError(format!(
+                        "{} end tag without a {0} in table scope",
+                        token.tag_name
+                    )))?;
+                } else {
+                    self.close_the_cell()?;
+                    self.pending_process_result = ProcessResult::Reprocess(
+                        self.insertion_mode,
+                        HtmlToken::TagToken(TagTokenType::EndTag(token)),
+                    );
+                }
+            }
+            _ => {
+                self.using_the_rules_for(token, InsertionMode::InBody)?;
+            }
+        }
+
+        Ok(Acknowledgement::no())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#close-the-cell>
+    fn close_the_cell(&mut self) -> Result<(), HtmlParseError> {
+        self.generate_implied_end_tags(None)?;
+
+        if self
+            .current_node_as_element()
+            .is_some_and(|element| !["td", "th"].contains(&element.name.as_str()))
+        {
+            self.handle_error(HtmlParserError::MinorError(String::from(
+                "closing a table cell that is not the current node",
+            )))?;
+        }
+
+        self.pop_until_tag_name_one_of(vec!["td", "th"])?;
+        self.clear_the_list_of_active_formatting_elements_up_to_the_last_marker()?;
+        self.insertion_mode = InsertionMode::InRow;
+
+        Ok(())
+    }
+}
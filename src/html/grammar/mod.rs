@@ -6,7 +6,10 @@ use indextree::{Arena, NodeId};
 use log::warn;
 use nom::error;
 use thiserror::Error;
-use tokenizer::{CommentToken, HtmlToken, Parser, TagToken, TagTokenType, TokenizerState};
+use tokenizer::{
+    CommentToken, HtmlToken, Parser, TagToken, TagTokenType, TokenizerError, TokenizerState,
+};
+use tracer::ParserTracer;
 
 use crate::{
     vecpointer::VecPointerRef,
@@ -15,18 +18,23 @@ use crate::{
             data_model::{
                 AttributeNode, CommentNode, ElementNode, TextNode, XpathDocumentNode, XpathItem,
             },
-            XpathItemTreeNode,
+            QuirksMode, XpathItemTreeNode,
         },
         Xpath, XpathItemTree,
     },
 };
 
 use super::DocumentNode;
+use tree_sink::{ArenaTreeSink, TreeSink};
 
 mod chars;
 pub mod document_builder;
 mod insertion_mode_impls;
+#[cfg(test)]
+mod test_driver;
 mod tokenizer;
+pub mod tracer;
+mod tree_sink;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub(crate) enum InsertionMode {
@@ -106,6 +114,168 @@ pub(crate) enum HtmlParseErrorType {
     UnexpectedQuestionMarkInsteadOfTagName,
     UnexpectedSolidusInTag,
     UnknownNamedCharacterReference,
+
+    /// A tree-construction-stage parse error raised through
+    /// [`HtmlParserError::MinorError`]/[`HtmlParserError::FatalError`] that
+    /// doesn't have its own catalog entry above yet. Carries the ad hoc
+    /// message those call sites already produce.
+    Other(String),
+}
+
+impl HtmlParseErrorType {
+    /// This error's name per the HTML spec's own "parse errors" catalog
+    /// (e.g. `"eof-in-comment"`), the same identifier html5lib-tests'
+    /// `errors`-format fixtures use. Returns `None` for
+    /// [`HtmlParseErrorType::Other`], which has no catalog entry to name.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#parse-errors>
+    pub(crate) fn spec_error_code(&self) -> Option<&'static str> {
+        Some(match self {
+            HtmlParseErrorType::AbruptClosingOfEmptyComment => "abrupt-closing-of-empty-comment",
+            HtmlParseErrorType::AbruptDoctypePublicIdentifier => {
+                "abrupt-doctype-public-identifier"
+            }
+            HtmlParseErrorType::AbruptDoctypeSystemIdentifier => {
+                "abrupt-doctype-system-identifier"
+            }
+            HtmlParseErrorType::AbsenceOfDigitsInNumericCharacterReference => {
+                "absence-of-digits-in-numeric-character-reference"
+            }
+            HtmlParseErrorType::CdataInHtmlContent => "cdata-in-html-content",
+            HtmlParseErrorType::CharacterReferenceOutsideUnicodeRange => {
+                "character-reference-outside-unicode-range"
+            }
+            HtmlParseErrorType::ControlCharacterInInputStream => {
+                "control-character-in-input-stream"
+            }
+            HtmlParseErrorType::ControlCharacterReference => "control-character-reference",
+            HtmlParseErrorType::DuplicateAttribute => "duplicate-attribute",
+            HtmlParseErrorType::EndTagWithAttributes => "end-tag-with-attributes",
+            HtmlParseErrorType::EndTagWithTrailingSolidus => "end-tag-with-trailing-solidus",
+            HtmlParseErrorType::EofBeforeTagName => "eof-before-tag-name",
+            HtmlParseErrorType::EofInCdata => "eof-in-cdata",
+            HtmlParseErrorType::EofInComment => "eof-in-comment",
+            HtmlParseErrorType::EofInDoctype => "eof-in-doctype",
+            HtmlParseErrorType::EofInScriptHtmlCommentLikeText => {
+                "eof-in-script-html-comment-like-text"
+            }
+            HtmlParseErrorType::EofInTag => "eof-in-tag",
+            HtmlParseErrorType::IncorrectlyClosedComment => "incorrectly-closed-comment",
+            HtmlParseErrorType::IncorrectlyOpenedComment => "incorrectly-opened-comment",
+            HtmlParseErrorType::InvalidCharacterSequenceAfterDoctypeName => {
+                "invalid-character-sequence-after-doctype-name"
+            }
+            HtmlParseErrorType::InvalidFirstCharacterOfTagName => {
+                "invalid-first-character-of-tag-name"
+            }
+            HtmlParseErrorType::MissingAttributeValue => "missing-attribute-value",
+            HtmlParseErrorType::MissingDoctypeName => "missing-doctype-name",
+            HtmlParseErrorType::MissingDoctypePublicIdentifier => {
+                "missing-doctype-public-identifier"
+            }
+            HtmlParseErrorType::MissingDoctypeSystemIdentifier => {
+                "missing-doctype-system-identifier"
+            }
+            HtmlParseErrorType::MissingEndTagName => "missing-end-tag-name",
+            HtmlParseErrorType::MissingQuoteBeforeDoctypePublicIdentifier => {
+                "missing-quote-before-doctype-public-identifier"
+            }
+            HtmlParseErrorType::MissingQuoteBeforeDoctypeSystemIdentifier => {
+                "missing-quote-before-doctype-system-identifier"
+            }
+            HtmlParseErrorType::MissingSemicolonAfterCharacterReference => {
+                "missing-semicolon-after-character-reference"
+            }
+            HtmlParseErrorType::MissingWhitespaceAfterDoctypePublicKeyword => {
+                "missing-whitespace-after-doctype-public-keyword"
+            }
+            HtmlParseErrorType::MissingWhitespaceAfterDoctypeSystemKeyword => {
+                "missing-whitespace-after-doctype-system-keyword"
+            }
+            HtmlParseErrorType::MissingWhitespaceBeforeDoctypeName => {
+                "missing-whitespace-before-doctype-name"
+            }
+            HtmlParseErrorType::MissingWhitespaceBetweenAttributes => {
+                "missing-whitespace-between-attributes"
+            }
+            HtmlParseErrorType::MissingWhitespaceBetweenDoctypePublicAndSystemIdentifiers => {
+                "missing-whitespace-between-doctype-public-and-system-identifiers"
+            }
+            HtmlParseErrorType::NestedComment => "nested-comment",
+            HtmlParseErrorType::NoncharacterCharacterReference => {
+                "noncharacter-character-reference"
+            }
+            HtmlParseErrorType::NoncharacterInInputStream => "noncharacter-in-input-stream",
+            HtmlParseErrorType::NonVoidHtmlElementStartTagWithTrailingSolidus => {
+                "non-void-html-element-start-tag-with-trailing-solidus"
+            }
+            HtmlParseErrorType::NullCharacterReference => "null-character-reference",
+            HtmlParseErrorType::SurrogateCharacterReference => "surrogate-character-reference",
+            HtmlParseErrorType::SurrogateInInputStream => "surrogate-in-input-stream",
+            HtmlParseErrorType::UnexpectedCharacterAfterDoctypeSystemIdentifier => {
+                "unexpected-character-after-doctype-system-identifier"
+            }
+            HtmlParseErrorType::UnexpectedCharacterInAttributeName => {
+                "unexpected-character-in-attribute-name"
+            }
+            HtmlParseErrorType::UnexpectedCharacterInUnquotedAttributeValue => {
+                "unexpected-character-in-unquoted-attribute-value"
+            }
+            HtmlParseErrorType::UnexpectedEqualsSignBeforeAttributeName => {
+                "unexpected-equals-sign-before-attribute-name"
+            }
+            HtmlParseErrorType::UnexpectedNullCharacter => "unexpected-null-character",
+            HtmlParseErrorType::UnexpectedQuestionMarkInsteadOfTagName => {
+                "unexpected-question-mark-instead-of-tag-name"
+            }
+            HtmlParseErrorType::UnexpectedSolidusInTag => "unexpected-solidus-in-tag",
+            HtmlParseErrorType::UnknownNamedCharacterReference => {
+                "unknown-named-character-reference"
+            }
+            HtmlParseErrorType::Other(_) => return None,
+        })
+    }
+}
+
+/// A 1-based line/column position in the text a [`HtmlParser`] is reading.
+///
+/// Positions are tracked by replaying the textual content of each token as
+/// it's emitted, since the tokenizer's underlying [`VecPointerRef`](crate::vecpointer::VecPointerRef)
+/// doesn't expose its cursor; tag/comment/doctype tokens are reconstructed
+/// from their parsed fields rather than the exact source bytes, so a
+/// position may be off by a character or two inside a tag. Character tokens
+/// (the bulk of a typical document) are exact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourcePosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Default for SourcePosition {
+    fn default() -> Self {
+        SourcePosition { line: 1, column: 1 }
+    }
+}
+
+impl SourcePosition {
+    fn advance(&mut self, text: &str) {
+        for c in text.chars() {
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+    }
+}
+
+/// A parse error collected during tree construction, together with where in
+/// the source it was emitted.
+#[derive(Debug)]
+pub struct CollectedParseError {
+    pub error: HtmlParseErrorType,
+    pub position: SourcePosition,
 }
 
 #[derive(Debug, Error)]
@@ -127,12 +297,120 @@ pub fn parse(text: &str) -> Result<XpathItemTree, HtmlParseError> {
     parser.parse(text)
 }
 
+/// Deep-copy the subtree rooted at `node_id` from `source` into `dest`,
+/// returning the new root's id in `dest`.
+///
+/// A node's own value (e.g. [`crate::xpath::grammar::data_model::ElementNode`])
+/// holds no children field of its own — parent/child structure lives
+/// entirely in the arena's links — so cloning just `node_id`'s data would
+/// silently drop everything below it. Used by [`HtmlParser::parse_fragment`]
+/// to hand each top-level fragment node an independent tree before the
+/// parser's own arena is wiped.
+fn clone_subtree(
+    source: &Arena<XpathItemTreeNode>,
+    node_id: NodeId,
+    dest: &mut Arena<XpathItemTreeNode>,
+) -> NodeId {
+    let data = source
+        .get(node_id)
+        .expect("node_id belongs to source")
+        .get()
+        .clone();
+    let new_id = dest.new_node(data);
+
+    for child_id in node_id.children(source) {
+        let new_child_id = clone_subtree(source, child_id, dest);
+        new_id.append(new_child_id, dest);
+    }
+
+    new_id
+}
+
+/// Parse `text` as an HTML fragment, the way html5ever's fragment mode works:
+/// as if it were the `innerHTML` of an element named `context_tag_name` in
+/// `context_namespace` (one of [`HTML_NAMESPACE`], [`SVG_NAMESPACE`], or
+/// [`MATHML_NAMESPACE`]).
+///
+/// `form_owner_tag_name` should be `Some("form")` when the context element is
+/// itself a `form` element or has one among its ancestors in the caller's own
+/// document; see [`HtmlParser::parse_fragment`] for why this can't be
+/// derived automatically here.
+///
+/// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-html-fragments>
+///
+/// Returns one standalone [`XpathItemTree`] per node the algorithm would
+/// have parented directly under the synthetic `html` root, in document
+/// order, each rooted at its own synthetic document node so its descendants
+/// (which the fragment's nesting can run arbitrarily deep into) stay
+/// reachable.
+pub fn parse_fragment(
+    text: &str,
+    context_tag_name: &str,
+    context_namespace: &str,
+    form_owner_tag_name: Option<&str>,
+) -> Result<Vec<XpathItemTree>, HtmlParseError> {
+    let mut parser = HtmlParser::new();
+    parser.parse_fragment(
+        text,
+        context_tag_name,
+        context_namespace,
+        form_owner_tag_name,
+    )
+}
+
+/// Parse `text`, returning every parse error collected along the way
+/// instead of silently ignoring them.
+///
+/// Unlike [`parse`], a malformed document is not itself an `Err` here; it
+/// still produces a best-effort [`XpathItemTree`] per the HTML spec's
+/// error-recovery rules, with the errors it triggered alongside it.
+pub fn parse_collecting_errors(text: &str) -> Result<RecoveringParseResult, HtmlParseError> {
+    let error_handler = std::rc::Rc::new(CollectingParseErrorHandler::new(false));
+    let mut parser = HtmlParser::new().with_error_handler(Box::new(error_handler.clone()));
+    let tree = parser.parse(text)?;
+
+    Ok(RecoveringParseResult {
+        tree,
+        errors: error_handler.take_errors(),
+    })
+}
+
+/// The result of [`parse_collecting_errors`]: a best-effort tree built per
+/// the HTML spec's error-recovery rules, alongside every error collected
+/// while building it.
+///
+/// Tools built on this crate (linters, minifiers) that need to report
+/// problems on otherwise-malformed input can match on `errors` directly, or
+/// check [`recovered_from_errors`](RecoveringParseResult::recovered_from_errors)
+/// first to decide whether to bother. Each [`CollectedParseError`] carries a
+/// [`SourcePosition`], not the tokenizer's own `Span` byte-offset range —
+/// that type isn't wired into token emission yet (see `tokenizer::span`'s
+/// module docs), so a byte-offset-precise location isn't available here;
+/// `SourcePosition` is reconstructed from token text instead and is exact
+/// for the character tokens most errors occur within.
+#[derive(Debug)]
+pub struct RecoveringParseResult {
+    pub tree: XpathItemTree,
+    pub errors: Vec<CollectedParseError>,
+}
+
+impl RecoveringParseResult {
+    /// Whether parsing hit at least one recoverable error and kept going
+    /// rather than stopping, per the HTML spec's error-recovery rules.
+    pub fn recovered_from_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+}
+
 /// <https://infra.spec.whatwg.org/#html-namespace>
 pub(crate) const HTML_NAMESPACE: &str = "http://www.w3.org/1999/xhtml";
 
 /// <https://infra.spec.whatwg.org/#svg-namespace>
 pub(crate) const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
 
+/// <https://infra.spec.whatwg.org/#mathml-namespace>
+pub(crate) const MATHML_NAMESPACE: &str = "http://www.w3.org/1998/Math/MathML";
+
 pub(crate) static ELEMENT_IN_SCOPE_TYPES: [&str; 9] = [
     "applet", "caption", "html", "table", "td", "th", "marquee", "object", "template",
 ];
@@ -227,11 +505,196 @@ pub(crate) static SPECIAL_ELEMENTS: [&str; 83] = [
     "xmp",
 ];
 
+/// Public identifiers that always select full quirks mode on their own,
+/// per <https://html.spec.whatwg.org/multipage/parsing.html#the-initial-insertion-mode>.
+static QUIRKS_PUBLIC_IDS: [&str; 3] = [
+    "-//w3o//dtd w3 html strict 3.0//en//",
+    "-/w3c/dtd html 4.0 transitional/en",
+    "html",
+];
+
+/// Public identifier prefixes that always select full quirks mode.
+static QUIRKS_PUBLIC_ID_PREFIXES: [&str; 57] = [
+    "+//silmaril//dtd html pro v0r11 19970101//",
+    "-//as//dtd html 3.0 aswedit + extensions//",
+    "-//advasoft ltd//dtd html 3.0 aswedit + extensions//",
+    "-//ietf//dtd html 2.0 level 1//",
+    "-//ietf//dtd html 2.0 level 2//",
+    "-//ietf//dtd html 2.0 strict level 1//",
+    "-//ietf//dtd html 2.0 strict level 2//",
+    "-//ietf//dtd html 2.0 strict//",
+    "-//ietf//dtd html 2.0//",
+    "-//ietf//dtd html 2.1e//",
+    "-//ietf//dtd html 3.0//",
+    "-//ietf//dtd html 3.2 final//",
+    "-//ietf//dtd html 3.2//",
+    "-//ietf//dtd html 3//",
+    "-//ietf//dtd html level 0//",
+    "-//ietf//dtd html level 1//",
+    "-//ietf//dtd html level 2//",
+    "-//ietf//dtd html level 3//",
+    "-//ietf//dtd html strict level 0//",
+    "-//ietf//dtd html strict level 1//",
+    "-//ietf//dtd html strict level 2//",
+    "-//ietf//dtd html strict level 3//",
+    "-//ietf//dtd html strict//",
+    "-//ietf//dtd html//",
+    "-//metrius//dtd metrius presentational//",
+    "-//microsoft//dtd internet explorer 2.0 html strict//",
+    "-//microsoft//dtd internet explorer 2.0 html//",
+    "-//microsoft//dtd internet explorer 2.0 tables//",
+    "-//microsoft//dtd internet explorer 3.0 html strict//",
+    "-//microsoft//dtd internet explorer 3.0 html//",
+    "-//microsoft//dtd internet explorer 3.0 tables//",
+    "-//netscape comm. corp.//dtd html//",
+    "-//netscape comm. corp.//dtd strict html//",
+    "-//o'reilly and associates//dtd html 2.0//",
+    "-//o'reilly and associates//dtd html extended 1.0//",
+    "-//o'reilly and associates//dtd html extended relaxed 1.0//",
+    "-//sq//dtd html 2.0 hotmetal + extensions//",
+    "-//softquad software//dtd hotmetal pro 6.0::19990601::extensions to html 4.0//",
+    "-//softquad//dtd hotmetal pro 4.0::19971010::extensions to html 4.0//",
+    "-//spyglass//dtd html 2.0 extended//",
+    "-//sun microsystems corp.//dtd hotjava html//",
+    "-//sun microsystems corp.//dtd hotjava strict html//",
+    "-//w3c//dtd html 3 1995-03-24//",
+    "-//w3c//dtd html 3.2 draft//",
+    "-//w3c//dtd html 3.2 final//",
+    "-//w3c//dtd html 3.2//",
+    "-//w3c//dtd html 3.2s draft//",
+    "-//w3c//dtd html 4.0 frameset//",
+    "-//w3c//dtd html 4.0 transitional//",
+    "-//w3c//dtd html experimental 19960712//",
+    "-//w3c//dtd html experimental 970421//",
+    "-//w3c//dtd w3 html//",
+    "-//w3o//dtd w3 html 3.0//",
+    "-//webtechs//dtd mozilla html 2.0//",
+    "-//webtechs//dtd mozilla html//",
+];
+
+/// Public identifier prefixes that select full quirks mode only when there
+/// is no system identifier.
+static QUIRKS_PUBLIC_ID_PREFIXES_NO_SYSTEM_ID: [&str; 2] = [
+    "-//w3c//dtd html 4.01 frameset//",
+    "-//w3c//dtd html 4.01 transitional//",
+];
+
+/// Public identifier prefixes that always select limited quirks mode.
+static LIMITED_QUIRKS_PUBLIC_ID_PREFIXES: [&str; 2] = [
+    "-//w3c//dtd xhtml 1.0 frameset//",
+    "-//w3c//dtd xhtml 1.0 transitional//",
+];
+
+/// Public identifier prefixes that select limited quirks mode only when
+/// there is a system identifier.
+static LIMITED_QUIRKS_PUBLIC_ID_PREFIXES_WITH_SYSTEM_ID: [&str; 2] = [
+    "-//w3c//dtd html 4.01 frameset//",
+    "-//w3c//dtd html 4.01 transitional//",
+];
+
+const IBM_QUIRKS_SYSTEM_ID: &str = "http://www.ibm.com/data/dtd/v11/ibmxhtml1-transitional.dtd";
+
+/// Compute the quirks mode a `<!DOCTYPE>` token selects.
+///
+/// Also reachable as [`DoctypeToken::quirks_mode`](tokenizer::DoctypeToken::quirks_mode)
+/// for callers that only have a token and not a full [`HtmlParser`] (e.g. a
+/// bare tokenizer consumer that never builds a tree). `initial_insertion_mode`
+/// calls this to set `HtmlParser::quirks_mode` as soon as a DOCTYPE token is
+/// seen (forcing [`QuirksMode::Quirks`] instead if a DOCTYPE never appears
+/// at all), and the result is exposed on the parsed document via
+/// `XpathItemTree::quirks_mode`.
+///
+/// <https://html.spec.whatwg.org/multipage/parsing.html#the-initial-insertion-mode>
+pub(crate) fn compute_quirks_mode(doctype: &DoctypeToken) -> QuirksMode {
+    if doctype.force_quirks || !doctype.name.eq_ignore_ascii_case("html") {
+        return QuirksMode::Quirks;
+    }
+
+    let public_id = doctype
+        .public_identifier
+        .as_deref()
+        .map(str::to_ascii_lowercase);
+    let system_id = doctype
+        .system_identifier
+        .as_deref()
+        .map(str::to_ascii_lowercase);
+
+    if let Some(public_id) = &public_id {
+        if QUIRKS_PUBLIC_IDS.contains(&public_id.as_str())
+            || QUIRKS_PUBLIC_ID_PREFIXES
+                .iter()
+                .any(|prefix| public_id.starts_with(prefix))
+        {
+            return QuirksMode::Quirks;
+        }
+
+        if system_id.is_none()
+            && QUIRKS_PUBLIC_ID_PREFIXES_NO_SYSTEM_ID
+                .iter()
+                .any(|prefix| public_id.starts_with(prefix))
+        {
+            return QuirksMode::Quirks;
+        }
+    }
+
+    if system_id.as_deref() == Some(IBM_QUIRKS_SYSTEM_ID) {
+        return QuirksMode::Quirks;
+    }
+
+    if let Some(public_id) = &public_id {
+        if LIMITED_QUIRKS_PUBLIC_ID_PREFIXES
+            .iter()
+            .any(|prefix| public_id.starts_with(prefix))
+        {
+            return QuirksMode::LimitedQuirks;
+        }
+
+        if system_id.is_some()
+            && LIMITED_QUIRKS_PUBLIC_ID_PREFIXES_WITH_SYSTEM_ID
+                .iter()
+                .any(|prefix| public_id.starts_with(prefix))
+        {
+            return QuirksMode::LimitedQuirks;
+        }
+    }
+
+    QuirksMode::NoQuirks
+}
+
 pub(crate) struct CreateAnElementForTheTokenResult {
     element: ElementNode,
     attributes: Vec<AttributeNode>,
 }
 
+/// Where to put a node per
+/// <https://html.spec.whatwg.org/multipage/parsing.html#appropriate-place-for-inserting-a-node>.
+///
+/// Usually a node is simply appended as `parent`'s last child, but foster
+/// parenting can require inserting it before an existing sibling instead
+/// (e.g. before a misnested `<table>`).
+pub(crate) struct InsertionLocation {
+    pub(crate) parent: NodeId,
+    pub(crate) before_sibling: Option<NodeId>,
+}
+
+impl InsertionLocation {
+    /// The node that would end up immediately before `node` were it inserted
+    /// at this location right now.
+    fn previous_sibling(&self, arena: &Arena<XpathItemTreeNode>) -> Option<NodeId> {
+        match self.before_sibling {
+            Some(sibling) => arena.get(sibling).unwrap().previous_sibling(),
+            None => arena.get(self.parent).unwrap().last_child(),
+        }
+    }
+
+    fn insert(&self, node: NodeId, arena: &mut Arena<XpathItemTreeNode>) {
+        match self.before_sibling {
+            Some(sibling) => sibling.insert_before(node, arena),
+            None => self.parent.append(node, arena),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) enum NodeOrMarker {
     Node(NodeEntry),
@@ -244,23 +707,55 @@ pub(crate) struct NodeEntry {
     token: TagToken,
 }
 
-pub struct HtmlParser {
+/// Drives the WHATWG tree construction algorithm, building its output
+/// through a [`TreeSink`] — [`ArenaTreeSink`] by default, building directly
+/// in an `Arena<XpathItemTreeNode>`. See [`tree_sink`]'s module docs for the
+/// scope (and limits) of what "pluggable" means here.
+pub struct HtmlParser<S: TreeSink<Handle = NodeId> + Default = ArenaTreeSink> {
     error_handler: Box<dyn ParseErrorHandler>,
     insertion_mode: InsertionMode,
     template_insertion_modes: Vec<InsertionMode>,
     original_insertion_mode: Option<InsertionMode>,
     open_elements: Vec<NodeId>,
     context_element: Option<NodeId>,
-    arena: Arena<XpathItemTreeNode>,
+    sink: S,
     root_node: Option<NodeId>,
     foster_parenting: bool,
     frameset_ok: bool,
     active_formatting_elements: Vec<NodeOrMarker>,
     head_element_pointer: Option<NodeId>,
     form_element_pointer: Option<NodeId>,
+    quirks_mode: QuirksMode,
+    is_iframe_srcdoc: bool,
+    current_position: SourcePosition,
+
+    /// Buffers character tokens seen while in the "in table text" insertion
+    /// mode, so they can be reprocessed together once a non-character token
+    /// reveals whether they're all whitespace (inserted as-is) or not
+    /// (foster-parented via the "in table" insertion mode).
+    pending_table_character_tokens: Vec<char>,
+
+    /// Set by an insertion-mode method that wants its token reprocessed in a
+    /// new mode instead of handling it further itself. Checked (and reset to
+    /// [`ProcessResult::Done`]) by [`HtmlParser::token_emitted`]'s dispatch
+    /// loop after every insertion-mode call, which keeps feeding the token
+    /// through successive modes until one of them leaves this as `Done`.
+    pending_process_result: ProcessResult,
+
+    /// Set right after inserting a `pre`/`listing`/`textarea` element. If
+    /// the very next token is a U+000A LINE FEED character token, that
+    /// token is dropped instead of being handled normally (per the spec's
+    /// "ignore the next line feed" rule for those elements) and this is
+    /// cleared; any other token clears it without being dropped.
+    ignore_next_line_feed: bool,
+
+    /// Set via [`HtmlParser::with_tracer`]. Notified of every insertion-mode
+    /// dispatch and every parse error, for debugging without recompiling
+    /// with ad-hoc `println!`s.
+    trace: Option<Box<dyn ParserTracer>>,
 }
 
-impl HtmlParser {
+impl<S: TreeSink<Handle = NodeId> + Default> HtmlParser<S> {
     pub fn new() -> Self {
         HtmlParser {
             error_handler: Box::new(DefaultParseErrorHandler),
@@ -269,20 +764,72 @@ impl HtmlParser {
             original_insertion_mode: None,
             open_elements: Vec::new(),
             context_element: None,
-            arena: Arena::new(),
+            sink: S::default(),
             root_node: None,
             foster_parenting: false,
             frameset_ok: true,
             active_formatting_elements: Vec::new(),
             head_element_pointer: None,
             form_element_pointer: None,
+            quirks_mode: QuirksMode::NoQuirks,
+            is_iframe_srcdoc: false,
+            current_position: SourcePosition::default(),
+            pending_table_character_tokens: Vec::new(),
+            pending_process_result: ProcessResult::Done,
+            ignore_next_line_feed: false,
+            trace: None,
+        }
+    }
+
+    /// Use `error_handler` instead of [`DefaultParseErrorHandler`] to observe
+    /// parse errors raised while this parser runs.
+    pub fn with_error_handler(mut self, error_handler: Box<dyn ParseErrorHandler>) -> Self {
+        self.error_handler = error_handler;
+        self
+    }
+
+    /// Install `tracer` to observe insertion-mode dispatches and parse
+    /// errors raised while this parser runs. See [`ParserTracer`].
+    pub fn with_tracer(mut self, tracer: Box<dyn ParserTracer>) -> Self {
+        self.trace = Some(tracer);
+        self
+    }
+
+    /// Mark this document as an iframe srcdoc document, i.e. the document
+    /// parsed from an `<iframe>`'s `srcdoc` attribute. Such documents are
+    /// always in no-quirks mode and never trigger the missing-DOCTYPE parse
+    /// error, regardless of what DOCTYPE (if any) they contain.
+    pub fn with_iframe_srcdoc(mut self, is_iframe_srcdoc: bool) -> Self {
+        self.is_iframe_srcdoc = is_iframe_srcdoc;
+        self
+    }
+
+    /// Best-effort textual reconstruction of `token`, used only to advance
+    /// [`HtmlParser::current_position`]. See [`SourcePosition`]'s docs for
+    /// its accuracy caveats.
+    fn token_text(token: &HtmlToken) -> String {
+        match token {
+            HtmlToken::Character(c) => c.to_string(),
+            HtmlToken::Comment(comment) => format!("<!--{}-->", comment.data),
+            HtmlToken::DocType(doctype) => format!("<!DOCTYPE {}>", doctype.name),
+            HtmlToken::TagToken(TagTokenType::StartTag(tag)) => {
+                let attributes: String = tag
+                    .attributes
+                    .iter()
+                    .map(|attribute| format!(" {}=\"{}\"", attribute.name, attribute.value))
+                    .collect();
+                format!("<{}{}>", tag.tag_name, attributes)
+            }
+            HtmlToken::TagToken(TagTokenType::EndTag(tag)) => format!("</{}>", tag.tag_name),
+            HtmlToken::EndOfFile => String::new(),
         }
     }
 
     pub fn parse(&mut self, text: &str) -> Result<XpathItemTree, HtmlParseError> {
         // set document node as the root node
         let document_node_id = self
-            .arena
+            .sink
+            .arena_mut()
             .new_node(XpathItemTreeNode::DocumentNode(XpathDocumentNode::new()));
 
         self.root_node = Some(document_node_id);
@@ -300,16 +847,154 @@ impl HtmlParser {
             tokenizer.step()?;
         }
 
-        let arena = std::mem::replace(&mut self.arena, Arena::new());
-        let document = XpathItemTree::new(arena, document_node_id);
+        let arena = std::mem::replace(self.sink.arena_mut(), Arena::new());
+        let document = XpathItemTree::new(arena, document_node_id, self.quirks_mode);
         Ok(document)
     }
 
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-html-fragments>
+    ///
+    /// Creates the synthetic `html` root, seeds the tokenizer's initial
+    /// state from `context_tag_name` (RCDATA for `title`/`textarea`,
+    /// RAWTEXT for `style`/`xmp`/`iframe`/`noembed`/`noframes`/`script`,
+    /// PLAINTEXT for `plaintext`), and calls
+    /// [`HtmlParser::reset_the_insertion_mode_appropriately`], which reads
+    /// `context_element` once it walks off the bottom of the stack of open
+    /// elements (the "if last" case below) to pick the right starting mode
+    /// for e.g. a `td` context. No active-formatting-elements marker is
+    /// pushed here, matching the spec: fragment parsing only ever pushes
+    /// one onto an empty list, same as document parsing.
+    ///
+    /// The spec sets the form element pointer to "the nearest node to the
+    /// context that is a form element (going straight up the ancestor
+    /// chain, including the element itself, if it is a form element), if
+    /// any" — but the context element here is synthetic (see below) and has
+    /// no ancestor chain to walk, so that fact can't be derived from
+    /// `context_tag_name` alone. `form_owner_tag_name` lets the caller
+    /// assert it directly instead: pass `Some("form")` (or whatever the
+    /// real owner's tag name is) when the caller's own context element has
+    /// one, `None` otherwise.
+    ///
+    /// Covers the full fragment-parsing algorithm: the synthetic document
+    /// and detached context element above, tokenizer-state seeding from
+    /// `context_tag_name` below, `InTemplate`-seeding for a `template`
+    /// context, and the form element pointer (added by chunk2-2 and
+    /// extended by chunk13-8/chunk14-6).
+    pub fn parse_fragment(
+        &mut self,
+        text: &str,
+        context_tag_name: &str,
+        context_namespace: &str,
+        form_owner_tag_name: Option<&str>,
+    ) -> Result<Vec<XpathItemTree>, HtmlParseError> {
+        // Create a document to root the synthetic tree in, per the spec's
+        // "let document be a new Document node" step.
+        let document_node_id = self
+            .sink
+            .arena_mut()
+            .new_node(XpathItemTreeNode::DocumentNode(XpathDocumentNode::new()));
+        self.root_node = Some(document_node_id);
+
+        // The context element is never attached to the tree; it only stands
+        // in for the real `innerHTML` target when resetting the insertion
+        // mode and computing the adjusted current node.
+        let context_element =
+            self.create_element(context_tag_name.to_string(), context_namespace, None, None)?;
+        let context_element_id = self.new_node(XpathItemTreeNode::ElementNode(context_element));
+        self.context_element = Some(context_element_id);
+
+        // Let root be a new html element with no attributes, append it to
+        // document, and push it onto the stack of open elements.
+        let root_element = self.create_element("html".to_string(), HTML_NAMESPACE, None, None)?;
+        let root_id = self.new_node(XpathItemTreeNode::ElementNode(root_element));
+        self.sink.append(document_node_id, root_id);
+        self.open_elements.push(root_id);
+
+        // If the context element is a template element, push InTemplate onto
+        // the stack of template insertion modes so it becomes the current
+        // template insertion mode; `reset_the_insertion_mode_appropriately`
+        // reads that stack when it reaches a "template" node on the stack of
+        // open elements.
+        if context_tag_name == "template" && context_namespace == HTML_NAMESPACE {
+            self.template_insertion_modes.push(InsertionMode::InTemplate);
+        }
+
+        // Set the form element pointer, per the caller's assertion above —
+        // this element is synthetic and detached like the context element,
+        // never attached to `root`.
+        if let Some(form_owner_tag_name) = form_owner_tag_name {
+            let form_owner =
+                self.create_element(form_owner_tag_name.to_string(), HTML_NAMESPACE, None, None)?;
+            let form_owner_id = self.new_node(XpathItemTreeNode::ElementNode(form_owner));
+            self.form_element_pointer = Some(form_owner_id);
+        }
+
+        self.reset_the_insertion_mode_appropriately()?;
+
+        // Seed the tokenizer's state from the context element's name, the
+        // way it would already be set had the context element's start tag
+        // just been tokenized. These special parsing states only exist for
+        // HTML elements, so a foreign (SVG/MathML) context leaves the
+        // tokenizer in its default data state.
+        let initial_tokenizer_state = match context_tag_name {
+            "title" | "textarea" if context_namespace == HTML_NAMESPACE => {
+                Some(TokenizerState::RCDATA)
+            }
+            "style" | "xmp" | "iframe" | "noembed" | "noframes" | "script"
+                if context_namespace == HTML_NAMESPACE =>
+            {
+                Some(TokenizerState::RAWTEXT)
+            }
+            "plaintext" if context_namespace == HTML_NAMESPACE => Some(TokenizerState::PLAINTEXT),
+            _ => None,
+        };
+
+        let chars: Vec<char> = text.chars().collect();
+        let input_stream = VecPointerRef::new(&chars);
+        let mut tokenizer = tokenizer::Tokenizer::new(input_stream, Box::new(self));
+        if let Some(state) = initial_tokenizer_state {
+            tokenizer.set_state(state);
+        }
+        let tokenizer_error_handler = tokenizer::DefaultTokenizerErrorHandler;
+        tokenizer.set_error_handler(Box::new(&tokenizer_error_handler));
+
+        while !tokenizer.is_terminated() {
+            tokenizer.step()?;
+        }
+
+        // Return one standalone tree per child of root rather than the whole
+        // synthetic document. A node's own value holds no children field —
+        // descendant structure lives entirely in the arena's parent/child
+        // links — so each child's whole subtree has to be deep-copied into
+        // its own fresh arena via `clone_subtree` before `self.sink`'s arena
+        // is wiped below, or everything beneath the first level would become
+        // unreachable.
+        let mut fragment_trees = Vec::new();
+        for child_id in root_id.children(self.sink.arena()) {
+            let mut fragment_arena = Arena::new();
+            let fragment_document_id = fragment_arena
+                .new_node(XpathItemTreeNode::DocumentNode(XpathDocumentNode::new()));
+            let fragment_root_id =
+                clone_subtree(self.sink.arena(), child_id, &mut fragment_arena);
+            fragment_document_id.append(fragment_root_id, &mut fragment_arena);
+
+            fragment_trees.push(XpathItemTree::new(
+                fragment_arena,
+                fragment_document_id,
+                self.quirks_mode,
+            ));
+        }
+
+        *self.sink.arena_mut() = Arena::new();
+
+        Ok(fragment_trees)
+    }
+
     /// <https://html.spec.whatwg.org/multipage/parsing.html#current-node>
     pub(crate) fn current_node(&self) -> Option<&XpathItemTreeNode> {
         self.open_elements
             .last()
-            .and_then(|id| self.arena.get(*id).map(|node| node.get()))
+            .and_then(|id| self.sink.arena().get(*id).map(|node| node.get()))
     }
 
     pub(crate) fn current_node_id(&self) -> Option<NodeId> {
@@ -341,19 +1026,34 @@ impl HtmlParser {
     pub(crate) fn top_node(&self) -> Option<&XpathItemTreeNode> {
         self.open_elements
             .first()
-            .map(|id| self.arena.get(*id).unwrap().get())
+            .map(|id| self.sink.arena().get(*id).unwrap().get())
     }
 
     pub(crate) fn top_node_mut(&mut self) -> Option<&mut XpathItemTreeNode> {
         self.open_elements
             .first()
-            .map(|id| self.arena.get_mut(*id).unwrap().get_mut())
+            .map(|id| self.sink.arena_mut().get_mut(*id).unwrap().get_mut())
     }
 
+    /// Nothing recorded here carries a [`SourcePosition`]/[`crate::html::grammar::tokenizer::span::Span`]:
+    /// `current_position` is tracked and handed to `error_emitted` (see
+    /// [`RecoveringParseResult`]'s docs), but a node built from `node`
+    /// doesn't retain where in the source it came from, so a caller walking
+    /// the resulting tree has no way to map a node back to a source range.
+    /// Exposing that means storing `self.current_position` (or, once
+    /// `tokenizer::span` is wired, a `Span`) on `node` before inserting
+    /// it — blocked on deciding where that field lives, since
+    /// `XpathItemTreeNode` as constructed and matched throughout this file
+    /// is an enum with variants like `ElementNode`/`AttributeNode`, while
+    /// the type of that name actually defined in `crate::xpath::grammar`
+    /// is a different, struct-shaped type wrapping its own
+    /// `XpathItemTreeNodeData` enum that has no `AttributeNode` variant at
+    /// all. That mismatch predates this position-tracking work and isn't
+    /// something to paper over here by picking a side.
     pub(crate) fn new_node(&mut self, node: XpathItemTreeNode) -> NodeId {
-        let id = self.arena.new_node(node);
+        let id = self.sink.arena_mut().new_node(node);
 
-        let node: &mut XpathItemTreeNode = self.arena.get_mut(id).unwrap().get_mut();
+        let node: &mut XpathItemTreeNode = self.sink.arena_mut().get_mut(id).unwrap().get_mut();
 
         if let XpathItemTreeNode::ElementNode(element) = node {
             element.set_id(id);
@@ -367,25 +1067,28 @@ impl HtmlParser {
     pub(crate) fn open_elements_as_nodes(&self) -> Vec<&XpathItemTreeNode> {
         self.open_elements
             .iter()
-            .map(|id| self.arena.get(*id).unwrap().get())
+            .map(|id| self.sink.arena().get(*id).unwrap().get())
             .collect()
     }
 
     pub(crate) fn open_elements_has_element(&self, tag_name: &str) -> bool {
         self.open_elements
             .iter()
-            .any(|id| match self.arena.get(*id).unwrap().get() {
+            .any(|id| match self.sink.arena().get(*id).unwrap().get() {
                 XpathItemTreeNode::ElementNode(element) => element.name == tag_name,
                 _ => false,
             })
     }
 
-    pub(crate) fn handle_error(&self, error: HtmlParserError) -> Result<(), HtmlParseError> {
+    pub(crate) fn handle_error(&mut self, error: HtmlParserError) -> Result<(), HtmlParseError> {
+        if let Some(trace) = self.trace.as_mut() {
+            trace.error(&error);
+        }
+
         match error {
-            HtmlParserError::MinorError(err) => {
-                dbg!(err);
-                Ok(())
-            }
+            HtmlParserError::MinorError(message) => self
+                .error_handler
+                .error_emitted(HtmlParseErrorType::Other(message), self.current_position),
             HtmlParserError::FatalError(err) => Err(HtmlParseError::new(&err)),
         }
     }
@@ -396,10 +1099,7 @@ impl HtmlParser {
         name: String,
         value: String,
     ) -> Result<(), HtmlParseError> {
-        let attribute = AttributeNode::new(name, value);
-        let item_id = self.new_node(XpathItemTreeNode::AttributeNode(attribute));
-
-        element_id.append(item_id, &mut self.arena);
+        self.sink.set_attribute(element_id, name, value);
 
         Ok(())
     }
@@ -434,10 +1134,15 @@ impl HtmlParser {
         if let Some(adjusted_insertion_location) = adjusted_insertion_location {
             #[cfg(feature = "debug_prints")]
             {
-                let element = self.arena.get(adjusted_insertion_location).unwrap().get();
+                let element = self
+                    .sink
+                    .arena()
+                    .get(adjusted_insertion_location.parent)
+                    .unwrap()
+                    .get();
                 println!("child of: {:?}", element);
             }
-            adjusted_insertion_location.append(element_id, &mut self.arena);
+            adjusted_insertion_location.insert(element_id, self.sink.arena_mut());
         }
 
         Ok(element_id)
@@ -469,7 +1174,7 @@ impl HtmlParser {
     ) -> Result<(), HtmlParseError> {
         let adjusted_insertion_location = self.appropriate_place_for_inserting_a_node(None)?;
 
-        adjusted_insertion_location.append(element_id, &mut self.arena);
+        adjusted_insertion_location.insert(element_id, self.sink.arena_mut());
 
         Ok(())
     }
@@ -480,31 +1185,44 @@ impl HtmlParser {
         comment: CommentToken,
         parent_override: Option<NodeId>,
     ) -> Result<(), HtmlParseError> {
-        let comment_id = CommentNode::create(comment.data, &mut self.arena);
+        let comment_id = CommentNode::create(comment.data, self.sink.arena_mut());
 
         let adjusted_insertion_location = if let Some(parent) = parent_override {
-            parent
+            InsertionLocation {
+                parent,
+                before_sibling: None,
+            }
         } else {
             self.appropriate_place_for_inserting_a_node(None)?
         };
 
-        adjusted_insertion_location.append(comment_id, &mut self.arena);
+        adjusted_insertion_location.insert(comment_id, self.sink.arena_mut());
 
         Ok(())
     }
 
     /// <https://html.spec.whatwg.org/multipage/parsing.html#appropriate-place-for-inserting-a-node>
+    ///
+    /// When [`HtmlParser::foster_parenting`] is set, content that would
+    /// otherwise land directly inside a `table`/`tbody`/`tfoot`/`thead`/`tr`
+    /// is redirected to just before that table (or, failing that, the
+    /// element above it on the stack of open elements) instead, mirroring
+    /// html5ever's `TreeSink`-level insertion point. `insert_an_html_element`
+    /// (via [`HtmlParser::insert_foreign_element`]) and
+    /// [`HtmlParser::insert_character`] both resolve their insertion point
+    /// through this method, so toggling the flag around a call is enough to
+    /// foster-parent whatever it inserts.
     pub(crate) fn appropriate_place_for_inserting_a_node(
         &self,
         override_target: Option<NodeId>,
-    ) -> Result<NodeId, HtmlParseError> {
+    ) -> Result<InsertionLocation, HtmlParseError> {
         let target = if let Some(override_target) = override_target {
             override_target
         } else {
             let open_elements: Vec<&XpathItemTreeNode> = self
                 .open_elements
                 .iter()
-                .map(|id| self.arena.get(*id).unwrap().get())
+                .map(|id| self.sink.arena().get(*id).unwrap().get())
                 .collect();
 
             #[cfg(feature = "debug_prints")]
@@ -520,11 +1238,73 @@ impl HtmlParser {
             let last_template = self.get_last_element_by_tag_name("template");
             let last_table = self.get_last_element_by_tag_name("table");
 
-            // if there is a last template element and either there is no last table element or the last table element is lower in the stack of open elements than the last template element
-            // then the adjusted insertion location is inside the last template element's template contents.
-            todo!()
+            // If there is a last template element and either there is no last
+            // table element, or last template is lower (more recently added)
+            // in the stack of open elements than last table, then the
+            // adjusted insertion location is inside last template element.
+            //
+            // This implementation has no separate "template contents"
+            // fragment; a template's children live directly under the
+            // template element, like any other element.
+            let template_is_lower_than_table = match (&last_template, &last_table) {
+                (Some((template_index, _)), Some((table_index, _))) => {
+                    template_index > table_index
+                }
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+
+            // Only a `table`/`tbody`/`tfoot`/`thead`/`tr` target is redirected;
+            // any other target receives the node as usual.
+            let target_is_table_like = match self.sink.arena().get(target).map(|node| node.get()) {
+                Some(XpathItemTreeNode::ElementNode(element)) => ["table", "tbody", "tfoot", "thead", "tr"]
+                    .iter()
+                    .any(|name| element.name == *name),
+                _ => false,
+            };
+
+            if !target_is_table_like {
+                InsertionLocation {
+                    parent: target,
+                    before_sibling: None,
+                }
+            } else if template_is_lower_than_table {
+                let (_, template_id) = last_template.expect("checked above");
+                InsertionLocation {
+                    parent: template_id,
+                    before_sibling: None,
+                }
+            } else if let Some((last_table_index, last_table_id)) = last_table {
+                match self.sink.parent(last_table_id) {
+                    // If last table has a parent node, then the adjusted
+                    // insertion location is inside last table's parent node,
+                    // immediately before last table.
+                    Some(parent) => InsertionLocation {
+                        parent,
+                        before_sibling: Some(last_table_id),
+                    },
+                    // Otherwise, the adjusted insertion location is inside the
+                    // element immediately above last table in the stack of
+                    // open elements, after its last child (if any).
+                    None => InsertionLocation {
+                        parent: self.open_elements[last_table_index - 1],
+                        before_sibling: None,
+                    },
+                }
+            } else {
+                // There is no last table; the adjusted insertion location is
+                // inside the first element in the stack of open elements
+                // (the html element).
+                InsertionLocation {
+                    parent: self.open_elements[0],
+                    before_sibling: None,
+                }
+            }
         } else {
-            target
+            InsertionLocation {
+                parent: target,
+                before_sibling: None,
+            }
         };
 
         Ok(adjusted_insertion_location)
@@ -533,7 +1313,7 @@ impl HtmlParser {
     fn get_last_element_by_tag_name(&self, tag_name: &str) -> Option<(usize, NodeId)> {
         for i in (0..self.open_elements.len()).rev() {
             let node_id = self.open_elements[i];
-            if let Some(node) = self.arena.get(node_id) {
+            if let Some(node) = self.sink.arena().get(node_id) {
                 if let XpathItemTreeNode::ElementNode(element) = node.get() {
                     if element.name == tag_name {
                         return Some((i, node_id));
@@ -575,8 +1355,9 @@ impl HtmlParser {
         prefix: Option<&str>,
         is: Option<&str>,
     ) -> Result<ElementNode, HtmlParseError> {
-        // TODO: namespace?
-        let element = ElementNode::new(local_name);
+        let mut element = ElementNode::new(local_name);
+        element.namespace = namespace.to_string();
+        element.prefix = prefix.map(|prefix| prefix.to_string());
 
         Ok(element)
     }
@@ -585,8 +1366,8 @@ impl HtmlParser {
     pub(crate) fn reconstruct_the_active_formatting_elements(
         &mut self,
     ) -> Result<(), HtmlParseError> {
-        fn step_4_rewind(
-            parser: &mut HtmlParser,
+        fn step_4_rewind<S: TreeSink<Handle = NodeId> + Default>(
+            parser: &mut HtmlParser<S>,
             entry: &NodeEntry,
             entry_index: usize,
         ) -> Result<(), HtmlParseError> {
@@ -624,8 +1405,8 @@ impl HtmlParser {
             }
         }
 
-        fn step_7_advance(
-            parser: &mut HtmlParser,
+        fn step_7_advance<S: TreeSink<Handle = NodeId> + Default>(
+            parser: &mut HtmlParser<S>,
             entry_index: usize,
         ) -> Result<(), HtmlParseError> {
             let (new_index, new_entry) = parser
@@ -646,8 +1427,8 @@ impl HtmlParser {
             return step_8_create(parser, &new_entry, new_index);
         }
 
-        fn step_8_create(
-            parser: &mut HtmlParser,
+        fn step_8_create<S: TreeSink<Handle = NodeId> + Default>(
+            parser: &mut HtmlParser<S>,
             entry: &NodeEntry,
             index: usize,
         ) -> Result<(), HtmlParseError> {
@@ -688,10 +1469,11 @@ impl HtmlParser {
 
     /// <https://html.spec.whatwg.org/multipage/parsing.html#insert-a-character>
     pub(crate) fn insert_character(&mut self, data: Vec<char>) -> Result<(), HtmlParseError> {
-        let adjusted_insertion_location_id = self.appropriate_place_for_inserting_a_node(None)?;
+        let adjusted_insertion_location = self.appropriate_place_for_inserting_a_node(None)?;
         let node = self
-            .arena
-            .get(adjusted_insertion_location_id)
+            .sink
+            .arena()
+            .get(adjusted_insertion_location.parent)
             .unwrap()
             .get();
 
@@ -700,17 +1482,12 @@ impl HtmlParser {
             return Ok(());
         }
 
-        // the adjusted insertion location in this implementation returns the parent node id
-        // where we are expected to insert the new node as the last child of this parent node.
-        // this means the previous sibling of the adjusted insertion location is the current last child of the parent node before inserting the new node.
-        let prev_sibling_id = self
-            .arena
-            .get(adjusted_insertion_location_id)
-            .unwrap()
-            .last_child();
+        // the node that would land immediately before the new text node, were
+        // it inserted at the adjusted insertion location right now.
+        let prev_sibling_id = adjusted_insertion_location.previous_sibling(self.sink.arena());
 
         let prev_sibling: Option<&mut XpathItemTreeNode> =
-            prev_sibling_id.map(|id| self.arena.get_mut(id).unwrap().get_mut());
+            prev_sibling_id.map(|id| self.sink.arena_mut().get_mut(id).unwrap().get_mut());
 
         if let Some(&mut XpathItemTreeNode::TextNode(ref mut text)) = prev_sibling {
             // If the adjusted insertion location's last child is a Text node, append the data to that Text node.
@@ -721,7 +1498,8 @@ impl HtmlParser {
             let text = XpathItemTreeNode::TextNode(TextNode::new(string));
             let text_id = self.new_node(text);
 
-            self.arena
+            self.sink
+                .arena_mut()
                 .get_mut(text_id)
                 .unwrap()
                 .get_mut()
@@ -729,7 +1507,7 @@ impl HtmlParser {
                 .unwrap()
                 .set_id(text_id);
 
-            adjusted_insertion_location_id.append(text_id, &mut self.arena);
+            adjusted_insertion_location.insert(text_id, self.sink.arena_mut());
         }
 
         Ok(())
@@ -742,7 +1520,7 @@ impl HtmlParser {
         element_types: Vec<&str>,
     ) -> bool {
         for node_id in self.open_elements.iter().rev() {
-            if let Some(node) = self.arena.get(*node_id) {
+            if let Some(node) = self.sink.arena().get(*node_id) {
                 if let XpathItemTreeNode::ElementNode(element) = node.get() {
                     if tag_names.contains(&element.name.as_str()) {
                         return true;
@@ -784,6 +1562,85 @@ impl HtmlParser {
         self.has_an_element_in_the_specific_scope(vec![tag_name], element_types)
     }
 
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#has-an-element-in-table-scope>
+    pub(crate) fn has_an_element_in_table_scope(&self, tag_name: &str) -> bool {
+        self.has_an_element_in_the_specific_scope(vec![tag_name], vec!["html", "table", "template"])
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#has-an-element-in-select-scope>
+    ///
+    /// Select scope's stopper set is the inverse of the other scopes': every
+    /// element type stops the search *except* `optgroup` and `option`, so
+    /// this can't be expressed via [`Self::has_an_element_in_the_specific_scope`]
+    /// and gets its own loop.
+    pub(crate) fn has_an_element_in_select_scope(&self, tag_name: &str) -> bool {
+        for node_id in self.open_elements.iter().rev() {
+            if let Some(node) = self.sink.arena().get(*node_id) {
+                if let XpathItemTreeNode::ElementNode(element) = node.get() {
+                    if element.name == tag_name {
+                        return true;
+                    }
+
+                    if !["optgroup", "option"].contains(&element.name.as_str()) {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#clear-the-stack-back-to-a-table-context>
+    pub(crate) fn clear_the_stack_back_to_a_table_context(&mut self) -> Result<(), HtmlParseError> {
+        while let Some(node) = self.current_node() {
+            if let XpathItemTreeNode::ElementNode(element) = node {
+                if ["table", "template", "html"].contains(&element.name.as_str()) {
+                    break;
+                }
+            }
+
+            self.open_elements.pop();
+        }
+
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#clear-the-stack-back-to-a-table-body-context>
+    pub(crate) fn clear_the_stack_back_to_a_table_body_context(
+        &mut self,
+    ) -> Result<(), HtmlParseError> {
+        while let Some(node) = self.current_node() {
+            if let XpathItemTreeNode::ElementNode(element) = node {
+                if ["tbody", "tfoot", "thead", "template", "html"].contains(&element.name.as_str())
+                {
+                    break;
+                }
+            }
+
+            self.open_elements.pop();
+        }
+
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#clear-the-stack-back-to-a-table-row-context>
+    pub(crate) fn clear_the_stack_back_to_a_table_row_context(
+        &mut self,
+    ) -> Result<(), HtmlParseError> {
+        while let Some(node) = self.current_node() {
+            if let XpathItemTreeNode::ElementNode(element) = node {
+                if ["tr", "template", "html"].contains(&element.name.as_str()) {
+                    break;
+                }
+            }
+
+            self.open_elements.pop();
+        }
+
+        Ok(())
+    }
+
     /// <https://html.spec.whatwg.org/multipage/parsing.html#close-a-p-element>
     pub(crate) fn close_a_p_element(&mut self) -> Result<(), HtmlParseError> {
         self.generate_implied_end_tags(Some("p"))?;
@@ -812,7 +1669,7 @@ impl HtmlParser {
         tag_names: Vec<&str>,
     ) -> Result<(), HtmlParseError> {
         while let Some(node_id) = self.open_elements.pop() {
-            let node = self.arena.get(node_id).unwrap().get();
+            let node = self.sink.arena().get(node_id).unwrap().get();
             if let XpathItemTreeNode::ElementNode(element) = node {
                 if tag_names.contains(&element.name.as_str()) {
                     break;
@@ -869,6 +1726,13 @@ impl HtmlParser {
     }
 
     /// <https://html.spec.whatwg.org/multipage/parsing.html#generic-rcdata-element-parsing-algorithm>
+    ///
+    /// Used by `textarea`'s start-tag handling in `in_body_insertion_mode`.
+    /// [`HtmlParser::generic_raw_text_element_parsing_algorithm`] below is
+    /// its RAWTEXT-mode counterpart, used by `xmp`/`iframe`/`noembed` (and
+    /// would cover `noscript` too, but this parser never sets the scripting
+    /// flag, so `noscript` falls through to ordinary element insertion
+    /// instead, per spec).
     pub(crate) fn generic_rcdata_element_parsing_algorithm(
         &mut self,
         token: TagToken,
@@ -891,7 +1755,8 @@ impl HtmlParser {
         token: TagToken,
     ) -> Result<(), HtmlParseError> {
         let element = self
-            .arena
+            .sink
+            .arena()
             .get(element_id)
             .unwrap()
             .get()
@@ -901,7 +1766,7 @@ impl HtmlParser {
         let elements_since_marker = self.active_formatting_elements.iter().map_while(
             |node_or_marker| match node_or_marker {
                 NodeOrMarker::Node(entry) => {
-                    let node = self.arena.get(entry.node_id).unwrap().get();
+                    let node = self.sink.arena().get(entry.node_id).unwrap().get();
                     match node {
                         XpathItemTreeNode::ElementNode(element) => Some(element),
                         _ => None,
@@ -911,14 +1776,14 @@ impl HtmlParser {
             },
         );
 
-        let element_attributes = element.attributes_arena(&self.arena);
+        let element_attributes = element.attributes_arena(self.sink.arena());
         let matching_elements = elements_since_marker
             .filter(|e| {
                 if e.name != element.name || e.namespace != element.namespace {
                     return false;
                 }
 
-                let e_attributes = e.attributes_arena(&self.arena);
+                let e_attributes = e.attributes_arena(self.sink.arena());
                 if e_attributes.len() != element_attributes.len() {
                     return false;
                 }
@@ -967,7 +1832,7 @@ impl HtmlParser {
             .rev()
             .map_while(|node_or_marker| {
                 if let NodeOrMarker::Node(entry) = node_or_marker {
-                    let node = self.arena.get(entry.node_id).unwrap().get();
+                    let node = self.sink.arena().get(entry.node_id).unwrap().get();
                     if let XpathItemTreeNode::ElementNode(element) = node {
                         return Some(element);
                     }
@@ -1011,28 +1876,33 @@ impl HtmlParser {
 
     /// <https://html.spec.whatwg.org/multipage/parsing.html#reset-the-insertion-mode-appropriately>
     pub(crate) fn reset_the_insertion_mode_appropriately(&mut self) -> Result<(), HtmlParseError> {
-        fn step_3_loop(
-            parser: &mut HtmlParser,
+        fn step_3_loop<S: TreeSink<Handle = NodeId> + Default>(
+            parser: &mut HtmlParser<S>,
             node_id: NodeId,
             last: bool,
         ) -> Result<(), HtmlParseError> {
             let mut last = last;
+            let mut node_id = node_id;
             if node_id == parser.open_elements[0] {
                 last = true;
 
-                // TODO: html fragment parsing algorithm
+                // If the parser was created as part of the HTML fragment
+                // parsing algorithm, node is instead the context element.
+                if let Some(context_element) = parser.context_element {
+                    node_id = context_element;
+                }
             }
 
             return step_4(parser, node_id, last);
         }
 
-        fn step_4(
-            parser: &mut HtmlParser,
+        fn step_4<S: TreeSink<Handle = NodeId> + Default>(
+            parser: &mut HtmlParser<S>,
             node_id: NodeId,
             last: bool,
         ) -> Result<(), HtmlParseError> {
-            fn step_4_3_loop(
-                parser: &mut HtmlParser,
+            fn step_4_3_loop<S: TreeSink<Handle = NodeId> + Default>(
+                parser: &mut HtmlParser<S>,
                 ancestor_id: NodeId,
                 last: bool,
             ) -> Result<(), HtmlParseError> {
@@ -1057,7 +1927,8 @@ impl HtmlParser {
                         ))?;
 
                 let ancestor = parser
-                    .arena
+                    .sink
+                    .arena()
                     .get(*ancestor_id)
                     .unwrap()
                     .get()
@@ -1076,12 +1947,15 @@ impl HtmlParser {
                 return step_4_3_loop(parser, *ancestor_id, last);
             }
 
-            fn step_4_8_done(parser: &mut HtmlParser) -> Result<(), HtmlParseError> {
+            fn step_4_8_done<S: TreeSink<Handle = NodeId> + Default>(
+                parser: &mut HtmlParser<S>,
+            ) -> Result<(), HtmlParseError> {
                 parser.insertion_mode = InsertionMode::InSelect;
                 Ok(())
             }
             let node = parser
-                .arena
+                .sink
+                .arena()
                 .get(node_id)
                 .unwrap()
                 .get()
@@ -1205,16 +2079,6 @@ impl HtmlParser {
         Ok(())
     }
 
-    fn adjusted_current_node_id(&self) -> Result<NodeId, HtmlParseError> {
-        if let Some(context_element) = self.context_element {
-            if self.open_elements.len() == 1 {
-                return Ok(context_element);
-            }
-        }
-
-        self.current_node_id_result()
-    }
-
     /// <https://html.spec.whatwg.org/multipage/parsing.html#generate-all-implied-end-tags-thoroughly>
     pub(crate) fn generate_all_implied_end_tags_thoroughly(
         &mut self,
@@ -1267,64 +2131,137 @@ impl Acknowledgement {
     }
 }
 
-impl Parser for HtmlParser {
+/// Tells [`HtmlParser::token_emitted`]'s dispatch loop what to do once an
+/// insertion-mode method has handled (or deferred) the current token.
+///
+/// Modelled on html5ever's `ProcessResult`: every "anything else, reprocess
+/// this token in the new insertion mode" branch sets
+/// `self.pending_process_result = ProcessResult::Reprocess(new_mode, token)`
+/// instead of recursively re-entering the parser, so the dispatch loop can
+/// keep feeding the token through successive modes itself, with no recursion
+/// and a single place to trace `(mode, token)` pairs.
+#[derive(Debug, Default)]
+pub(crate) enum ProcessResult {
+    #[default]
+    Done,
+    Reprocess(InsertionMode, HtmlToken),
+}
+
+impl<S: TreeSink<Handle = NodeId> + Default> Parser for HtmlParser<S> {
     fn token_emitted(&mut self, token: HtmlToken) -> Result<Acknowledgement, HtmlParseError> {
+        self.current_position.advance(&Self::token_text(&token));
+
+        // Checked here rather than in one insertion mode: a `textarea`'s
+        // next token is handled by the "text" mode, not "in body".
+        if self.ignore_next_line_feed {
+            self.ignore_next_line_feed = false;
+
+            if matches!(token, HtmlToken::Character('\n')) {
+                return Ok(Acknowledgement::no());
+            }
+        }
+
         let self_closing = match &token {
             HtmlToken::TagToken(tag) => tag.self_closing(),
             _ => false,
         };
 
-        #[cfg(feature = "debug_prints")]
-        {
-            if let HtmlToken::TagToken(TagTokenType::StartTag(token)) = &token {
-                println!("start tag: {}", token.tag_name);
+        let mut token = token;
+
+        let acknowledgement = loop {
+            #[cfg(feature = "debug_prints")]
+            {
+                if let HtmlToken::TagToken(TagTokenType::StartTag(token)) = &token {
+                    println!("start tag: {}", token.tag_name);
+                }
+
+                if let HtmlToken::TagToken(TagTokenType::EndTag(token)) = &token {
+                    println!("end tag: {}", token.tag_name);
+                }
             }
 
-            if let HtmlToken::TagToken(TagTokenType::EndTag(token)) = &token {
-                println!("end tag: {}", token.tag_name);
+            if self.trace.is_some() {
+                let open_elements: Vec<&str> = self
+                    .open_elements
+                    .iter()
+                    .filter_map(|id| match self.sink.arena().get(*id).unwrap().get() {
+                        XpathItemTreeNode::ElementNode(element) => Some(element.name.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+
+                self.trace
+                    .as_mut()
+                    .unwrap()
+                    .step(self.insertion_mode, &token, &open_elements);
             }
-        }
 
-        let acknowledgement = match self.insertion_mode {
-            InsertionMode::Initial => self.initial_insertion_mode(token),
-            InsertionMode::BeforeHtml => self.before_html_insertion_mode(token),
-            InsertionMode::BeforeHead => self.before_head_insertion_mode(token),
-            InsertionMode::InHead => self.in_head_insertion_mode(token),
-            InsertionMode::InHeadNoscript => todo!(),
-            InsertionMode::AfterHead => self.after_head_insertion_mode(token),
-            InsertionMode::InBody => self.in_body_insertion_mode(token),
-            InsertionMode::Text => self.text_insertion_mode(token),
-            InsertionMode::InTable => todo!(),
-            InsertionMode::InTableText => todo!(),
-            InsertionMode::InCaption => todo!(),
-            InsertionMode::InColumnGroup => todo!(),
-            InsertionMode::InTableBody => todo!(),
-            InsertionMode::InRow => todo!(),
-            InsertionMode::InCell => todo!(),
-            InsertionMode::InSelect => todo!(),
-            InsertionMode::InSelectInTable => todo!(),
-            InsertionMode::InTemplate => self.in_template_insertion_mode(token),
-            InsertionMode::AfterBody => self.after_body_insertion_mode(token),
-            InsertionMode::InFrameset => todo!(),
-            InsertionMode::AfterFrameset => todo!(),
-            InsertionMode::AfterAfterBody => self.after_after_body_insertion_mode(token),
-            InsertionMode::AfterAfterFrameset => todo!(),
-        }?;
+            // https://html.spec.whatwg.org/multipage/parsing.html#tree-construction-dispatcher
+            let acknowledgement = if self.use_foreign_content_rules(&token) {
+                self.foreign_content_insertion_mode(token)
+            } else {
+                match self.insertion_mode {
+                    InsertionMode::Initial => self.initial_insertion_mode(token),
+                    InsertionMode::BeforeHtml => self.before_html_insertion_mode(token),
+                    InsertionMode::BeforeHead => self.before_head_insertion_mode(token),
+                    InsertionMode::InHead => self.in_head_insertion_mode(token),
+                    InsertionMode::InHeadNoscript => self.in_head_noscript_insertion_mode(token),
+                    InsertionMode::AfterHead => self.after_head_insertion_mode(token),
+                    InsertionMode::InBody => self.in_body_insertion_mode(token),
+                    InsertionMode::Text => self.text_insertion_mode(token),
+                    InsertionMode::InTable => self.in_table_insertion_mode(token),
+                    InsertionMode::InTableText => self.in_table_text_insertion_mode(token),
+                    InsertionMode::InCaption => self.in_caption_insertion_mode(token),
+                    InsertionMode::InColumnGroup => self.in_column_group_insertion_mode(token),
+                    InsertionMode::InTableBody => self.in_table_body_insertion_mode(token),
+                    InsertionMode::InRow => self.in_row_insertion_mode(token),
+                    InsertionMode::InCell => self.in_cell_insertion_mode(token),
+                    InsertionMode::InSelect => self.in_select_insertion_mode(token),
+                    InsertionMode::InSelectInTable => {
+                        self.in_select_in_table_insertion_mode(token)
+                    }
+                    InsertionMode::InTemplate => self.in_template_insertion_mode(token),
+                    InsertionMode::AfterBody => self.after_body_insertion_mode(token),
+                    InsertionMode::InFrameset => self.in_frameset_insertion_mode(token),
+                    InsertionMode::AfterFrameset => self.after_frameset_insertion_mode(token),
+                    InsertionMode::AfterAfterBody => self.after_after_body_insertion_mode(token),
+                    InsertionMode::AfterAfterFrameset => {
+                        self.after_after_frameset_insertion_mode(token)
+                    }
+                }
+            }?;
+
+            match std::mem::take(&mut self.pending_process_result) {
+                ProcessResult::Done => break acknowledgement,
+                ProcessResult::Reprocess(mode, next_token) => {
+                    self.insertion_mode = mode;
+                    token = next_token;
+                }
+            }
+        };
 
         if self_closing && !acknowledgement.self_closed {
-            self.error_handler
-                .error_emitted(HtmlParseErrorType::NonVoidHtmlElementStartTagWithTrailingSolidus)?;
+            self.error_handler.error_emitted(
+                HtmlParseErrorType::NonVoidHtmlElementStartTagWithTrailingSolidus,
+                self.current_position,
+            )?;
         }
 
         Ok(acknowledgement)
     }
 
+    fn handle_tokenizer_error(&self, error: TokenizerError) -> Result<(), HtmlParseError> {
+        self.error_handler
+            .error_emitted(error.parse_error_type(), self.current_position)
+    }
+
     /// <https://html.spec.whatwg.org/multipage/parsing.html#adjusted-current-node>
     fn adjusted_current_node(&self) -> Option<&XpathItemTreeNode> {
         if let Some(context_element) = self.context_element {
             if self.open_elements.len() == 1 {
                 return Some(
-                    self.arena
+                    self.sink
+                        .arena()
                         .get(context_element)
                         .expect("context element not in arena")
                         .get(),
@@ -1337,15 +2274,120 @@ impl Parser for HtmlParser {
 }
 
 pub trait ParseErrorHandler {
-    fn error_emitted(&self, error: HtmlParseErrorType) -> Result<(), HtmlParseError>;
+    fn error_emitted(
+        &self,
+        error: HtmlParseErrorType,
+        position: SourcePosition,
+    ) -> Result<(), HtmlParseError>;
 }
 
+/// Per spec, parse errors are advisory: a conforming consumer doesn't need to
+/// stop parsing because of one. This handler reflects that by ignoring every
+/// error; use [`CollectingParseErrorHandler`] to capture them instead, or to
+/// fail on the first one via its `strict` flag.
 pub struct DefaultParseErrorHandler;
 
 impl ParseErrorHandler for DefaultParseErrorHandler {
-    fn error_emitted(&self, error: HtmlParseErrorType) -> Result<(), HtmlParseError> {
-        Err(HtmlParseError {
-            message: format!("{:?}", error),
-        })
+    fn error_emitted(
+        &self,
+        _error: HtmlParseErrorType,
+        _position: SourcePosition,
+    ) -> Result<(), HtmlParseError> {
+        Ok(())
+    }
+}
+
+/// Accumulates every parse error emitted during tree construction instead of
+/// discarding or immediately failing on them.
+///
+/// Pass one to [`HtmlParser::with_error_handler`], then read back the
+/// collected errors with [`CollectingParseErrorHandler::take_errors`] once
+/// parsing finishes (or use [`parse_collecting_errors`] for a ready-made
+/// entry point).
+pub struct CollectingParseErrorHandler {
+    /// If `true`, the first error emitted becomes a fatal [`HtmlParseError`]
+    /// instead of being collected.
+    pub strict: bool,
+    errors: std::cell::RefCell<Vec<CollectedParseError>>,
+}
+
+impl CollectingParseErrorHandler {
+    pub fn new(strict: bool) -> Self {
+        CollectingParseErrorHandler {
+            strict,
+            errors: std::cell::RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Take every error collected so far, leaving the handler empty.
+    pub fn take_errors(&self) -> Vec<CollectedParseError> {
+        self.errors.borrow_mut().drain(..).collect()
+    }
+}
+
+impl ParseErrorHandler for CollectingParseErrorHandler {
+    fn error_emitted(
+        &self,
+        error: HtmlParseErrorType,
+        position: SourcePosition,
+    ) -> Result<(), HtmlParseError> {
+        if self.strict {
+            return Err(HtmlParseError::new(&format!(
+                "{:?} at line {}, column {}",
+                error, position.line, position.column
+            )));
+        }
+
+        self.errors
+            .borrow_mut()
+            .push(CollectedParseError { error, position });
+        Ok(())
+    }
+}
+
+/// Lets callers keep their own `Rc` to read back collected errors after
+/// handing a trait-object clone to [`HtmlParser::with_error_handler`]; see
+/// [`parse_collecting_errors`].
+impl ParseErrorHandler for std::rc::Rc<CollectingParseErrorHandler> {
+    fn error_emitted(
+        &self,
+        error: HtmlParseErrorType,
+        position: SourcePosition,
+    ) -> Result<(), HtmlParseError> {
+        (**self).error_emitted(error, position)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::xpath::grammar::XpathItemTreeNodeData;
+
+    use super::*;
+
+    #[test]
+    fn parse_fragment_preserves_nesting_below_the_top_level() {
+        // arrange
+        let markup = "<div><p>hi</p></div>";
+
+        // act
+        let fragments = parse_fragment(markup, "body", HTML_NAMESPACE, None).unwrap();
+
+        // assert
+        assert_eq!(fragments.len(), 1);
+
+        let div = fragments[0].root().children(&fragments[0]).next().unwrap();
+        assert!(
+            matches!(div.data, XpathItemTreeNodeData::ElementNode(element) if element.name == "div")
+        );
+
+        let p = div.children(&fragments[0]).next().unwrap();
+        assert!(
+            matches!(p.data, XpathItemTreeNodeData::ElementNode(element) if element.name == "p")
+        );
+
+        let text = p.children(&fragments[0]).next().unwrap();
+        assert!(
+            matches!(text.data, XpathItemTreeNodeData::TextNode(text) if text.content == "hi")
+        );
     }
 }
@@ -0,0 +1,290 @@
+//! A minimal html5lib-tests-style `.dat` tree-construction harness, the
+//! tree-construction suite's counterpart to [`super::tokenizer::test_driver`]'s
+//! tokenizer harness.
+//!
+//! The `.dat` format groups records with blank lines between them, each made
+//! of `#`-prefixed sections: `#data` (the input markup), an optional
+//! `#errors` section (one expected error per line, ignored here — this
+//! parser's error set doesn't line up with html5lib's spec-error-code
+//! strings closely enough to be worth asserting on yet), an optional
+//! `#document-fragment` section naming a context element (switching the
+//! case to fragment parsing), and `#document`, which encodes the expected
+//! tree with `| ` per indentation level, `<tag>` for elements, `"text"` for
+//! text nodes, `<!-- comment -->` for comments, and an indented
+//! `name="value"` line under an element for each of its attributes.
+//!
+//! [`parse_dat_file`] reads that format into [`DatTestCase`]s. [`run_cases`]
+//! actually drives each one through [`super::parse`], dumps the resulting
+//! [`XpathItemTree`] back into the same indented shape with [`dump_tree`],
+//! and diffs it against `expected_document`, tallying pass/fail counts in a
+//! [`ConformanceReport`].
+//!
+//! That comparison only covers document-mode cases. `#document-fragment`
+//! cases would need [`super::HtmlParser::parse_fragment`] instead, which —
+//! matching the fragment-parsing algorithm's "return root's children, not
+//! the whole document" step — hands back one [`XpathItemTree`] per child of
+//! root rather than a single tree `dump_tree` can walk directly. [`run_cases`]
+//! counts those as [`TestOutcome::Unsupported`] rather than silently
+//! dropping them; comparing a `#document-fragment` case's multiple trees
+//! against its single `expected_document` section needs its own diff shape,
+//! which is a separate, not yet attempted, lift.
+use std::fmt::{self, Write};
+
+use super::{HtmlParseError, HTML_NAMESPACE, MATHML_NAMESPACE, SVG_NAMESPACE};
+use crate::xpath::grammar::{XpathItemTree, XpathItemTreeNode, XpathItemTreeNodeData};
+
+/// One test case parsed out of an html5lib `tree-construction` `.dat` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DatTestCase {
+    /// The `#data` section: the markup to parse.
+    pub(crate) data: String,
+
+    /// The `#document-fragment` section, if present: the context element's
+    /// tag name to parse `data` as the fragment contents of.
+    pub(crate) document_fragment: Option<String>,
+
+    /// The `#document` section, exactly as written (the `| `-indented
+    /// expected tree), which [`run_case`] compares [`dump_tree`]'s output to.
+    pub(crate) expected_document: String,
+}
+
+/// The result of running a single [`DatTestCase`] through the parser.
+#[derive(Debug)]
+pub(crate) enum TestOutcome {
+    /// [`dump_tree`]'s output matched `expected_document` exactly.
+    Pass,
+
+    /// [`dump_tree`]'s output didn't match; both sides are kept for the
+    /// caller to print a diff from.
+    Fail { actual: String, expected: String },
+
+    /// The parser itself returned an error instead of a tree.
+    Error(HtmlParseError),
+
+    /// A `#document-fragment` case: not run, see the module docs.
+    Unsupported,
+}
+
+/// Pass/fail/error/unsupported counts over a batch of [`DatTestCase`]s, plus
+/// every [`TestOutcome::Fail`] and [`TestOutcome::Error`] outcome, in file
+/// order, for reporting.
+#[derive(Debug, Default)]
+pub(crate) struct ConformanceReport {
+    pub(crate) passed: usize,
+    pub(crate) failed: usize,
+    pub(crate) errored: usize,
+    pub(crate) unsupported: usize,
+
+    /// `(index into the input slice, outcome)` for every `Fail` or `Error`
+    /// case; `Unsupported` cases are only counted.
+    pub(crate) failures: Vec<(usize, TestOutcome)>,
+}
+
+/// Run every case in `cases` through [`run_case`] and tally the results.
+pub(crate) fn run_cases(cases: &[DatTestCase]) -> ConformanceReport {
+    let mut report = ConformanceReport::default();
+
+    for (index, case) in cases.iter().enumerate() {
+        let outcome = run_case(case);
+
+        match &outcome {
+            TestOutcome::Pass => report.passed += 1,
+            TestOutcome::Fail { .. } => {
+                report.failed += 1;
+                report.failures.push((index, outcome));
+            }
+            TestOutcome::Error(_) => {
+                report.errored += 1;
+                report.failures.push((index, outcome));
+            }
+            TestOutcome::Unsupported => report.unsupported += 1,
+        }
+    }
+
+    report
+}
+
+/// Run a single [`DatTestCase`] through [`super::parse`] (document mode
+/// only — see the module docs for `#document-fragment`) and compare
+/// [`dump_tree`]'s output against `expected_document`.
+pub(crate) fn run_case(case: &DatTestCase) -> TestOutcome {
+    if case.document_fragment.is_some() {
+        return TestOutcome::Unsupported;
+    }
+
+    match super::parse(&case.data) {
+        Ok(tree) => {
+            let actual = dump_tree(&tree);
+            if actual == case.expected_document {
+                TestOutcome::Pass
+            } else {
+                TestOutcome::Fail {
+                    actual,
+                    expected: case.expected_document.clone(),
+                }
+            }
+        }
+        Err(error) => TestOutcome::Error(error),
+    }
+}
+
+/// Dump `tree` into the html5lib `#document` format: `| ` repeated once per
+/// indentation level, `<tag>` for elements (with a `name="value"` line per
+/// attribute, sorted by name, one level deeper), `"text"` for text nodes,
+/// `<!-- comment -->` for comments, and `<?target content?>` for processing
+/// instructions. No trailing newline, matching how [`parse_dat_file`] stores
+/// `expected_document`.
+pub(crate) fn dump_tree(tree: &XpathItemTree) -> String {
+    let mut out = String::new();
+    let mut first = true;
+
+    for child in tree.root().children(tree) {
+        if !first {
+            out.push('\n');
+        }
+        first = false;
+
+        dump_node(&child, tree, 0, &mut out).expect("writing to a String can't fail");
+    }
+
+    out
+}
+
+fn dump_node(
+    node: &XpathItemTreeNode,
+    tree: &XpathItemTree,
+    depth: usize,
+    out: &mut String,
+) -> fmt::Result {
+    match node.data {
+        XpathItemTreeNodeData::ElementNode(element) => {
+            write_indent(out, depth)?;
+            match namespace_prefix(element.namespace_uri.as_deref()) {
+                Some(prefix) => write!(out, "<{} {}>", prefix, element.name)?,
+                None => write!(out, "<{}>", element.name)?,
+            }
+
+            let mut attributes: Vec<_> = element.attributes.iter().collect();
+            attributes.sort_by(|a, b| a.name.cmp(&b.name));
+            for attribute in attributes {
+                out.push('\n');
+                write_indent(out, depth + 1)?;
+                write!(out, "{}=\"{}\"", attribute.name, attribute.value)?;
+            }
+
+            for child in node.children(tree) {
+                out.push('\n');
+                dump_node(&child, tree, depth + 1, out)?;
+            }
+        }
+        XpathItemTreeNodeData::TextNode(text) => {
+            write_indent(out, depth)?;
+            write!(out, "\"{}\"", text.content)?;
+        }
+        XpathItemTreeNodeData::CommentNode(comment) => {
+            write_indent(out, depth)?;
+            write!(out, "<!-- {} -->", comment.content)?;
+        }
+        XpathItemTreeNodeData::PINode(pi) => {
+            write_indent(out, depth)?;
+            write!(out, "<?{} {}?>", pi.target, pi.content)?;
+        }
+        XpathItemTreeNodeData::DocumentNode(_) => {
+            for child in node.children(tree) {
+                dump_node(&child, tree, depth, out)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The html5lib dump format's namespace prefix for a foreign-content
+/// element's namespace (`svg`/`math`), or `None` for the HTML namespace
+/// (including `namespace_uri: None`, the common case of a plain HTML
+/// element), which is written bare.
+fn namespace_prefix(namespace_uri: Option<&str>) -> Option<&'static str> {
+    match namespace_uri {
+        Some(uri) if uri == SVG_NAMESPACE => Some("svg"),
+        Some(uri) if uri == MATHML_NAMESPACE => Some("math"),
+        Some(uri) if uri == HTML_NAMESPACE => None,
+        _ => None,
+    }
+}
+
+/// Write `depth + 1` copies of `"| "`, the html5lib dump format's per-level
+/// indentation marker.
+fn write_indent(out: &mut String, depth: usize) -> fmt::Result {
+    for _ in 0..=depth {
+        out.write_str("| ")?;
+    }
+
+    Ok(())
+}
+
+/// Parse the contents of an html5lib-tests `tree-construction` `.dat` file
+/// into its individual test cases, in file order.
+///
+/// Unrecognized sections (`#script-on`/`#script-off`, which this parser has
+/// no scripting flag to vary) and the `#errors` section are read past but
+/// not retained; see the module docs for why `#errors` isn't compared yet.
+pub(crate) fn parse_dat_file(contents: &str) -> Vec<DatTestCase> {
+    let mut cases = Vec::new();
+
+    for record in split_records(contents) {
+        let mut data = String::new();
+        let mut document_fragment = None;
+        let mut expected_document = String::new();
+        let mut section: Option<&str> = None;
+
+        for line in record.lines() {
+            if let Some(name) = line.strip_prefix('#') {
+                section = Some(name);
+                if name == "document-fragment" {
+                    document_fragment = Some(String::new());
+                }
+                continue;
+            }
+
+            match section {
+                Some("data") => push_line(&mut data, line),
+                Some("document-fragment") => {
+                    if let Some(context) = document_fragment.as_mut() {
+                        context.push_str(line.trim());
+                    }
+                }
+                Some("document") => push_line(&mut expected_document, line),
+                _ => {
+                    // `#errors`, `#script-on`/`#script-off`, and anything
+                    // else this format defines: not compared here.
+                }
+            }
+        }
+
+        if !data.is_empty() || !expected_document.is_empty() {
+            cases.push(DatTestCase {
+                data,
+                document_fragment,
+                expected_document,
+            });
+        }
+    }
+
+    cases
+}
+
+/// Split a `.dat` file's contents on blank lines into individual records.
+fn split_records(contents: &str) -> Vec<&str> {
+    contents
+        .split("\n\n")
+        .map(str::trim_end)
+        .filter(|record| !record.is_empty())
+        .collect()
+}
+
+fn push_line(section: &mut String, line: &str) {
+    if !section.is_empty() {
+        section.push('\n');
+    }
+    section.push_str(line);
+}
@@ -0,0 +1,128 @@
+//! An abstraction over where a [`Tokenizer`](super::Tokenizer)'s tokens and
+//! errors go, modeled on html5ever's `TokenSink` trait (and on this crate's
+//! own [`TreeSink`](super::super::tree_sink::TreeSink), which does the same
+//! thing one stage further down the pipeline).
+//!
+//! [`Tokenizer`](super::Tokenizer) still routes every token through
+//! [`Tokenizer::emit`](super::Tokenizer::emit) straight into its `parser`
+//! field, and every error through
+//! [`Tokenizer::handle_error`](super::Tokenizer::handle_error) and the
+//! configured [`TokenizerErrorHandler`](super::TokenizerErrorHandler), the
+//! only consumer being [`HtmlParser`](super::super::HtmlParser)'s tree
+//! construction stage. Lifting `Tokenizer` itself to be generic over
+//! `Emitter` — so a caller could plug in a SAX-style streaming consumer, a
+//! token-filtering/rewriting pass, or a scraper that never builds a tree —
+//! means threading `Tokenizer<'a, E: Emitter>` through every state method in
+//! [`super::state_impls`] and replacing their `self.emit(...)`/
+//! `self.handle_error(...)` calls with calls through this trait. That's
+//! substantial, mechanical follow-up work; this module lands the trait on
+//! its own first so that work can happen incrementally instead of as one
+//! unreviewable rewrite.
+
+use super::{CommentToken, DoctypeToken, HtmlParseError, HtmlToken, TokenizerError};
+
+/// Where a [`Tokenizer`](super::Tokenizer)'s output goes: every emitted
+/// token and every tokenizer-stage error.
+///
+/// Per-kind methods are provided so an implementor that only cares about,
+/// say, start tags doesn't have to match on [`HtmlToken`] itself; each has a
+/// default that just wraps its argument in the matching `HtmlToken` variant
+/// and forwards to [`Self::emit_token`], so overriding none of them
+/// reproduces today's behavior exactly.
+pub(crate) trait Emitter {
+    /// Handle a token the tokenizer just emitted. The per-kind methods
+    /// below all funnel through this one by default.
+    fn emit_token(&mut self, token: HtmlToken) -> Result<(), HtmlParseError>;
+
+    /// Handle a tokenizer-stage parse error.
+    fn emit_error(&mut self, error: TokenizerError) -> Result<(), HtmlParseError>;
+
+    fn emit_character(&mut self, c: char) -> Result<(), HtmlParseError> {
+        self.emit_token(HtmlToken::Character(c))
+    }
+
+    fn emit_tag(&mut self, tag: super::TagTokenType) -> Result<(), HtmlParseError> {
+        self.emit_token(HtmlToken::TagToken(tag))
+    }
+
+    fn emit_comment(&mut self, comment: CommentToken) -> Result<(), HtmlParseError> {
+        self.emit_token(HtmlToken::Comment(comment))
+    }
+
+    fn emit_doctype(&mut self, doctype: DoctypeToken) -> Result<(), HtmlParseError> {
+        self.emit_token(HtmlToken::DocType(doctype))
+    }
+
+    fn emit_eof(&mut self) -> Result<(), HtmlParseError> {
+        self.emit_token(HtmlToken::EndOfFile)
+    }
+
+    /// Start building a new comment token, discarding any in-progress one.
+    ///
+    /// Mirrors how [`super::state_impls`]'s comment states build
+    /// [`CommentToken`] up one char at a time via `current_comment_token_mut`
+    /// rather than receiving it complete, so an emitter that wants to stream
+    /// comment text (instead of buffering the whole thing like
+    /// [`CommentToken`] does) has somewhere to plug in before the comment is
+    /// known to be finished.
+    fn init_comment(&mut self);
+
+    /// Append `c` to the comment started by [`Self::init_comment`].
+    fn push_comment_data(&mut self, c: char);
+
+    /// Emit the comment accumulated since [`Self::init_comment`].
+    fn emit_current_comment(&mut self) -> Result<(), HtmlParseError>;
+
+    /// Start building a new DOCTYPE token named `name`, discarding any
+    /// in-progress one. Mirrors `doctype_token = Some(DoctypeToken::new(...))`
+    /// in [`super::state_impls`]'s DOCTYPE states.
+    fn init_doctype(&mut self, name: String);
+
+    /// Set the public identifier of the DOCTYPE started by
+    /// [`Self::init_doctype`].
+    fn set_doctype_public_id(&mut self, id: String);
+
+    /// Set the system identifier of the DOCTYPE started by
+    /// [`Self::init_doctype`].
+    fn set_doctype_system_id(&mut self, id: String);
+
+    /// Set the force-quirks flag of the DOCTYPE started by
+    /// [`Self::init_doctype`].
+    fn set_force_quirks(&mut self, value: bool);
+
+    /// Emit the DOCTYPE accumulated since [`Self::init_doctype`].
+    fn emit_current_doctype(&mut self) -> Result<(), HtmlParseError>;
+}
+
+// Covers the things every state method in `state_impls` currently does
+// through `Tokenizer::emit`/`Tokenizer::handle_error` — emit a token of some
+// kind, raise an error — so the eventual `Tokenizer<'a, E: Emitter>` lift is
+// a mechanical swap of call sites, not a redesign of this trait.
+//
+// Unlike `TreeSink`, which has a standalone `ArenaTreeSink` default impl,
+// there's no standalone default `Emitter` to ship yet: today's "forward to
+// the parser" behavior lives as `Tokenizer::emit`/`Tokenizer::handle_error`,
+// reading `Tokenizer`'s own `parser`/`error_handler` fields directly. Turning
+// that into a real `impl Emitter` means giving `Tokenizer<'a, E: Emitter>` an
+// `emitter: E` field in place of those two, which is exactly the cross-cutting
+// rewrite this module is deferring.
+//
+// A later request asked for the emitter to own the current-comment/
+// current-doctype scratch state too, instead of `Tokenizer` building a
+// `CommentToken`/`DoctypeToken` field-by-field and handing it over complete.
+// `init_comment`/`push_comment_data`/`emit_current_comment` and
+// `init_doctype`/`set_doctype_public_id`/`set_doctype_system_id`/
+// `set_force_quirks`/`emit_current_doctype` above are that: required methods
+// (not defaulted — there's no generic way to default "where does the
+// in-progress comment text live" without picking a representation for every
+// implementor), added so the deferred rewrite above can swap `Tokenizer`'s own
+// `current_comment_token`/`doctype_token` fields for calls through them
+// alongside the `Tokenizer<'a, E: Emitter>` lift, rather than doing it as a
+// second pass afterward.
+//
+// Tag-building scratch state (`current_tag_token`/`attribute_name`/
+// `push_char_to_attribute_value`) isn't mirrored the same way yet — the
+// request that asked for comment/DOCTYPE builder methods didn't ask for tag
+// ones, and tags have more scratch fields (name, self-closing, a list of
+// attributes each with their own name/value) to cover correctly; left for
+// whichever follow-up extends this trait that far.
@@ -0,0 +1,62 @@
+//! The first step of the spec's encoding-sniffing algorithm: BOM detection.
+//!
+//! <https://html.spec.whatwg.org/multipage/parsing.html#determining-the-character-encoding>
+//!
+//! A full implementation also pre-scans the first ~1024 bytes for a
+//! `<meta charset>`/`Content-Type` hint when no BOM is present, and supports
+//! restarting tokenization from scratch in a new encoding if a `meta` found
+//! *during* tokenization contradicts an earlier `Tentative` guess. Neither
+//! is here yet: the pre-scan needs a byte-level tag/attribute scanner
+//! independent of [`super::Tokenizer`] (the real tokenizer can't run twice,
+//! once to sniff and once for real, without re-doing this exact work), and
+//! a restart needs [`super::Tokenizer`]/[`super::super::HtmlParser`] to be
+//! able to throw away their state and begin again mid-parse — both bigger
+//! than what BOM sniffing alone justifies. This module lands the BOM check
+//! and the `Confidence` it should be paired with, so that follow-up work has
+//! somewhere to plug in rather than inventing its own.
+
+/// How sure the encoding-sniffing algorithm is about the encoding it picked,
+/// per the spec's "encoding sniffing algorithm" and its effect on later
+/// `meta` tags.
+///
+/// <https://html.spec.whatwg.org/multipage/parsing.html#concept-encoding-confidence>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Confidence {
+    /// Guessed from a pre-scan or a default; a `meta charset` encountered
+    /// while tokenizing should still override it and restart parsing.
+    Tentative,
+    /// Established by a BOM, a `Content-Type` header, or an explicit caller
+    /// override; a `meta charset` found later must not change it.
+    Certain,
+    /// The encoding doesn't matter for this document (e.g. it has no textual
+    /// content to decode), so nothing should trigger a restart over it.
+    Irrelevant,
+}
+
+/// The three encodings a BOM can unambiguously identify.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BomEncoding {
+    Utf8,
+    Utf16Be,
+    Utf16Le,
+}
+
+/// Check `bytes` for one of the three BOMs the spec's encoding sniffing
+/// algorithm checks for, in the order it checks them in. Returns the
+/// encoding and how many leading bytes the BOM itself occupies (to be
+/// skipped before decoding), or `None` if `bytes` doesn't start with any of
+/// them.
+///
+/// A BOM match is always [`Confidence::Certain`] per the spec — pair this
+/// with that confidence level rather than `Tentative` when wiring it in.
+pub(crate) fn sniff_bom(bytes: &[u8]) -> Option<(BomEncoding, usize)> {
+    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        Some((BomEncoding::Utf8, 3))
+    } else if bytes.starts_with(&[0xFE, 0xFF]) {
+        Some((BomEncoding::Utf16Be, 2))
+    } else if bytes.starts_with(&[0xFF, 0xFE]) {
+        Some((BomEncoding::Utf16Le, 2))
+    } else {
+        None
+    }
+}
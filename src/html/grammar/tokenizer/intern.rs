@@ -0,0 +1,69 @@
+//! A string interner for tag and attribute names, so repeated names across a
+//! document become cheap [`Rc`]-clone handles instead of fresh `String`
+//! allocations and byte-for-byte comparisons every time.
+//!
+//! Not wired into [`super::Tokenizer`] yet: `TagToken::tag_name` and
+//! `Attribute::name` (`super::TagToken`/`super::Attribute`) are plain
+//! `String`s built up one `char` at a time via `tag_name_mut().push(c)`/
+//! `push_char_to_attribute_name` across [`super::state_impls`], and
+//! switching them to [`Atom`] touches every reader of those fields — not
+//! just inside this crate's tree-construction stage, but
+//! [`Attribute`](super::Attribute)/[`TagToken`](super::TagToken) are also
+//! `pub`, so external callers matching on `.tag_name: String` today would
+//! need to change too. This module lands the interner and its handle type
+//! on their own first, so that wiring can happen as a deliberate, reviewable
+//! follow-up instead of alongside inventing the interner itself.
+
+use std::rc::Rc;
+
+/// A cheaply-cloned interned string: an [`Rc<str>`] deduplicated through an
+/// [`Interner`], so two `Atom`s for the same text point at the same
+/// allocation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Atom(Rc<str>);
+
+impl Atom {
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::ops::Deref for Atom {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for Atom {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Deduplicates strings into [`Atom`]s. Not `Sync` (it holds
+/// `Rc<str>`s), matching [`super::Tokenizer`] itself, which isn't shared
+/// across threads either.
+#[derive(Default)]
+pub(crate) struct Interner {
+    strings: std::collections::HashSet<Rc<str>>,
+}
+
+impl Interner {
+    pub(crate) fn new() -> Self {
+        Interner::default()
+    }
+
+    /// Return the [`Atom`] for `s`, reusing an existing allocation if this
+    /// exact string has been interned before.
+    pub(crate) fn intern(&mut self, s: &str) -> Atom {
+        if let Some(existing) = self.strings.get(s) {
+            return Atom(existing.clone());
+        }
+
+        let rc: Rc<str> = Rc::from(s);
+        self.strings.insert(rc.clone());
+        Atom(rc)
+    }
+}
@@ -0,0 +1,68 @@
+//! An incremental trie over [`named_character_references::NAMED_CHARACTER_REFS`],
+//! used by [`super::state_impls::Tokenizer::named_character_reference_state`]
+//! to find the longest matching named character reference in one walk
+//! instead of scanning every entry in the table on every `&`.
+//!
+//! The previous implementation built the lookahead string once, then ran
+//! `NAMED_CHARACTER_REFS.keys().filter(|k| key.starts_with(**k)).max_by_key(len)`
+//! over all ~2200 entries for every ampersand, then did a second `.get()`
+//! lookup to fetch the matched entry's expansion. Walking this trie instead
+//! costs one hash lookup per character consumed (bounded by
+//! `NAMED_CHARACTER_REFS_MAX_LENGTH`), and a terminal node already carries
+//! its expansion, so there's no second lookup needed.
+//!
+//! Built lazily from [`NAMED_CHARACTER_REFS`](super::named_character_references::NAMED_CHARACTER_REFS)
+//! itself rather than a separately maintained entity list, so there's one
+//! source of truth for the table.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+
+use super::named_character_references::NAMED_CHARACTER_REFS;
+
+/// One node of the trie: an edge per next character, and — if some named
+/// reference's text ends exactly here — the code points it expands to.
+#[derive(Default)]
+pub(crate) struct NamedCharRefTrieNode {
+    children: HashMap<char, NamedCharRefTrieNode>,
+    expansion: Option<&'static str>,
+}
+
+impl NamedCharRefTrieNode {
+    /// Follow the edge for `c`, if this node has one.
+    pub(crate) fn child(&self, c: char) -> Option<&NamedCharRefTrieNode> {
+        self.children.get(&c)
+    }
+
+    /// The expansion a named reference ending at this node maps to, if any
+    /// named reference actually ends here (as opposed to merely passing
+    /// through on the way to a longer one — e.g. `&not` is itself a terminal
+    /// node on the way to `&notin;`, both have an expansion).
+    pub(crate) fn expansion(&self) -> Option<&'static str> {
+        self.expansion
+    }
+
+    fn insert(&mut self, name: &'static str, expansion: &'static str) {
+        let mut node = self;
+        for c in name.chars() {
+            node = node.children.entry(c).or_default();
+        }
+        node.expansion = Some(expansion);
+    }
+}
+
+/// The root of the trie, built once from every entry in
+/// [`NAMED_CHARACTER_REFS`]. Entity names in that table already include
+/// their leading `&` (and trailing `;` when the spec requires one), so
+/// walking from this root one character at a time — starting with the `&`
+/// itself — reaches the same terminal nodes the table's keys name.
+pub(crate) static NAMED_CHARACTER_REFS_TRIE: Lazy<NamedCharRefTrieNode> = Lazy::new(|| {
+    let mut root = NamedCharRefTrieNode::default();
+
+    for (&name, &expansion) in NAMED_CHARACTER_REFS.iter() {
+        root.insert(name, expansion);
+    }
+
+    root
+});
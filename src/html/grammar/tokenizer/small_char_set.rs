@@ -0,0 +1,77 @@
+//! A 64-bit bitset for scanning ahead over a run of "uninteresting" code
+//! points in one pass, porting html5ever's `SmallCharSet`/
+//! `pop_except_from` technique.
+//!
+//! [`data_state`](super::Tokenizer::data_state),
+//! [`rcdata_state`](super::Tokenizer::rcdata_state),
+//! [`rawtext_state`](super::Tokenizer::rawtext_state), and
+//! [`script_data_state`](super::Tokenizer::script_data_state) still emit one
+//! [`HtmlToken::Character`](super::HtmlToken::Character) per code point —
+//! [`scan_run`] and [`HtmlToken::Characters`](super::HtmlToken::Characters)
+//! (see that variant's doc comment) are what a batched version of those
+//! four states would use, but actually switching them over means auditing
+//! every insertion-mode handler in
+//! [`super::super::insertion_mode_impls`] that currently pattern-matches
+//! `HtmlToken::Character` token-at-a-time for something stateful — e.g.
+//! `before_head_insertion_mode` skipping leading ASCII whitespace one
+//! character at a time, or the in-table modes' "pending table character
+//! tokens" list deciding foster parenting only once a non-whitespace
+//! character shows up in the run — and teaching each one to unpack a
+//! batch back into the same decisions. That's the same class of
+//! substantial, all-call-sites follow-up work already called out on
+//! `HtmlToken::Characters` itself; this module lands the scanning
+//! primitive on its own, not yet called from the tokenizer, so that migration can happen one state (and one
+//! insertion-mode handler) at a time instead of as one unreviewable
+//! rewrite. A throughput benchmark needs a `Cargo.toml`/benchmarking
+//! harness this checkout doesn't have; the doc comment above is what one
+//! would validate once that's available.
+
+use crate::vecpointer::VecPointerRef;
+
+/// A small set of `char`s, represented as a 64-bit mask keyed by
+/// `c as u64 & 63`. Membership needs confirming against [`Self::members`]
+/// directly — the low 6 bits of two different code points can collide —
+/// but a clear mask bit means `c` is definitely *not* a member, which is
+/// what makes scanning a long run of ordinary text cheap.
+pub(crate) struct SmallCharSet {
+    mask: u64,
+    members: Vec<char>,
+}
+
+impl SmallCharSet {
+    pub(crate) fn new(members: &[char]) -> Self {
+        let mut mask = 0u64;
+        for &c in members {
+            mask |= 1u64 << (c as u64 & 63);
+        }
+
+        SmallCharSet {
+            mask,
+            members: members.to_vec(),
+        }
+    }
+
+    /// Whether `c` is one of this set's members.
+    pub(crate) fn contains(&self, c: char) -> bool {
+        self.mask & (1u64 << (c as u64 & 63)) != 0 && self.members.contains(&c)
+    }
+}
+
+/// Consume and return every code point up to (but not including) the next
+/// member of `set`, stopping early at end of input. Returns an empty
+/// string if the very next code point is already a member (or input is
+/// empty), the same as `set` matching on the first character would.
+pub(crate) fn scan_run(input: &mut VecPointerRef<'_, char>, set: &SmallCharSet) -> String {
+    let mut run = String::new();
+
+    while let Some(c) = input.peek() {
+        if set.contains(*c) {
+            break;
+        }
+
+        run.push(*c);
+        input.next();
+    }
+
+    run
+}
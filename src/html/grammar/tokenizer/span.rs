@@ -0,0 +1,60 @@
+//! A byte-offset range into the tokenizer's input, for attaching exact
+//! source locations to emitted tokens.
+//!
+//! This is a different (and more precise) mechanism from
+//! [`SourcePosition`](super::super::SourcePosition): that one is a line/column
+//! reconstructed after the fact by replaying each token's text once it
+//! reaches [`HtmlParser`](super::super::HtmlParser), which can be off by a
+//! character or two for tag/comment/doctype tokens built from parsed fields
+//! rather than exact source bytes. A [`Span`] instead records the exact
+//! input offsets the tokenizer itself saw while producing the token, before
+//! any such reconstruction.
+//!
+//! Nothing in [`super::Tokenizer`] attaches one yet: `input_stream` (a
+//! `VecPointerRef<'a, char>`) doesn't track an offset, so every
+//! `self.input_stream.next()` call site across [`super::state_impls`] would
+//! need one added (and `emit`/`emit_current_tag_token`/
+//! `emit_current_comment_token` would need to read it back out when building
+//! the token, same for sub-spans on individual attributes as
+//! `attribute_name_state`/`attribute_value_double_quoted_state`/etc. build
+//! them up). That's a lot of call sites to touch for a feature most callers
+//! don't need, which is exactly why the request asks for it to be optional —
+//! this module lands the type the rest of that work would hand back, so it
+//! exists to agree on before the call sites change.
+//!
+//! That wiring is more blocked than usual in this checkout specifically:
+//! `VecPointerRef` itself (declared at `crate::vecpointer`) isn't present on
+//! disk here, so there's no way to confirm whether it already exposes a
+//! position accessor a `Span` could read, or whether `Tokenizer` would need
+//! to maintain its own running offset counter alongside it. Either answer
+//! still touches all ~80 of those call sites; this module doesn't guess at
+//! `VecPointerRef`'s API in its absence.
+
+/// A half-open byte range `[start, end)` into the document the tokenizer is
+/// reading, identifying exactly what produced a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub(crate) fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// The number of bytes this span covers.
+    pub(crate) fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Whether this span covers zero bytes.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Whether `offset` falls within this span.
+    pub(crate) fn contains(&self, offset: usize) -> bool {
+        (self.start..self.end).contains(&offset)
+    }
+}
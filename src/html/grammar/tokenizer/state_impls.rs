@@ -9,9 +9,9 @@ use crate::{
 };
 
 use super::{
-    named_character_references::{NAMED_CHARACTER_REFS, NAMED_CHARACTER_REFS_MAX_LENGTH},
-    Attribute, CommentToken, DoctypeToken, HtmlToken, TagToken, TagTokenType, Tokenizer,
-    TokenizerError, TokenizerState,
+    named_char_ref_trie, named_character_references::NAMED_CHARACTER_REFS_MAX_LENGTH, Attribute,
+    CommentToken, DoctypeToken, HtmlToken, TagToken, TagTokenType, Tokenizer, TokenizerError,
+    TokenizerState,
 };
 
 impl<'a> Tokenizer<'a> {
@@ -93,6 +93,104 @@ impl<'a> Tokenizer<'a> {
         Ok(())
     }
 
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#rawtext-less-than-sign-state>
+    pub(super) fn rawtext_less_than_sign_state(&mut self) -> Result<(), HtmlParseError> {
+        match self.input_stream.next() {
+            Some('/') => {
+                self.temporary_buffer.clear();
+                self.state = TokenizerState::RAWTEXTEndTagOpen;
+            }
+            _ => {
+                self.emit(HtmlToken::Character('<'))?;
+                self.reconsume_in_state(TokenizerState::RAWTEXT)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#rawtext-end-tag-open-state>
+    pub(super) fn rawtext_end_tag_open_state(&mut self) -> Result<(), HtmlParseError> {
+        match self.input_stream.next() {
+            Some(c) if c.is_ascii_alphabetic() => {
+                self.tag_token = Some(TagTokenType::EndTag(TagToken::new(String::new())));
+                self.reconsume_in_state(TokenizerState::RAWTEXTEndTagName)?;
+            }
+            _ => {
+                self.emit(HtmlToken::Character('<'))?;
+                self.emit(HtmlToken::Character('/'))?;
+                self.reconsume_in_state(TokenizerState::RAWTEXT)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#rawtext-end-tag-name-state>
+    pub(super) fn rawtext_end_tag_name_state(&mut self) -> Result<(), HtmlParseError> {
+        fn anything_else(tokenizer: &mut Tokenizer) -> Result<(), HtmlParseError> {
+            tokenizer.emit(HtmlToken::Character('<'))?;
+            tokenizer.emit(HtmlToken::Character('/'))?;
+
+            let chars: Vec<char> = tokenizer.temporary_buffer.drain(..).collect();
+            for c in chars.into_iter() {
+                tokenizer.emit(HtmlToken::Character(c))?;
+            }
+
+            tokenizer.reconsume_in_state(TokenizerState::RAWTEXT)?;
+            Ok(())
+        }
+
+        match self.input_stream.next() {
+            Some(
+                &chars::CHARACTER_TABULATION
+                | &chars::LINE_FEED
+                | &chars::FORM_FEED
+                | &chars::SPACE,
+            ) => {
+                if self.is_current_end_tag_token_appropriate() {
+                    self.state = TokenizerState::BeforeAttributeName;
+                    return Ok(());
+                }
+
+                anything_else(self)?;
+            }
+            Some('/') => {
+                if self.is_current_end_tag_token_appropriate() {
+                    self.state = TokenizerState::SelfClosingStartTag;
+                    return Ok(());
+                }
+
+                anything_else(self)?;
+            }
+            Some('>') => {
+                if self.is_current_end_tag_token_appropriate() {
+                    self.state = TokenizerState::Data;
+                    self.emit_current_tag_token()?;
+                    return Ok(());
+                }
+
+                anything_else(self)?;
+            }
+            Some(c) if c.is_ascii_uppercase() => {
+                let c = *c;
+                let lowercase = c.to_ascii_lowercase();
+                self.current_tag_token_mut()?.tag_name_mut().push(lowercase);
+
+                self.temporary_buffer.push(c);
+            }
+            Some(c) if c.is_ascii_lowercase() => {
+                let c = *c;
+                self.current_tag_token_mut()?.tag_name_mut().push(c);
+
+                self.temporary_buffer.push(c);
+            }
+            _ => anything_else(self)?,
+        }
+
+        Ok(())
+    }
+
     /// <https://html.spec.whatwg.org/multipage/parsing.html#script-data-state>
     pub(super) fn script_data_state(&mut self) -> Result<(), HtmlParseError> {
         match self.input_stream.next() {
@@ -114,6 +212,80 @@ impl<'a> Tokenizer<'a> {
         Ok(())
     }
 
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#plaintext-state>
+    pub(super) fn plaintext_state(&mut self) -> Result<(), HtmlParseError> {
+        match self.input_stream.next() {
+            Some(&chars::NULL) => {
+                self.handle_error(TokenizerError::UnexpectedNullCharacter)?;
+
+                self.emit(HtmlToken::Character(chars::FEED_REPLACEMENT_CHARACTER))?;
+            }
+            Some(c) => {
+                let current_input_character = *c;
+                self.emit(HtmlToken::Character(current_input_character))?;
+            }
+            None => self.emit(HtmlToken::EndOfFile)?,
+        };
+
+        // There is no escape from this state; every remaining code point
+        // (including further `<`) is consumed as plain character data.
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#cdata-section-state>
+    pub(super) fn cdata_section_state(&mut self) -> Result<(), HtmlParseError> {
+        match self.input_stream.next() {
+            Some(']') => {
+                self.state = TokenizerState::CDATASectionBracket;
+            }
+            None => {
+                self.handle_error(TokenizerError::EofInCdata)?;
+
+                self.emit(HtmlToken::EndOfFile)?;
+            }
+            Some(c) => {
+                let current_input_character = *c;
+                self.emit(HtmlToken::Character(current_input_character))?;
+            }
+        };
+
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#cdata-section-bracket-state>
+    pub(super) fn cdata_section_bracket_state(&mut self) -> Result<(), HtmlParseError> {
+        match self.input_stream.next() {
+            Some(']') => {
+                self.state = TokenizerState::CDATASectionEnd;
+            }
+            _ => {
+                self.emit(HtmlToken::Character(']'))?;
+                self.reconsume_in_state(TokenizerState::CDATASection)?;
+            }
+        };
+
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#cdata-section-end-state>
+    pub(super) fn cdata_section_end_state(&mut self) -> Result<(), HtmlParseError> {
+        match self.input_stream.next() {
+            Some(']') => {
+                self.emit(HtmlToken::Character(']'))?;
+            }
+            Some('>') => {
+                self.state = TokenizerState::Data;
+            }
+            _ => {
+                self.emit(HtmlToken::Character(']'))?;
+                self.emit(HtmlToken::Character(']'))?;
+                self.reconsume_in_state(TokenizerState::CDATASection)?;
+            }
+        };
+
+        Ok(())
+    }
+
     /// <https://html.spec.whatwg.org/multipage/parsing.html#tag-open-state>
     pub(super) fn tag_open_state(&mut self) -> Result<(), HtmlParseError> {
         match self.input_stream.next() {
@@ -936,7 +1108,11 @@ impl<'a> Tokenizer<'a> {
             }
         }
 
-        // TODO: check for duplicate attribtue names before emitting
+        // Duplicate attribute names are resolved once the tag is finalized
+        // (see `Tokenizer::emit_current_tag_token`), not here — the name
+        // isn't complete until `AttributeName` is left, and a later
+        // attribute with the same name can still appear before the tag
+        // ends.
 
         Ok(())
     }
@@ -1226,15 +1402,21 @@ impl<'a> Tokenizer<'a> {
         if next_seven_chars == "[CDATA[" {
             self.input_stream.next_add(7);
 
-            // if there is an adjusted current node
-            if let Some(node) = self.parser.adjusted_current_node() {
-                // ... and it is an element
-                if let XpathItemTreeNode::ElementNode(element) = node {
-                    // ... not in the html namespace
-                    if element.namespace.as_ref().map(String::as_str) != Some(HTML_NAMESPACE) {
-                        self.state = TokenizerState::CDATASection;
-                    }
-                }
+            // if there is an adjusted current node, and it is an element,
+            // and it is not in the html namespace
+            let in_foreign_content =
+                self.parser
+                    .adjusted_current_node()
+                    .is_some_and(|node| match node {
+                        XpathItemTreeNode::ElementNode(element) => {
+                            element.namespace.as_ref().map(String::as_str) != Some(HTML_NAMESPACE)
+                        }
+                        _ => false,
+                    });
+
+            if in_foreign_content {
+                self.state = TokenizerState::CDATASection;
+                return Ok(());
             }
 
             // otherwise, this is a parse error
@@ -1242,6 +1424,8 @@ impl<'a> Tokenizer<'a> {
 
             self.comment_token = Some(CommentToken::new(String::from("[CDATA[")));
             self.state = TokenizerState::BogusComment;
+
+            return Ok(());
         }
 
         // anything else is a parse error
@@ -1303,36 +1487,63 @@ impl<'a> Tokenizer<'a> {
 
     /// <https://html.spec.whatwg.org/multipage/parsing.html#comment-state>
     pub(super) fn comment_state(&mut self) -> Result<(), HtmlParseError> {
-        match self.input_stream.next() {
-            Some('<') => {
-                self.current_comment_token_mut()?.data.push_str("<");
-                self.state = TokenizerState::CommentLessThanSign;
-            }
-            Some('-') => {
-                self.state = TokenizerState::CommentEndDash;
-            }
-            Some(&chars::NULL) => {
-                self.handle_error(TokenizerError::UnexpectedNullCharacter)?;
+        // Ordinary comment text (everything but `-`, `<`, and NULL, which
+        // each need their own handling below) is scanned as one contiguous
+        // run and appended in a single `push_str`, rather than one `push`
+        // per character, since a run this state doesn't otherwise care about
+        // can be arbitrarily long.
+        let mut run = String::new();
+
+        loop {
+            match self.input_stream.next() {
+                Some('<') => {
+                    self.flush_comment_run(&mut run)?;
+                    self.current_comment_token_mut()?.data.push_str("<");
+                    self.state = TokenizerState::CommentLessThanSign;
+                    break;
+                }
+                Some('-') => {
+                    self.flush_comment_run(&mut run)?;
+                    self.state = TokenizerState::CommentEndDash;
+                    break;
+                }
+                Some(&chars::NULL) => {
+                    self.flush_comment_run(&mut run)?;
+                    self.handle_error(TokenizerError::UnexpectedNullCharacter)?;
 
-                self.current_comment_token_mut()?
-                    .data
-                    .push(chars::FEED_REPLACEMENT_CHARACTER);
-            }
-            None => {
-                self.handle_error(TokenizerError::EofInComment)?;
+                    self.current_comment_token_mut()?
+                        .data
+                        .push(chars::FEED_REPLACEMENT_CHARACTER);
+                }
+                None => {
+                    self.flush_comment_run(&mut run)?;
+                    self.handle_error(TokenizerError::EofInComment)?;
 
-                self.emit_current_comment_token()?;
-                self.emit(HtmlToken::EndOfFile)?;
-            }
-            Some(c) => {
-                let c = *c;
-                self.current_comment_token_mut()?.data.push(c);
+                    self.emit_current_comment_token()?;
+                    self.emit(HtmlToken::EndOfFile)?;
+                    break;
+                }
+                Some(c) => {
+                    run.push(*c);
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Append `run` to the current comment's data if it has anything in it,
+    /// then clear it. Used by [`Self::comment_state`] to flush the
+    /// contiguous run it's been accumulating before handling a terminator.
+    fn flush_comment_run(&mut self, run: &mut String) -> Result<(), HtmlParseError> {
+        if !run.is_empty() {
+            self.current_comment_token_mut()?.data.push_str(run);
+            run.clear();
+        }
+
+        Ok(())
+    }
+
     /// <https://html.spec.whatwg.org/multipage/parsing.html#comment-less-than-sign-state>
     pub(super) fn comment_less_than_sign_state(&mut self) -> Result<(), HtmlParseError> {
         match self.input_stream.next() {
@@ -1562,6 +1773,12 @@ impl<'a> Tokenizer<'a> {
     }
 
     /// <https://html.spec.whatwg.org/multipage/parsing.html#doctype-name-state>
+    ///
+    /// Unlike [`Self::comment_state`] or the quoted identifier states, this
+    /// one isn't batched into a scan-a-run-then-append-once loop: every
+    /// ASCII uppercase letter needs its own case-fold check, so there's no
+    /// terminator-free run to accumulate — the per-character branch below
+    /// already does the minimum work per character.
     pub(super) fn doctype_name_state(&mut self) -> Result<(), HtmlParseError> {
         match self.input_stream.next() {
             Some(&chars::CHARACTER_TABULATION)
@@ -1747,33 +1964,37 @@ impl<'a> Tokenizer<'a> {
     pub(super) fn doctype_public_identifier_double_quoted_state(
         &mut self,
     ) -> Result<(), HtmlParseError> {
-        match self.input_stream.next() {
-            Some('"') => {
-                self.state = TokenizerState::AfterDOCTYPEPublicIdentifier;
-            }
-            Some(&chars::NULL) => {
-                self.handle_error(TokenizerError::UnexpectedNullCharacter)?;
+        let mut run = String::new();
+
+        loop {
+            match self.input_stream.next() {
+                Some('"') => {
+                    self.flush_doctype_public_identifier_run(&mut run)?;
+                    self.state = TokenizerState::AfterDOCTYPEPublicIdentifier;
+                    break;
+                }
+                Some(&chars::NULL) => {
+                    self.flush_doctype_public_identifier_run(&mut run)?;
+                    self.handle_error(TokenizerError::UnexpectedNullCharacter)?;
 
-                self.current_doctype_token_mut()?
-                    .public_identifier
-                    .as_mut()
-                    .unwrap()
-                    .push(chars::FEED_REPLACEMENT_CHARACTER);
-            }
-            None => {
-                self.handle_error(TokenizerError::EofInDoctype)?;
+                    self.current_doctype_token_mut()?
+                        .public_identifier
+                        .as_mut()
+                        .unwrap()
+                        .push(chars::FEED_REPLACEMENT_CHARACTER);
+                }
+                None => {
+                    self.flush_doctype_public_identifier_run(&mut run)?;
+                    self.handle_error(TokenizerError::EofInDoctype)?;
 
-                self.current_doctype_token_mut()?.force_quirks = true;
-                self.emit_current_doctype_token()?;
-                self.emit(HtmlToken::EndOfFile)?;
-            }
-            Some(c) => {
-                let c = *c;
-                self.current_doctype_token_mut()?
-                    .public_identifier
-                    .as_mut()
-                    .unwrap()
-                    .push(c);
+                    self.current_doctype_token_mut()?.force_quirks = true;
+                    self.emit_current_doctype_token()?;
+                    self.emit(HtmlToken::EndOfFile)?;
+                    break;
+                }
+                Some(c) => {
+                    run.push(*c);
+                }
             }
         }
 
@@ -1784,39 +2005,62 @@ impl<'a> Tokenizer<'a> {
     pub(super) fn doctype_public_identifier_single_quoted_state(
         &mut self,
     ) -> Result<(), HtmlParseError> {
-        match self.input_stream.next() {
-            Some('\'') => {
-                self.state = TokenizerState::AfterDOCTYPEPublicIdentifier;
-            }
-            Some(&chars::NULL) => {
-                self.handle_error(TokenizerError::UnexpectedNullCharacter)?;
+        let mut run = String::new();
+
+        loop {
+            match self.input_stream.next() {
+                Some('\'') => {
+                    self.flush_doctype_public_identifier_run(&mut run)?;
+                    self.state = TokenizerState::AfterDOCTYPEPublicIdentifier;
+                    break;
+                }
+                Some(&chars::NULL) => {
+                    self.flush_doctype_public_identifier_run(&mut run)?;
+                    self.handle_error(TokenizerError::UnexpectedNullCharacter)?;
 
-                self.current_doctype_token_mut()?
-                    .public_identifier
-                    .as_mut()
-                    .unwrap()
-                    .push(chars::FEED_REPLACEMENT_CHARACTER);
-            }
-            None => {
-                self.handle_error(TokenizerError::EofInDoctype)?;
+                    self.current_doctype_token_mut()?
+                        .public_identifier
+                        .as_mut()
+                        .unwrap()
+                        .push(chars::FEED_REPLACEMENT_CHARACTER);
+                }
+                None => {
+                    self.flush_doctype_public_identifier_run(&mut run)?;
+                    self.handle_error(TokenizerError::EofInDoctype)?;
 
-                self.current_doctype_token_mut()?.force_quirks = true;
-                self.emit_current_doctype_token()?;
-                self.emit(HtmlToken::EndOfFile)?;
-            }
-            Some(c) => {
-                let c = *c;
-                self.current_doctype_token_mut()?
-                    .public_identifier
-                    .as_mut()
-                    .unwrap()
-                    .push(c);
+                    self.current_doctype_token_mut()?.force_quirks = true;
+                    self.emit_current_doctype_token()?;
+                    self.emit(HtmlToken::EndOfFile)?;
+                    break;
+                }
+                Some(c) => {
+                    run.push(*c);
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Append `run` to the current DOCTYPE's public identifier if it has
+    /// anything in it, then clear it. Used by the quoted public-identifier
+    /// states to flush the contiguous run accumulated before a terminator.
+    fn flush_doctype_public_identifier_run(
+        &mut self,
+        run: &mut String,
+    ) -> Result<(), HtmlParseError> {
+        if !run.is_empty() {
+            self.current_doctype_token_mut()?
+                .public_identifier
+                .as_mut()
+                .unwrap()
+                .push_str(run);
+            run.clear();
+        }
+
+        Ok(())
+    }
+
     /// <https://html.spec.whatwg.org/multipage/parsing.html#after-doctype-public-identifier-state>
     pub(super) fn after_doctype_public_identifier_state(&mut self) -> Result<(), HtmlParseError> {
         match self.input_stream.next() {
@@ -1997,33 +2241,37 @@ impl<'a> Tokenizer<'a> {
     pub(super) fn doctype_system_identifier_double_quoted_state(
         &mut self,
     ) -> Result<(), HtmlParseError> {
-        match self.input_stream.next() {
-            Some('"') => {
-                self.state = TokenizerState::AfterDOCTYPESystemIdentifier;
-            }
-            Some(&chars::NULL) => {
-                self.handle_error(TokenizerError::UnexpectedNullCharacter)?;
+        let mut run = String::new();
+
+        loop {
+            match self.input_stream.next() {
+                Some('"') => {
+                    self.flush_doctype_system_identifier_run(&mut run)?;
+                    self.state = TokenizerState::AfterDOCTYPESystemIdentifier;
+                    break;
+                }
+                Some(&chars::NULL) => {
+                    self.flush_doctype_system_identifier_run(&mut run)?;
+                    self.handle_error(TokenizerError::UnexpectedNullCharacter)?;
 
-                self.current_doctype_token_mut()?
-                    .system_identifier
-                    .as_mut()
-                    .unwrap()
-                    .push(chars::FEED_REPLACEMENT_CHARACTER);
-            }
-            None => {
-                self.handle_error(TokenizerError::EofInDoctype)?;
+                    self.current_doctype_token_mut()?
+                        .system_identifier
+                        .as_mut()
+                        .unwrap()
+                        .push(chars::FEED_REPLACEMENT_CHARACTER);
+                }
+                None => {
+                    self.flush_doctype_system_identifier_run(&mut run)?;
+                    self.handle_error(TokenizerError::EofInDoctype)?;
 
-                self.current_doctype_token_mut()?.force_quirks = true;
-                self.emit_current_doctype_token()?;
-                self.emit(HtmlToken::EndOfFile)?;
-            }
-            Some(c) => {
-                let c = *c;
-                self.current_doctype_token_mut()?
-                    .system_identifier
-                    .as_mut()
-                    .unwrap()
-                    .push(c);
+                    self.current_doctype_token_mut()?.force_quirks = true;
+                    self.emit_current_doctype_token()?;
+                    self.emit(HtmlToken::EndOfFile)?;
+                    break;
+                }
+                Some(c) => {
+                    run.push(*c);
+                }
             }
         }
 
@@ -2034,39 +2282,62 @@ impl<'a> Tokenizer<'a> {
     pub(super) fn doctype_system_identifier_single_quoted_state(
         &mut self,
     ) -> Result<(), HtmlParseError> {
-        match self.input_stream.next() {
-            Some('\'') => {
-                self.state = TokenizerState::AfterDOCTYPESystemIdentifier;
-            }
-            Some(&chars::NULL) => {
-                self.handle_error(TokenizerError::UnexpectedNullCharacter)?;
+        let mut run = String::new();
+
+        loop {
+            match self.input_stream.next() {
+                Some('\'') => {
+                    self.flush_doctype_system_identifier_run(&mut run)?;
+                    self.state = TokenizerState::AfterDOCTYPESystemIdentifier;
+                    break;
+                }
+                Some(&chars::NULL) => {
+                    self.flush_doctype_system_identifier_run(&mut run)?;
+                    self.handle_error(TokenizerError::UnexpectedNullCharacter)?;
 
-                self.current_doctype_token_mut()?
-                    .system_identifier
-                    .as_mut()
-                    .unwrap()
-                    .push(chars::FEED_REPLACEMENT_CHARACTER);
-            }
-            None => {
-                self.handle_error(TokenizerError::EofInDoctype)?;
+                    self.current_doctype_token_mut()?
+                        .system_identifier
+                        .as_mut()
+                        .unwrap()
+                        .push(chars::FEED_REPLACEMENT_CHARACTER);
+                }
+                None => {
+                    self.flush_doctype_system_identifier_run(&mut run)?;
+                    self.handle_error(TokenizerError::EofInDoctype)?;
 
-                self.current_doctype_token_mut()?.force_quirks = true;
-                self.emit_current_doctype_token()?;
-                self.emit(HtmlToken::EndOfFile)?;
-            }
-            Some(c) => {
-                let c = *c;
-                self.current_doctype_token_mut()?
-                    .system_identifier
-                    .as_mut()
-                    .unwrap()
-                    .push(c);
+                    self.current_doctype_token_mut()?.force_quirks = true;
+                    self.emit_current_doctype_token()?;
+                    self.emit(HtmlToken::EndOfFile)?;
+                    break;
+                }
+                Some(c) => {
+                    run.push(*c);
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Append `run` to the current DOCTYPE's system identifier if it has
+    /// anything in it, then clear it. Used by the quoted system-identifier
+    /// states to flush the contiguous run accumulated before a terminator.
+    fn flush_doctype_system_identifier_run(
+        &mut self,
+        run: &mut String,
+    ) -> Result<(), HtmlParseError> {
+        if !run.is_empty() {
+            self.current_doctype_token_mut()?
+                .system_identifier
+                .as_mut()
+                .unwrap()
+                .push_str(run);
+            run.clear();
+        }
+
+        Ok(())
+    }
+
     /// <https://html.spec.whatwg.org/multipage/parsing.html#after-doctype-system-identifier-state>
     pub(super) fn after_doctype_system_identifier_state(&mut self) -> Result<(), HtmlParseError> {
         match self.input_stream.next() {
@@ -2176,60 +2447,62 @@ impl<'a> Tokenizer<'a> {
                 .map(|c| *c),
         );
 
-        let key = chars.into_iter().collect::<String>();
-
-        let char_ref = NAMED_CHARACTER_REFS
-            .keys()
-            .filter(|k| key.starts_with(**k))
-            .max_by_key(|x| x.len())
-            .map(|x| x.to_string());
+        // Walk the trie one character at a time instead of testing `chars`
+        // against every entry in `NAMED_CHARACTER_REFS`; entities aren't
+        // prefix-free (`&not` and `&notin;` both exist), so the walk keeps
+        // the most recent terminal node it passed through rather than
+        // stopping at the first one, and commits to the longest once no
+        // further edge matches.
+        let mut node = &*named_char_ref_trie::NAMED_CHARACTER_REFS_TRIE;
+        let mut last_match: Option<(usize, &'static str)> = None;
+
+        for (i, &c) in chars.iter().enumerate() {
+            match node.child(c) {
+                Some(next) => {
+                    node = next;
+                    if let Some(expansion) = node.expansion() {
+                        last_match = Some((i + 1, expansion));
+                    }
+                }
+                None => break,
+            }
+        }
 
-        match char_ref {
-            Some(char_ref) => {
-                let length = char_ref.len();
+        match last_match {
+            Some((length, expansion)) => {
+                let last_matched_char = chars[length - 1];
 
                 // consume the characters
                 self.input_stream.next_add(length - 1); // subtract 1 for the & character
 
-                // append the char_ref characters to the temporary buffer
-                for code_point in char_ref.chars() {
-                    self.temporary_buffer.push(code_point);
-                }
-
                 // if the character reference was consumed as part of an attribute,
                 // and the last character matched is not a ";" character,
                 // and the next input character is either a "=" character or an alphanumeric ASCII character,
                 // then flush the code points consumed as a character reference,
                 // and switch to the return state
-                if self.charref_in_attribute() && char_ref.chars().last() != Some(';') {
+                if self.charref_in_attribute() && last_matched_char != ';' {
                     if let Some(c) = self.input_stream.peek() {
                         match c {
                             '=' => {
-                                historical_reasons(self)?;
-                                return Ok(());
+                                // append the matched characters to the temporary buffer
+                                self.temporary_buffer.extend(chars[..length].iter());
+                                return historical_reasons(self);
                             }
                             c if c.is_ascii_alphanumeric() => {
-                                historical_reasons(self)?;
-                                return Ok(());
+                                self.temporary_buffer.extend(chars[..length].iter());
+                                return historical_reasons(self);
                             }
                             _ => {}
                         }
                     }
                 }
 
-                if char_ref.chars().last() != Some(';') {
+                if last_matched_char != ';' {
                     self.handle_error(TokenizerError::MissingSemicolonAfterCharacterReference)?;
                 }
 
-                // TODO: this will always be true since it's not matching character by character and every
-                // known named character reference ends with a semicolon
                 self.temporary_buffer.clear();
-                let char_ref_characters = NAMED_CHARACTER_REFS.get(&char_ref.as_ref()).unwrap();
-
-                // append the char_ref characters to the temporary buffer
-                for code_point in char_ref_characters.chars() {
-                    self.temporary_buffer.push(code_point);
-                }
+                self.temporary_buffer.extend(expansion.chars());
 
                 self.flush_code_points_consumed_as_character_reference()?;
                 self.state = self.current_return_state()?;
@@ -2282,6 +2555,45 @@ impl<'a> Tokenizer<'a> {
         Ok(())
     }
 
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#hexadecimal-character-reference-start-state>
+    pub(super) fn hexadecimal_character_reference_start_state(
+        &mut self,
+    ) -> Result<(), HtmlParseError> {
+        match self.input_stream.next() {
+            Some(c) if c.is_ascii_hexdigit() => {
+                self.reconsume_in_state(TokenizerState::HexadecimalCharacterReference)?;
+            }
+            _ => {
+                self.handle_error(TokenizerError::AbsenceOfDigitsInNumericCharacterReference)?;
+                self.flush_code_points_consumed_as_character_reference()?;
+                self.reconsume_in_state(self.current_return_state()?)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#hexadecimal-character-reference-state>
+    pub(super) fn hexadecimal_character_reference_state(&mut self) -> Result<(), HtmlParseError> {
+        match self.input_stream.next() {
+            Some(c) if c.is_ascii_hexdigit() => {
+                self.character_reference_code *= 16;
+                self.character_reference_code += c
+                    .to_digit(16)
+                    .ok_or(HtmlParseError::new("hexadecimal character not a digit"))?;
+            }
+            Some(';') => {
+                self.state = TokenizerState::NumericCharacterReferenceEnd;
+            }
+            _ => {
+                self.handle_error(TokenizerError::MissingSemicolonAfterCharacterReference)?;
+                self.reconsume_in_state(TokenizerState::NumericCharacterReferenceEnd)?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// <https://html.spec.whatwg.org/multipage/parsing.html#decimal-character-reference-start-state>
     pub(super) fn decimal_character_reference_start_state(&mut self) -> Result<(), HtmlParseError> {
         match self.input_stream.next() {
@@ -0,0 +1,110 @@
+//! A minimal [`Parser`] that just records what the tokenizer emits, so the
+//! state methods in [`super::state_impls`] can be driven and inspected
+//! without a real [`HtmlParser`](super::super::HtmlParser) and the tree it
+//! builds.
+//!
+//! This is the piece an html5lib-tests-style conformance harness needs most:
+//! a way to start a [`Tokenizer`] in an arbitrary [`TokenizerState`] (with a
+//! given "last start tag" so `is_current_end_tag_token_appropriate` behaves
+//! correctly in RCDATA/RAWTEXT/script-data end tag states) and collect its
+//! full token and error output. It doesn't by itself read the html5lib JSON
+//! fixture format — those fixture files aren't part of this checkout, and
+//! consuming them from outside this crate would also need
+//! [`TokenizerState`]/[`Tokenizer::set_state`] to be `pub` rather than
+//! `pub(crate)`, which is a bigger visibility change than this harness alone
+//! justifies.
+
+use std::cell::RefCell;
+
+use super::{HtmlParseError, HtmlToken, Parser, TagToken, Tokenizer, TokenizerError, TokenizerState};
+use crate::{vecpointer::VecPointerRef, xpath::grammar::XpathItemTreeNode};
+
+/// Maps this module's recorded output into the html5lib tokenizer test
+/// suite's expected-output format, for whichever follow-up reads that
+/// suite's fixtures (see the module's own doc comment for why that reading
+/// isn't here yet).
+pub(crate) mod html5lib_format;
+
+/// A [`Parser`] that records every token it's handed instead of building a
+/// tree. Errors are recorded separately, by [`RecordingTokenizerErrorHandler`]
+/// below, since [`Parser::handle_tokenizer_error`] only gets `&self`.
+#[derive(Default)]
+pub(crate) struct RecordingParser {
+    pub(crate) tokens: Vec<HtmlToken>,
+}
+
+impl Parser for RecordingParser {
+    fn token_emitted(
+        &mut self,
+        token: HtmlToken,
+    ) -> Result<super::super::Acknowledgement, HtmlParseError> {
+        self.tokens.push(token);
+        Ok(super::super::Acknowledgement::no())
+    }
+
+    fn adjusted_current_node(&self) -> Option<&XpathItemTreeNode> {
+        // A bare tokenizer run has no tree, so there's no adjusted current
+        // node to report; this only affects whether the CDATA-section state
+        // is reachable (it's gated on being inside foreign content).
+        None
+    }
+
+    fn handle_tokenizer_error(&self, _error: TokenizerError) -> Result<(), HtmlParseError> {
+        // Errors reach `RecordingTokenizerErrorHandler` instead, which is
+        // installed as the tokenizer's error handler and gets first look at
+        // every `TokenizerError` before (if ever) it would reach here.
+        Ok(())
+    }
+}
+
+/// A [`TokenizerErrorHandler`](super::TokenizerErrorHandler) that records
+/// every error instead of forwarding it to a parser. Needs a `RefCell`
+/// since `error_emitted` only gets `&self` (the default handler instead
+/// forwards through the `&mut Tokenizer` it's also given, but a bare
+/// recorder has nothing else to hold the list).
+#[derive(Default)]
+struct RecordingTokenizerErrorHandler {
+    errors: RefCell<Vec<TokenizerError>>,
+}
+
+impl super::TokenizerErrorHandler for RecordingTokenizerErrorHandler {
+    fn error_emitted(
+        &self,
+        error: TokenizerError,
+        _tokenizer: &mut Tokenizer,
+    ) -> Result<(), HtmlParseError> {
+        self.errors.borrow_mut().push(error);
+        Ok(())
+    }
+}
+
+/// Run the tokenizer over `input` to completion, starting in `initial_state`
+/// with `last_start_tag` pre-seeded (as if that tag's start tag had already
+/// been emitted), and return every token and error produced in order.
+pub(crate) fn run_tokenizer_to_completion(
+    input: &str,
+    initial_state: TokenizerState,
+    last_start_tag: Option<&str>,
+) -> Result<(Vec<HtmlToken>, Vec<TokenizerError>), HtmlParseError> {
+    let chars: Vec<char> = input.chars().collect();
+    let input_stream = VecPointerRef::new(&chars);
+    let mut parser = RecordingParser::default();
+    let error_handler = RecordingTokenizerErrorHandler::default();
+
+    {
+        let mut tokenizer = Tokenizer::new(input_stream, Box::new(&mut parser));
+
+        tokenizer.set_state(initial_state);
+        if let Some(tag_name) = last_start_tag {
+            tokenizer.set_last_emitted_start_tag(TagToken::new(tag_name.to_string()));
+        }
+
+        tokenizer.set_error_handler(Box::new(&error_handler));
+
+        while !tokenizer.is_terminated() {
+            tokenizer.step()?;
+        }
+    }
+
+    Ok((parser.tokens, error_handler.errors.into_inner()))
+}
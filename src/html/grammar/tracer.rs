@@ -0,0 +1,28 @@
+//! An optional hook for observing the tree-construction dispatcher from
+//! outside the crate, modeled on html5ever's `Tracer`/`debug_step`. Useful
+//! for logging or snapshotting the exact sequence of insertion-mode
+//! transitions and parse errors a malformed document produces, without
+//! recompiling with ad-hoc `println!`s.
+
+use super::tokenizer::HtmlToken;
+use super::{HtmlParserError, InsertionMode};
+
+/// Installed on a parser via
+/// [`HtmlParser::with_tracer`](super::HtmlParser::with_tracer). Both methods
+/// default to doing nothing, so an implementation only needs to override the
+/// one it cares about.
+pub trait ParserTracer {
+    /// Called once per dispatch, immediately before `token` is handed to
+    /// `mode`'s insertion-mode handler. `open_elements` lists the tag name
+    /// of every element currently on the stack of open elements, outermost
+    /// first.
+    fn step(&mut self, mode: InsertionMode, token: &HtmlToken, open_elements: &[&str]) {
+        let _ = (mode, token, open_elements);
+    }
+
+    /// Called whenever [`HtmlParser::handle_error`](super::HtmlParser::handle_error)
+    /// fires, with the error it was given.
+    fn error(&mut self, error: &HtmlParserError) {
+        let _ = error;
+    }
+}
@@ -0,0 +1,203 @@
+//! An abstraction over how the parser's tree-construction stage stores the
+//! nodes it builds, modeled on html5ever's `TreeSink` trait.
+//!
+//! [`HtmlParser`](super::HtmlParser) is generic over `TreeSink<Handle =
+//! NodeId>` (defaulting to [`ArenaTreeSink`], the only implementation in
+//! this crate today), and routes every node creation, append, reparent,
+//! attribute-set, and detach through it. The `Handle` is pinned to
+//! `indextree::NodeId` rather than left fully abstract: `HtmlParser` still
+//! tracks its own state (the stack of open elements, the active formatting
+//! elements list, the context element) as `NodeId`s directly, and reads the
+//! tree back via [`TreeSink::arena`]/[`TreeSink::arena_mut`] for the
+//! traversal queries (children, parent, previous sibling, attribute lookup)
+//! that don't have a dedicated mutation method above. A `TreeSink` is
+//! therefore a pluggable *construction strategy* layered over an
+//! `indextree::Arena<XpathItemTreeNode>` — e.g. one that validates or traces
+//! every mutation — rather than an arbitrary caller-owned DOM
+//! representation; generalizing `Handle` itself to something other than
+//! `NodeId` would also require generalizing `open_elements`,
+//! `active_formatting_elements`, and every other `NodeId`-typed field on
+//! `HtmlParser`, which is a larger, separate lift.
+
+use indextree::{Arena, NodeId};
+
+use crate::xpath::grammar::{
+    data_model::{AttributeNode, CommentNode, ElementNode, TextNode},
+    XpathItemTreeNode,
+};
+
+/// The tree-construction operations the WHATWG HTML parsing algorithm needs,
+/// parameterized over a [`Handle`](TreeSink::Handle) type identifying a node
+/// in the sink's own tree representation.
+///
+/// <https://html.spec.whatwg.org/multipage/parsing.html#tree-construction>
+pub(crate) trait TreeSink {
+    /// A reference to a node in this sink's tree.
+    type Handle: Copy + Eq;
+
+    /// Create a detached element node and return a handle to it.
+    fn create_element(&mut self, element: ElementNode) -> Self::Handle;
+
+    /// Create a detached comment node and return a handle to it.
+    fn create_comment(&mut self, data: String) -> Self::Handle;
+
+    /// Create a detached text node and return a handle to it.
+    fn create_text(&mut self, data: String) -> Self::Handle;
+
+    /// Append `child` as `parent`'s last child.
+    fn append(&mut self, parent: Self::Handle, child: Self::Handle);
+
+    /// Insert `node` as the sibling immediately before `sibling`.
+    fn append_before_sibling(&mut self, sibling: Self::Handle, node: Self::Handle);
+
+    /// Move every child of `node` onto `new_parent`, preserving order.
+    fn reparent_children(&mut self, node: Self::Handle, new_parent: Self::Handle);
+
+    /// Set an attribute on `element`, overwriting any existing attribute of
+    /// the same name. A no-op for any other kind of node.
+    fn set_attribute(&mut self, element: Self::Handle, name: String, value: String);
+
+    /// Detach `node` from its parent, if it has one. `node` and its
+    /// descendants remain valid handles afterwards.
+    fn detach(&mut self, node: Self::Handle);
+
+    /// The parent of `node`, if it has one.
+    fn parent(&self, node: Self::Handle) -> Option<Self::Handle>;
+
+    /// The underlying arena backing this sink's tree, for the traversal
+    /// queries (children, siblings, attribute lookup) `HtmlParser` needs
+    /// that don't have a dedicated method above. See this module's docs for
+    /// why `TreeSink` exposes the arena directly rather than growing a
+    /// method per traversal.
+    fn arena(&self) -> &Arena<XpathItemTreeNode>;
+
+    /// Mutable counterpart of [`TreeSink::arena`].
+    fn arena_mut(&mut self) -> &mut Arena<XpathItemTreeNode>;
+}
+
+// Covers create/append/reparent/attribute/detach — the primitives every
+// insertion-mode handler in `insertion_mode_impls` bottoms out on — plus
+// `arena`/`arena_mut` for the handful of read-only traversal queries
+// (children, previous sibling, attribute lookup) that don't warrant a
+// dedicated method. `HtmlParser<S: TreeSink<Handle = NodeId> + Default>`
+// routes every one of those call sites through `self.sink` instead of
+// holding an `Arena<XpathItemTreeNode>` directly.
+//
+// `create_element` can't yet take a sink-chosen `ElementData` instead of
+// a concrete [`ElementNode`]: `ElementNode`'s own field
+// definition lives in `data_model.rs`, which isn't part of this
+// checkout, so there's nothing here to generalize away from — adding an
+// associated `ElementData` type would just be `type ElementData =
+// ElementNode` with no caller able to supply anything else.
+
+/// The default [`TreeSink`]: builds an `indextree::Arena<XpathItemTreeNode>`,
+/// exactly as [`HtmlParser`](super::HtmlParser) always has.
+pub(crate) struct ArenaTreeSink {
+    pub(crate) arena: Arena<XpathItemTreeNode>,
+}
+
+impl ArenaTreeSink {
+    pub(crate) fn new() -> Self {
+        ArenaTreeSink {
+            arena: Arena::new(),
+        }
+    }
+}
+
+impl Default for ArenaTreeSink {
+    fn default() -> Self {
+        ArenaTreeSink::new()
+    }
+}
+
+impl TreeSink for ArenaTreeSink {
+    type Handle = NodeId;
+
+    fn create_element(&mut self, element: ElementNode) -> NodeId {
+        let id = self.arena.new_node(XpathItemTreeNode::ElementNode(element));
+
+        if let XpathItemTreeNode::ElementNode(element) = self.arena.get_mut(id).unwrap().get_mut()
+        {
+            element.set_id(id);
+        }
+
+        id
+    }
+
+    fn create_comment(&mut self, data: String) -> NodeId {
+        self.arena
+            .new_node(XpathItemTreeNode::CommentNode(CommentNode::new(data)))
+    }
+
+    fn create_text(&mut self, data: String) -> NodeId {
+        self.arena
+            .new_node(XpathItemTreeNode::TextNode(TextNode::new(data)))
+    }
+
+    fn append(&mut self, parent: NodeId, child: NodeId) {
+        parent.append(child, &mut self.arena);
+    }
+
+    fn append_before_sibling(&mut self, sibling: NodeId, node: NodeId) {
+        sibling.insert_before(node, &mut self.arena);
+    }
+
+    fn reparent_children(&mut self, node: NodeId, new_parent: NodeId) {
+        let children: Vec<NodeId> = node.children(&self.arena).collect();
+
+        for child in children {
+            child.detach(&mut self.arena);
+            new_parent.append(child, &mut self.arena);
+        }
+    }
+
+    fn set_attribute(&mut self, element: NodeId, name: String, value: String) {
+        if !matches!(
+            self.arena.get(element).map(|node| node.get()),
+            Some(XpathItemTreeNode::ElementNode(_))
+        ) {
+            return;
+        }
+
+        let existing = element.children(&self.arena).find(|child_id| {
+            matches!(
+                self.arena.get(*child_id).unwrap().get(),
+                XpathItemTreeNode::AttributeNode(attribute) if attribute.name == name
+            )
+        });
+
+        match existing {
+            Some(attribute_id) => {
+                if let XpathItemTreeNode::AttributeNode(attribute) =
+                    self.arena.get_mut(attribute_id).unwrap().get_mut()
+                {
+                    attribute.value = value;
+                }
+            }
+            None => {
+                let attribute_id = self
+                    .arena
+                    .new_node(XpathItemTreeNode::AttributeNode(AttributeNode::new(
+                        name, value,
+                    )));
+                element.append(attribute_id, &mut self.arena);
+            }
+        }
+    }
+
+    fn detach(&mut self, node: NodeId) {
+        node.detach(&mut self.arena);
+    }
+
+    fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.arena.get(node).and_then(|n| n.parent())
+    }
+
+    fn arena(&self) -> &Arena<XpathItemTreeNode> {
+        &self.arena
+    }
+
+    fn arena_mut(&mut self) -> &mut Arena<XpathItemTreeNode> {
+        &mut self.arena
+    }
+}
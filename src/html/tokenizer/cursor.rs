@@ -0,0 +1,154 @@
+//! A `&str`/byte cursor, ported from the
+//! [proc-macro2](https://docs.rs/proc-macro2) model, for lower-allocation
+//! lexing than `VecPointer<char>` gives this module's functions today.
+//!
+//! The tokenizer currently materializes the whole input into a `Vec<char>`
+//! up front, and every `is_*` function that collects a name/text/literal
+//! pushes matched characters into its own per-token `Vec<char>` buffer
+//! before collecting that into a `String` — an allocation per token on top
+//! of the input's own. [`Cursor`] instead borrows a `&str` slice of the
+//! remaining input and advances by `split_at` over UTF-8 boundaries, so a
+//! matched run can be returned as a borrowed `&str` slice (plus its byte
+//! offset) with no per-token buffer at all.
+//!
+//! Nothing in this module builds one yet. Porting the nine `is_*` functions
+//! over means changing every one of them from `&mut VecPointer<char>` to
+//! `&mut Cursor` (or a `Cursor -> Cursor` transform returning the matched
+//! slice), which also means touching every one of their ~20 existing unit
+//! tests (each of which constructs a `VecPointer` directly) — a
+//! disproportionate amount of change to land in the same commit as the
+//! cursor type. It's also the same `VecPointer`-shaped blocker
+//! [`super::span`] and [`super::lex_error`] ran into: `VecPointer` itself
+//! (declared at `crate::vecpointer`) isn't present on disk here, so there's
+//! no existing implementation to diff against or migrate off of, only the
+//! call sites in this file that use it. This module lands [`Cursor`] as the
+//! type that port would build on, with the multibyte-boundary handling
+//! `str::chars`/`char::len_utf8` give for free, so it exists to agree on
+//! before the nine functions change.
+
+/// A borrowed cursor over the remaining UTF-8 input, advancing by
+/// `split_at` instead of indexing into a materialized `Vec<char>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Cursor<'a> {
+    rest: &'a str,
+    off: usize,
+}
+
+impl<'a> Cursor<'a> {
+    /// A cursor starting at the beginning of `input`.
+    pub(crate) fn new(input: &'a str) -> Self {
+        Cursor { rest: input, off: 0 }
+    }
+
+    /// The byte offset into the original input this cursor is positioned at.
+    pub(crate) fn offset(&self) -> usize {
+        self.off
+    }
+
+    /// The remaining, not-yet-consumed input.
+    pub(crate) fn rest(&self) -> &'a str {
+        self.rest
+    }
+
+    /// Whether there is no input left.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.rest.is_empty()
+    }
+
+    /// The next character without consuming it.
+    pub(crate) fn first(&self) -> Option<char> {
+        self.rest.chars().next()
+    }
+
+    /// The character one past `first`, without consuming either.
+    pub(crate) fn second(&self) -> Option<char> {
+        let mut chars = self.rest.chars();
+        chars.next();
+        chars.next()
+    }
+
+    /// Advance past `first()`'s character, returning it, same shape as
+    /// `VecPointer::next`.
+    pub(crate) fn bump(&mut self) -> Option<char> {
+        let c = self.first()?;
+        self.rest = &self.rest[c.len_utf8()..];
+        self.off += c.len_utf8();
+        Some(c)
+    }
+
+    /// Whether the remaining input starts with `needle`.
+    pub(crate) fn starts_with(&self, needle: &str) -> bool {
+        self.rest.starts_with(needle)
+    }
+
+    /// Split off and return the borrowed slice consumed by `count` calls to
+    /// [`Cursor::bump`], without actually calling it: advances `self` past
+    /// the first `count` characters and hands back the `&str` they span.
+    pub(crate) fn split_at_chars(&mut self, count: usize) -> &'a str {
+        let byte_len: usize = self.rest.chars().take(count).map(char::len_utf8).sum();
+        let (matched, rest) = self.rest.split_at(byte_len);
+        self.rest = rest;
+        self.off += byte_len;
+        matched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bump_advances_one_character_at_a_time() {
+        // arrange
+        let mut cursor = Cursor::new("ab");
+
+        // act
+        let first = cursor.bump();
+        let second = cursor.bump();
+        let third = cursor.bump();
+
+        // assert
+        assert_eq!(Some('a'), first);
+        assert_eq!(Some('b'), second);
+        assert_eq!(None, third);
+        assert!(cursor.is_empty());
+    }
+
+    #[test]
+    fn bump_handles_multibyte_characters_as_one_char() {
+        // arrange
+        let mut cursor = Cursor::new("é>");
+
+        // act
+        let first = cursor.bump();
+
+        // assert
+        assert_eq!(Some('é'), first);
+        assert_eq!('é'.len_utf8(), cursor.offset());
+        assert_eq!(Some('>'), cursor.first());
+    }
+
+    #[test]
+    fn split_at_chars_splits_on_a_character_boundary_not_a_byte_boundary() {
+        // arrange
+        let mut cursor = Cursor::new("日本語foo");
+
+        // act
+        let matched = cursor.split_at_chars(3);
+
+        // assert
+        assert_eq!("日本語", matched);
+        assert_eq!("foo", cursor.rest());
+        assert_eq!("日本語".len(), cursor.offset());
+    }
+
+    #[test]
+    fn starts_with_works_across_multibyte_characters() {
+        // arrange
+        let cursor = Cursor::new("日本語");
+
+        // act & assert
+        assert!(cursor.starts_with("日本"));
+        assert!(!cursor.starts_with("本語"));
+    }
+}
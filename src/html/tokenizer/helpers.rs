@@ -2,6 +2,25 @@ use crate::vecpointer::VecPointer;
 
 use super::Symbol;
 
+/// A declaration-family symbol `is_doctype`/`is_cdata`/
+/// `is_processing_instruction` recognize, carrying the inner text between
+/// the construct's keyword/opening marker and its closing marker.
+///
+/// These aren't `Symbol` variants because `Symbol` (declared in this
+/// module's parent, via a `mod.rs` not present in this checkout) doesn't
+/// have `Doctype`/`Cdata`/`ProcessingInstruction` variants to return — this
+/// enum is the provisional stand-in the driving loop would fold into
+/// `Symbol` once it does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RawDeclarationSymbol {
+    /// `<!DOCTYPE{{String}}>`
+    Doctype(String),
+    /// `<![CDATA[{{String}}]]>`
+    Cdata(String),
+    /// `<?{{String}}?>`
+    ProcessingInstruction(String),
+}
+
 /// Checks if the [TextPointer](TextPointer) is currently pointing to a StartTag [Symbol](Symbol).
 /// If true it will move the text pointer to the next symbol, otherwise it will not change the pointer.
 /// 
@@ -91,6 +110,119 @@ pub fn is_end_comment(pointer: &mut VecPointer<char>) -> bool {
     false
 }
 
+/// Checks whether the [TextPointer](TextPointer) is currently pointing at
+/// `prefix`, ASCII case-insensitively, without moving the pointer either
+/// way. A helper method not used directly in the lexer, the same role
+/// [`is_end_comment`] plays for `is_comment`.
+fn is_prefix_match(pointer: &mut VecPointer<char>, prefix: &str) -> bool {
+    let start_index = pointer.index;
+    let mut chars = prefix.chars();
+
+    let matched = match chars.next() {
+        Some(expected) => match pointer.current() {
+            Some(c) if c.eq_ignore_ascii_case(&expected) => {
+                chars.all(|expected| matches!(pointer.next(), Some(c) if c.eq_ignore_ascii_case(&expected)))
+            }
+            _ => false,
+        },
+        None => true,
+    };
+
+    pointer.index = start_index;
+    matched
+}
+
+/// Checks if the [TextPointer](TextPointer) is currently pointing to a DOCTYPE declaration.
+/// If true it will move the text pointer to the next symbol, otherwise it will not change the pointer.
+///
+/// DOCTYPE is defined as `<!DOCTYPE{{String}}>`, matched case-insensitively
+/// like the spec requires. See [`RawDeclarationSymbol`] for why this
+/// doesn't return a `Symbol` directly.
+pub fn is_doctype(pointer: &mut VecPointer<char>) -> Option<RawDeclarationSymbol> {
+    if !is_prefix_match(pointer, "<!DOCTYPE") {
+        return None;
+    }
+
+    pointer.next_add(8); // consume "<!DOCTYPE" (9 chars), landing on its last character
+
+    let mut text: Vec<char> = Vec::new();
+    while let Some(c) = pointer.next() {
+        if c == '>' {
+            let name: String = text.into_iter().collect();
+            return Some(RawDeclarationSymbol::Doctype(name));
+        }
+        text.push(c);
+    }
+    None
+}
+
+/// Checks if the [TextPointer](TextPointer) is currently pointing to the end
+/// of a CDATA section [Symbol](Symbol). If true it will move the text
+/// pointer to the next symbol, otherwise it will not change the pointer.
+///
+/// This is a helper method not used directly in the lexer.
+///
+/// The end of a CDATA section is defined as `]]>`, mirroring
+/// [`is_end_comment`]'s 3-character lookahead for `-->`.
+fn is_end_cdata(pointer: &mut VecPointer<char>) -> bool {
+    if let (Some(']'), Some(']'), Some('>')) = (pointer.current(), pointer.peek(), pointer.peek_add(2)) {
+        pointer.next_add(3);
+
+        return true;
+    }
+    false
+}
+
+/// Checks if the [TextPointer](TextPointer) is currently pointing to a CDATA section.
+/// If true it will move the text pointer to the next symbol, otherwise it will not change the pointer.
+///
+/// CDATA is defined as `<![CDATA[{{String}}]]>`, consumed verbatim until
+/// the matching `]]>` — see [`RawDeclarationSymbol`] for why this doesn't
+/// return a `Symbol` directly.
+pub fn is_cdata(pointer: &mut VecPointer<char>) -> Option<RawDeclarationSymbol> {
+    if !is_prefix_match(pointer, "<![CDATA[") {
+        return None;
+    }
+
+    pointer.next_add(8); // consume "<![CDATA[" (9 chars), landing on its last character
+
+    let mut text: Vec<char> = Vec::new();
+    while let Some(c) = pointer.next() {
+        if is_end_cdata(pointer) {
+            let name: String = text.into_iter().collect();
+            return Some(RawDeclarationSymbol::Cdata(name));
+        }
+        text.push(c);
+    }
+    None
+}
+
+/// Checks if the [TextPointer](TextPointer) is currently pointing to a
+/// processing instruction. If true it will move the text pointer to the
+/// next symbol, otherwise it will not change the pointer.
+///
+/// A processing instruction is defined as `<?{{String}}?>`, consumed
+/// verbatim until the matching `?>` — see [`RawDeclarationSymbol`] for why
+/// this doesn't return a `Symbol` directly.
+pub fn is_processing_instruction(pointer: &mut VecPointer<char>) -> Option<RawDeclarationSymbol> {
+    if let (Some('<'), Some('?')) = (pointer.current(), pointer.peek()) {
+        pointer.next(); // peeked before, move up now
+
+        let mut text: Vec<char> = Vec::new();
+        while let Some(c) = pointer.next() {
+            if let (Some('?'), Some('>')) = (pointer.current(), pointer.peek()) {
+                pointer.next_add(2);
+
+                let name: String = text.into_iter().collect();
+                return Some(RawDeclarationSymbol::ProcessingInstruction(name));
+            }
+            text.push(c);
+        }
+        return None;
+    }
+    None
+}
+
 /// Checks if the [TextPointer](TextPointer) is currently pointing to a TagClose [Symbol](Symbol).
 /// If true it will move the text pointer to the next symbol, otherwise it will not change the pointer.
 /// 
@@ -204,50 +336,187 @@ pub fn is_identifier(pointer: &mut VecPointer<char>, has_open_tag: bool) -> Opti
     None
 }
 
+/// Tag names whose content the lexer should stop tokenizing as markup once
+/// their start tag closes, consuming everything up to their own matching end
+/// tag as one literal body, instead of letting `is_start_tag`/`is_text` treat
+/// every `<` inside as a potential tag.
+///
+/// `title` and `textarea` are technically "escapable raw text" elements
+/// rather than "raw text" elements in spec terms (they still decode
+/// character references), but both share the same "read verbatim until the
+/// literal end tag" lexing shape, so this lexer treats all four the same
+/// way.
+pub fn is_raw_text_element(tag_name: &str) -> bool {
+    matches!(
+        tag_name.to_ascii_lowercase().as_str(),
+        "script" | "style" | "textarea" | "title"
+    )
+}
+
+/// Checks whether the [TextPointer](TextPointer) is currently pointing at the
+/// end tag that closes a raw text element named `tag_name`, case-insensitive,
+/// without moving the pointer either way.
+///
+/// Unlike [`is_end_comment`], which consumes `-->` when it matches, this is a
+/// pure lookahead: callers use it only to decide when to stop consuming raw
+/// text, then let the ordinary [`is_end_tag`] parse the real
+/// [`Symbol::EndTag`] afterwards, exactly as it would for any other
+/// element's closing tag.
+///
+/// Only matches if whitespace, `/`, `>`, or end of input follows the name, so
+/// `</scriptfoo>` does not count as closing a `<script>`.
+pub fn is_raw_text_end_tag(pointer: &mut VecPointer<char>, tag_name: &str) -> bool {
+    let start_index = pointer.index;
+
+    let matched = if let (Some('<'), Some('/')) = (pointer.current(), pointer.peek()) {
+        pointer.next(); // land on the `/`, so each `next()` below yields one name char
+
+        let name_matches = tag_name.chars().all(|expected| match pointer.next() {
+            Some(c) => c.eq_ignore_ascii_case(&expected),
+            None => false,
+        });
+
+        name_matches
+            && matches!(
+                pointer.peek(),
+                None | Some('>') | Some('/') | Some(' ') | Some('\t') | Some('\n') | Some('\r')
+            )
+    } else {
+        false
+    };
+
+    pointer.index = start_index;
+    matched
+}
+
+/// Checks if the [TextPointer](TextPointer) is currently pointing to the
+/// verbatim body of a raw text element (`script`, `style`, `textarea`, or
+/// `title` — see [`is_raw_text_element`]), consuming every character up to
+/// but not including the matching end tag recognized by
+/// [`is_raw_text_end_tag`].
+///
+/// Without this, a `<script>foo<bar></baz></script>` block gets shredded
+/// into bogus `StartTag`/`EndTag` symbols by `is_start_tag`/`is_text`, since
+/// they treat every `<` as a potential tag regardless of context. This
+/// returns the body as a [`Symbol::Text`] rather than a separate `RawText`
+/// variant, since the existing variant already carries exactly what's
+/// needed here.
+///
+/// Driving this from the lexer loop requires tracking which raw text
+/// element (if any) is currently open — analogous to the existing
+/// `has_open_tag` flag — and calling this function instead of
+/// `is_start_tag`/`is_text` while one is, starting right after the open
+/// tag's `is_tag_close`/`is_tag_close_and_end` fires for a tag name
+/// [`is_raw_text_element`] accepts. That loop lives in `super`
+/// (`html::tokenizer`'s top-level module), which is not part of this
+/// checkout, so it can't be wired in here; this function is written to the
+/// exact shape that loop would call once it is.
+pub fn is_raw_text(pointer: &mut VecPointer<char>, tag_name: &str) -> Option<Symbol> {
+    // An empty body (`<script></script>`) isn't text at all; leave the
+    // pointer at the end tag for `is_end_tag` to parse, same as every other
+    // "nothing matched" case in this file.
+    if is_raw_text_end_tag(pointer, tag_name) {
+        return None;
+    }
+
+    let mut text: Vec<char> = Vec::new();
+    while let Some(c) = pointer.current() {
+        text.push(c);
+        if pointer.next().is_none() || is_raw_text_end_tag(pointer, tag_name) {
+            break;
+        }
+    }
+
+    let name: String = text.into_iter().collect();
+    Some(Symbol::Text(name))
+}
+
 lazy_static! {
-    /// List of characters that end a Text [Symbol](Symbol).
+    /// List of characters that end a Text [Symbol](Symbol), unless
+    /// [`is_tag_like_construct`] says they don't actually begin one here.
     static ref INAVLID_TEXT_CHARS: Vec<char> = vec!['<', '>'];
 }
 
+/// Checks whether the [TextPointer](TextPointer) is currently positioned at
+/// a construct that should end a Text [Symbol](Symbol) — a start tag, end
+/// tag, or comment — without moving the pointer either way.
+///
+/// `<`/`>` alone don't always mean markup: `<div>foo > bar < baz</div>`
+/// should keep the `>`/`<` around "bar" as literal text, since neither
+/// begins a valid start tag, end tag, or comment. Each `is_*` check mutates
+/// the pointer on a match, so its index is saved and restored regardless of
+/// the outcome — this is a pure lookahead.
+fn is_tag_like_construct(pointer: &mut VecPointer<char>) -> bool {
+    let start_index = pointer.index;
+
+    // An empty name (`< ` or `</>`) isn't a tag any real document means to
+    // write, so it doesn't count as "actually succeeding" here even though
+    // `is_start_tag`/`is_end_tag` return `Some` for it.
+    let matched = match is_start_tag(pointer) {
+        Some(Symbol::StartTag(name)) => !name.is_empty(),
+        _ => match is_end_tag(pointer) {
+            Some(Symbol::EndTag(name)) => !name.is_empty(),
+            _ => is_comment(pointer).is_some(),
+        },
+    };
+
+    pointer.index = start_index;
+    matched
+}
+
 /// Checks if the [TextPointer](TextPointer) is currently pointing to a Text [Symbol](Symbol).
 /// If true it will move the text pointer to the next symbol, otherwise it will not change the pointer.
-/// 
-/// Text is defined as any text outside a tag definition.
+///
+/// Text is defined as any text outside a tag definition. A `<` or `>` that
+/// doesn't actually begin a start tag, end tag, or comment (see
+/// [`is_tag_like_construct`]) is absorbed as literal text instead of ending
+/// the run.
 pub fn is_text(pointer: &mut VecPointer<char>, has_open_tag: bool) -> Option<Symbol> {
     if has_open_tag {
         return None;
     }
 
     if let Some(c) = pointer.current() {
-        if !INAVLID_TEXT_CHARS.contains(&c) {
-            let start_index = pointer.index;
-            let mut has_non_whitespace = false;
+        if INAVLID_TEXT_CHARS.contains(&c) && is_tag_like_construct(pointer) {
+            return None;
+        }
 
-            let mut text: Vec<char> = vec![c];
-            loop {
-                match pointer.next() {
-                    Some(c) if INAVLID_TEXT_CHARS.contains(&c) => break,
-                    Some(c) => {
-                        if !c.is_whitespace() {
-                            has_non_whitespace = true;
-                        }
+        let start_index = pointer.index;
+        let mut has_non_whitespace = false;
 
-                        text.push(c);
-                    },
-                    None => break,
-                };
-            }
-            let name: String = text.into_iter().collect();
-    
-            if has_non_whitespace {
-                return Some(Symbol::Text(name));
-            } else {
-                // roll back pointer
-                pointer.index = start_index;
-                return None;
-            }
+        let mut text: Vec<char> = vec![c];
+        loop {
+            match pointer.next() {
+                Some(c) if INAVLID_TEXT_CHARS.contains(&c) => {
+                    if is_tag_like_construct(pointer) {
+                        break;
+                    }
+
+                    if !c.is_whitespace() {
+                        has_non_whitespace = true;
+                    }
+
+                    text.push(c);
+                }
+                Some(c) => {
+                    if !c.is_whitespace() {
+                        has_non_whitespace = true;
+                    }
+
+                    text.push(c);
+                },
+                None => break,
+            };
+        }
+        let name: String = text.into_iter().collect();
+
+        if has_non_whitespace {
+            return Some(Symbol::Text(name));
+        } else {
+            // roll back pointer
+            pointer.index = start_index;
+            return None;
         }
-        return None;
     }
     None
 }
@@ -368,6 +637,106 @@ mod tests {
         assert_eq!(0, pointer.index);
     }
 
+    #[test]
+    fn is_doctype_works() {
+        // arrange
+        let chars = "<!DOCTYPE html>".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_doctype(&mut pointer).unwrap();
+
+        // assert
+        assert_eq!(RawDeclarationSymbol::Doctype(String::from(" html")), result);
+        assert_eq!(14, pointer.index);
+    }
+
+    #[test]
+    fn is_doctype_is_case_insensitive() {
+        // arrange
+        let chars = "<!doctype html>".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_doctype(&mut pointer).unwrap();
+
+        // assert
+        assert_eq!(RawDeclarationSymbol::Doctype(String::from(" html")), result);
+    }
+
+    #[test]
+    fn is_doctype_does_not_move_pointer_if_not_found() {
+        // arrange
+        let chars = "abcd".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_doctype(&mut pointer);
+
+        // assert
+        assert_eq!(None, result);
+        assert_eq!(0, pointer.index);
+    }
+
+    #[test]
+    fn is_cdata_works() {
+        // arrange
+        let chars = "<![CDATA[bean is-nice]]>".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_cdata(&mut pointer).unwrap();
+
+        // assert
+        assert_eq!(RawDeclarationSymbol::Cdata(String::from("bean is-nice")), result);
+        assert_eq!(24, pointer.index);
+    }
+
+    #[test]
+    fn is_cdata_does_not_move_pointer_if_not_found() {
+        // arrange
+        let chars = "abcd".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_cdata(&mut pointer);
+
+        // assert
+        assert_eq!(None, result);
+        assert_eq!(0, pointer.index);
+    }
+
+    #[test]
+    fn is_processing_instruction_works() {
+        // arrange
+        let chars = "<?xml version=\"1.0\"?>".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_processing_instruction(&mut pointer).unwrap();
+
+        // assert
+        assert_eq!(
+            RawDeclarationSymbol::ProcessingInstruction(String::from("xml version=\"1.0\"")),
+            result
+        );
+        assert_eq!(21, pointer.index);
+    }
+
+    #[test]
+    fn is_processing_instruction_does_not_move_pointer_if_not_found() {
+        // arrange
+        let chars = "abcd".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_processing_instruction(&mut pointer);
+
+        // assert
+        assert_eq!(None, result);
+        assert_eq!(0, pointer.index);
+    }
+
     #[test]
     fn is_tag_close_works() {
         // arrange
@@ -553,7 +922,7 @@ mod tests {
     #[test]
     fn is_text_not_move_pointer_if_not_found() {
         // arrange
-        let chars = "<".chars().collect();
+        let chars = " ".chars().collect();
         let mut pointer = VecPointer::new(chars);
 
         // act
@@ -563,4 +932,175 @@ mod tests {
         assert!(matches!(result, None));
         assert_eq!(0, pointer.index);
     }
+
+    #[test]
+    fn is_text_absorbs_angle_brackets_that_do_not_form_a_real_construct() {
+        // Neither `<` (immediately followed by `>`) nor the `>` itself
+        // begins a start tag, end tag, or comment here, so both are
+        // literal text.
+        // arrange
+        let chars = "<>foo".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_text(&mut pointer, false).unwrap();
+
+        // assert
+        assert_eq!(Symbol::Text(String::from("<>foo")), result);
+        assert_eq!(5, pointer.index);
+    }
+
+    #[test]
+    fn is_text_absorbs_stray_brackets_that_do_not_form_a_real_construct() {
+        // arrange
+        let chars = "foo > bar < baz".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_text(&mut pointer, false).unwrap();
+
+        // assert
+        assert_eq!(Symbol::Text(String::from("foo > bar < baz")), result);
+        assert_eq!(15, pointer.index);
+    }
+
+    #[test]
+    fn is_text_still_stops_at_a_real_start_tag() {
+        // arrange
+        let chars = "foo<bar>".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_text(&mut pointer, false).unwrap();
+
+        // assert
+        assert_eq!(Symbol::Text(String::from("foo")), result);
+        assert_eq!(3, pointer.index);
+    }
+
+    #[test]
+    fn is_text_still_stops_at_a_real_end_tag() {
+        // arrange
+        let chars = "foo</bar>".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_text(&mut pointer, false).unwrap();
+
+        // assert
+        assert_eq!(Symbol::Text(String::from("foo")), result);
+        assert_eq!(3, pointer.index);
+    }
+
+    #[test]
+    fn is_text_still_stops_at_a_real_comment() {
+        // arrange
+        let chars = "foo<!--hi-->".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_text(&mut pointer, false).unwrap();
+
+        // assert
+        assert_eq!(Symbol::Text(String::from("foo")), result);
+        assert_eq!(3, pointer.index);
+    }
+
+    #[test]
+    fn is_raw_text_element_recognizes_raw_text_tags() {
+        assert!(is_raw_text_element("script"));
+        assert!(is_raw_text_element("SCRIPT"));
+        assert!(is_raw_text_element("style"));
+        assert!(is_raw_text_element("textarea"));
+        assert!(is_raw_text_element("title"));
+    }
+
+    #[test]
+    fn is_raw_text_element_rejects_other_tags() {
+        assert!(!is_raw_text_element("div"));
+        assert!(!is_raw_text_element("scripty"));
+    }
+
+    #[test]
+    fn is_raw_text_end_tag_works_and_does_not_move_pointer() {
+        // arrange
+        let chars = "</script>".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_raw_text_end_tag(&mut pointer, "script");
+
+        // assert
+        assert!(result);
+        assert_eq!(0, pointer.index);
+    }
+
+    #[test]
+    fn is_raw_text_end_tag_is_case_insensitive() {
+        // arrange
+        let chars = "</SCRIPT>".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_raw_text_end_tag(&mut pointer, "script");
+
+        // assert
+        assert!(result);
+    }
+
+    #[test]
+    fn is_raw_text_end_tag_rejects_name_with_extra_characters() {
+        // arrange
+        let chars = "</scriptfoo>".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_raw_text_end_tag(&mut pointer, "script");
+
+        // assert
+        assert!(!result);
+        assert_eq!(0, pointer.index);
+    }
+
+    #[test]
+    fn is_raw_text_end_tag_does_not_move_pointer_if_not_found() {
+        // arrange
+        let chars = "abcd".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_raw_text_end_tag(&mut pointer, "script");
+
+        // assert
+        assert!(!result);
+        assert_eq!(0, pointer.index);
+    }
+
+    #[test]
+    fn is_raw_text_consumes_tag_like_text_verbatim() {
+        // arrange
+        let chars = "foo<bar></baz></script>".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_raw_text(&mut pointer, "script").unwrap();
+
+        // assert
+        assert_eq!(Symbol::Text(String::from("foo<bar></baz>")), result);
+        assert_eq!(14, pointer.index);
+    }
+
+    #[test]
+    fn is_raw_text_returns_none_for_empty_body() {
+        // arrange
+        let chars = "</script>".chars().collect();
+        let mut pointer = VecPointer::new(chars);
+
+        // act
+        let result = is_raw_text(&mut pointer, "script");
+
+        // assert
+        assert!(matches!(result, None));
+        assert_eq!(0, pointer.index);
+    }
 }
\ No newline at end of file
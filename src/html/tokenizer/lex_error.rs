@@ -0,0 +1,59 @@
+//! Diagnostic, non-panicking malformation flags for this module's lexer
+//! functions, following the approach `rustc_lexer` uses: store the error as
+//! a flag on (or alongside) the token instead of aborting, so the lexer
+//! always makes forward progress and a caller can do partial parsing and
+//! report recoverable errors.
+//!
+//! Several functions in this file currently swallow malformed input
+//! silently instead of flagging it: `is_comment` that never finds `-->`
+//! just returns `None` once the input runs out, `is_literal` that hits EOF
+//! before the closing quote still returns `Some(Symbol::Literal(..))` as if
+//! the string were well-formed, and `is_start_tag` that runs off the end
+//! returns a tag with whatever partial name it collected. None of the three
+//! tell the caller anything went wrong.
+//!
+//! Wiring [`LexError`] in means changing those three functions' return
+//! types (e.g. to `Result<Symbol, LexError>`, or a tuple alongside the
+//! existing `Option<Symbol>`) to surface it, which means updating their
+//! ~15 existing unit tests and every call site that currently does
+//! `is_comment(&mut pointer).unwrap()` expecting a bare `Symbol` — a
+//! disproportionate amount of unrelated churn to land in the same commit as
+//! the type. It's also blocked the same way [`super::span`] is: the
+//! `Symbol` enum and the lexer's driving loop both live in this module's
+//! parent (`html::tokenizer`'s top-level module, declared via a `mod.rs`
+//! that isn't part of this checkout), so there's no `Symbol::Error` variant
+//! to return instead even if the three functions' signatures did change
+//! today. This module lands the error type itself, carrying exactly what
+//! those call sites would need to hand back, so it exists to agree on
+//! before they change.
+
+/// Why a lexer function's input was malformed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LexErrorReason {
+    /// `is_comment` ran out of input before finding a closing `-->`.
+    UnterminatedComment,
+    /// `is_literal` ran out of input before the closing quote matching the
+    /// one it opened with.
+    UnterminatedString,
+    /// A function expecting more input (e.g. `is_start_tag` expecting a
+    /// tag name, `>`, or `/>`) hit the end of the document instead.
+    UnexpectedEof,
+}
+
+/// A malformation a lexer function noticed but recovered from by making
+/// forward progress anyway, carrying whatever partial text it had collected
+/// when it noticed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LexError {
+    pub reason: LexErrorReason,
+    pub partial_text: String,
+}
+
+impl LexError {
+    pub(crate) fn new(reason: LexErrorReason, partial_text: String) -> Self {
+        LexError {
+            reason,
+            partial_text,
+        }
+    }
+}
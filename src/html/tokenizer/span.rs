@@ -0,0 +1,72 @@
+//! A source location for a [`Symbol`](super::Symbol) emitted by the legacy
+//! lexer in this module, borrowing the span model
+//! [proc-macro2](https://docs.rs/proc-macro2)/`rustc_lexer` use: every
+//! [`Span`] records the byte/char offset range a symbol came from, plus the
+//! line and column its start falls on, so a downstream parser or error
+//! message can point at the exact location of malformed markup instead of
+//! failing opaquely.
+//!
+//! Nothing in this module constructs one yet. Every `is_*` function here
+//! (`is_start_tag` through `is_text`) would need to capture
+//! [`VecPointer`](crate::vecpointer::VecPointer)'s `index` on entry and
+//! again after it advances, return `(Symbol, Span)` (or a `Spanned<Symbol>`)
+//! instead of a bare `Symbol`, and maintain a running line/column counter
+//! that increments on `\n` — a signature change to all nine functions (and
+//! every caller of them) that's disproportionate to land in the same commit
+//! as the type itself.
+//!
+//! That wiring is more blocked than usual in this checkout specifically:
+//! `VecPointer` itself (declared at `crate::vecpointer`) isn't present on
+//! disk here, so there's no way to confirm whether it already tracks a line
+//! and column alongside `index`, or whether that counter would need to live
+//! on this module's side instead. This module lands [`Span`] and
+//! [`Spanned`] as the types that work would hand back, in [`Span`]'s case
+//! built directly from the `index` field every `is_*` function already
+//! reads and writes (`pointer.index`), since that much of the shape is
+//! already visible from the existing code in this file.
+
+/// A location a [`Symbol`](super::Symbol) came from: a half-open
+/// `[start, end)` range into the document (in `char` offsets, matching
+/// [`VecPointer`](crate::vecpointer::VecPointer)'s `index`), plus the
+/// 1-based line and column `start` falls on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: u32,
+    pub col: u32,
+}
+
+impl Span {
+    pub(crate) fn new(start: usize, end: usize, line: u32, col: u32) -> Self {
+        Span {
+            start,
+            end,
+            line,
+            col,
+        }
+    }
+
+    /// The number of `char`s this span covers.
+    pub(crate) fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Whether this span covers zero characters.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// A value paired with the [`Span`] it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Spanned<T> {
+    pub value: T,
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+    pub(crate) fn new(value: T, span: Span) -> Self {
+        Spanned { value, span }
+    }
+}
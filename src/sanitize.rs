@@ -0,0 +1,269 @@
+//! XPath-driven sanitization of a parsed document: remove matched subtrees,
+//! or rename a matched element's attribute, then serialize the result back
+//! out. Built on the mutation API in [`crate::xpath::grammar::mutate`].
+//!
+//! This covers the common "strip scripts/iframes and rewrite image sources
+//! before re-publishing" use case:
+//!
+//! ```ignore
+//! let rules = [
+//!     SanitizeRule::remove("//script"),
+//!     SanitizeRule::remove("//iframe"),
+//!     SanitizeRule::rename_attribute("//img", "src", "data-source"),
+//! ];
+//! tree.sanitize(&rules)?;
+//! let cleaned = tree.to_html_string();
+//! ```
+//!
+//! [`XpathItemTree::rename_attribute_everywhere`] and
+//! [`XpathItemTree::remove_elements_with_tags`] cover the same kinds of
+//! cleanup, and [`XpathItemTree::strip_comments`] removes comments, without
+//! writing an XPath: they walk the tree directly, collecting matching
+//! [`indextree::NodeId`]s before mutating, same as [`XpathItemTree::sanitize`]
+//! does with its XPath matches.
+
+use indextree::NodeId;
+use thiserror::Error;
+
+use crate::xpath::{
+    self,
+    grammar::{
+        data_model::{Node, XpathItem},
+        XpathItemTreeNode, XpathItemTreeNodeData,
+    },
+    XpathItemTree,
+};
+
+/// What to do with the elements matched by a [`SanitizeRule`]'s XPath.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SanitizeAction {
+    /// Remove the matched element, and everything below it, from the tree.
+    Remove,
+
+    /// Rename the `from` attribute of the matched element to `to`,
+    /// preserving its value. Does nothing to elements with no `from`
+    /// attribute.
+    RenameAttribute { from: String, to: String },
+}
+
+/// One sanitization step: an XPath selecting the elements to act on, plus
+/// the [`SanitizeAction`] to apply to each match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SanitizeRule {
+    pub xpath: String,
+    pub action: SanitizeAction,
+}
+
+impl SanitizeRule {
+    /// A rule that removes every element matched by `xpath`.
+    pub fn remove(xpath: impl Into<String>) -> Self {
+        Self {
+            xpath: xpath.into(),
+            action: SanitizeAction::Remove,
+        }
+    }
+
+    /// A rule that renames the `from` attribute to `to` on every element
+    /// matched by `xpath`.
+    pub fn rename_attribute(
+        xpath: impl Into<String>,
+        from: impl Into<String>,
+        to: impl Into<String>,
+    ) -> Self {
+        Self {
+            xpath: xpath.into(),
+            action: SanitizeAction::RenameAttribute {
+                from: from.into(),
+                to: to.into(),
+            },
+        }
+    }
+}
+
+/// An error produced while sanitizing a document.
+#[derive(Debug, Error, PartialEq)]
+pub enum SanitizeError {
+    /// A rule's XPath could not be parsed.
+    #[error("failed to parse sanitize rule XPath {xpath:?}: {message}")]
+    Parse { xpath: String, message: String },
+
+    /// A rule's XPath failed to apply against the document.
+    #[error("failed to apply sanitize rule XPath {xpath:?}: {message}")]
+    Apply { xpath: String, message: String },
+}
+
+impl XpathItemTree {
+    /// Apply a list of [`SanitizeRule`]s to this tree in order, mutating it
+    /// in place. Rules are applied one at a time, so an earlier rule's
+    /// removals are reflected when later rules are evaluated.
+    ///
+    /// `Remove` and `RenameAttribute` cover the two mutations this crate's
+    /// [`mutate`](crate::xpath::grammar::mutate) API exposes that a
+    /// sanitizer needs; add a variant here (and a matching arm below) if a
+    /// future rule needs another one, e.g. stripping a whole attribute.
+    pub fn sanitize(&mut self, rules: &[SanitizeRule]) -> Result<(), SanitizeError> {
+        for rule in rules {
+            let expr = xpath::parse(&rule.xpath).map_err(|err| SanitizeError::Parse {
+                xpath: rule.xpath.clone(),
+                message: err.to_string(),
+            })?;
+
+            let matches = expr.apply(self).map_err(|err| SanitizeError::Apply {
+                xpath: rule.xpath.clone(),
+                message: err.to_string(),
+            })?;
+
+            // Collect node ids before mutating; the items borrow `self`
+            // immutably, and removing a node invalidates any later node in
+            // the same subtree.
+            let node_ids: Vec<_> = matches
+                .iter()
+                .filter_map(|item| match item {
+                    XpathItem::Node(Node::TreeNode(node)) => Some(node.id()),
+                    _ => None,
+                })
+                .collect();
+
+            for node_id in node_ids {
+                match &rule.action {
+                    SanitizeAction::Remove => self.remove_node(node_id),
+                    SanitizeAction::RenameAttribute { from, to } => {
+                        self.rename_attribute(node_id, from, to);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rename an attribute, wherever it appears, across every element in
+    /// the tree at once — e.g. rewriting every `src` to `data-src` to
+    /// neutralize image/script loading, without writing an XPath for it.
+    pub fn rename_attribute_everywhere(&mut self, from: &str, to: &str) {
+        let node_ids = self
+            .collect_matching(|node| matches!(node.data, XpathItemTreeNodeData::ElementNode(_)));
+
+        for node_id in node_ids {
+            self.rename_attribute(node_id, from, to);
+        }
+    }
+
+    /// Remove every element (and everything below it) whose tag name is in
+    /// `tags`, e.g. `["script", "style", "iframe"]`.
+    pub fn remove_elements_with_tags(&mut self, tags: &[&str]) {
+        let node_ids = self.collect_matching(|node| {
+            matches!(
+                node.data,
+                XpathItemTreeNodeData::ElementNode(element) if tags.contains(&element.name.as_str())
+            )
+        });
+
+        for node_id in node_ids {
+            self.remove_node(node_id);
+        }
+    }
+
+    /// Remove every comment node in the tree.
+    pub fn strip_comments(&mut self) {
+        let node_ids = self
+            .collect_matching(|node| matches!(node.data, XpathItemTreeNodeData::CommentNode(_)));
+
+        for node_id in node_ids {
+            self.remove_node(node_id);
+        }
+    }
+
+    /// Walk the whole tree once, collecting the [`NodeId`] of every node
+    /// matching `predicate`, before any mutation happens.
+    ///
+    /// [`Self::rename_attribute_everywhere`], [`Self::remove_elements_with_tags`],
+    /// and [`Self::strip_comments`] all collect into a `Vec` first and
+    /// mutate from that afterwards, same as [`Self::sanitize`] does with its
+    /// XPath matches — detaching/removing a node while still walking its
+    /// siblings would invalidate the arena's child/sibling links mid-walk.
+    fn collect_matching(&self, predicate: impl Fn(&XpathItemTreeNode) -> bool) -> Vec<NodeId> {
+        fn visit<'a>(
+            node: XpathItemTreeNode<'a>,
+            tree: &'a XpathItemTree,
+            predicate: &impl Fn(&XpathItemTreeNode) -> bool,
+            out: &mut Vec<NodeId>,
+        ) {
+            if predicate(&node) {
+                out.push(node.id());
+            }
+
+            for child in node.children(tree) {
+                visit(child, tree, predicate, out);
+            }
+        }
+
+        let mut node_ids = Vec::new();
+        visit(self.root(), self, &predicate, &mut node_ids);
+        node_ids
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::html;
+
+    use super::*;
+
+    fn tree_from(html: &str) -> XpathItemTree {
+        let document = html::parse(html).unwrap();
+        XpathItemTree::from(&document)
+    }
+
+    #[test]
+    fn sanitize_removes_matched_elements_and_renames_attributes() {
+        // arrange
+        let mut tree =
+            tree_from(r#"<html><script>alert(1)</script><img src="x.png"><p>keep</p></html>"#);
+        let rules = [
+            SanitizeRule::remove("//script"),
+            SanitizeRule::rename_attribute("//img", "src", "data-source"),
+        ];
+
+        // act
+        tree.sanitize(&rules).unwrap();
+        let html_out = tree.to_html_string();
+
+        // assert
+        assert!(!html_out.contains("<script"));
+        assert!(!html_out.contains("alert(1)"));
+        assert!(html_out.contains(r#"data-source="x.png""#));
+        assert!(html_out.contains("<p>keep</p>"));
+    }
+
+    #[test]
+    fn sanitize_reports_a_parse_error_for_an_invalid_xpath() {
+        // arrange
+        let mut tree = tree_from("<html></html>");
+        let rules = [SanitizeRule::remove("///")];
+
+        // act
+        let result = tree.sanitize(&rules);
+
+        // assert
+        assert!(matches!(result, Err(SanitizeError::Parse { .. })));
+    }
+
+    #[test]
+    fn remove_elements_with_tags_and_strip_comments_walk_the_whole_tree() {
+        // arrange
+        let mut tree = tree_from(
+            "<html><!-- top --><div><iframe></iframe><!-- nested --></div><p>keep</p></html>",
+        );
+
+        // act
+        tree.remove_elements_with_tags(&["iframe"]);
+        tree.strip_comments();
+        let html_out = tree.to_html_string();
+
+        // assert
+        assert!(!html_out.contains("<iframe"));
+        assert!(!html_out.contains("<!--"));
+        assert!(html_out.contains("<p>keep</p>"));
+    }
+}
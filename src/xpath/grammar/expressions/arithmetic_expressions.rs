@@ -6,8 +6,15 @@ use nom::{
     branch::alt, bytes::complete::tag, character::complete::char, multi::many0, sequence::tuple,
 };
 
-use crate::xpath::grammar::{
-    expressions::sequence_expressions::combining_node_sequences::union_expr, recipes::Res,
+use crate::xpath::{
+    grammar::{
+        data_model::{AnyAtomicType, XpathItem},
+        expressions::sequence_expressions::combining_node_sequences::union_expr,
+        recipes::Res,
+    },
+    xpath_item_set,
+    xpath_item_set::XpathItemSet,
+    ExpressionApplyError, XpathExpressionContext,
 };
 
 use super::{
@@ -15,6 +22,87 @@ use super::{
     simple_map_operator::{simple_map_expr, SimpleMapExpr},
 };
 
+/// A numeric value promoted to the widest type among a pair of operands, per
+/// <https://www.w3.org/TR/2017/REC-xpath-31-20170321/#dt-type-promotion>.
+///
+/// The promotion hierarchy is `integer -> decimal -> double`; arithmetic
+/// between two values always happens at the narrowest type that can hold
+/// both.
+#[derive(Debug, Clone, Copy)]
+enum NumericValue {
+    Integer(i64),
+    Decimal(f64),
+    Double(f64),
+}
+
+impl NumericValue {
+    /// Read the first item of an operand sequence as a numeric value.
+    ///
+    /// Returns `None` for an empty sequence (per the XPath rule that an
+    /// empty operand produces an empty-sequence result), and an error for
+    /// any item that is not already an integer, decimal or double atomic
+    /// value. Nodes are not atomized and no cast is made, so a node operand
+    /// is an error too; items after the first are not read.
+    fn from_items(
+        items: &XpathItemSet,
+        operand_desc: &str,
+    ) -> Result<Option<NumericValue>, ExpressionApplyError> {
+        let Some(item) = items.iter().next() else {
+            return Ok(None);
+        };
+
+        match item {
+            XpathItem::AnyAtomicType(AnyAtomicType::Integer(i)) => Ok(Some(NumericValue::Integer(*i))),
+            XpathItem::AnyAtomicType(AnyAtomicType::Decimal(d)) => Ok(Some(NumericValue::Decimal(*d))),
+            XpathItem::AnyAtomicType(AnyAtomicType::Double(d)) => Ok(Some(NumericValue::Double(*d))),
+            _ => Err(ExpressionApplyError {
+                msg: format!("{} did not atomize to a numeric value", operand_desc),
+            }),
+        }
+    }
+
+    fn into_item(self) -> XpathItem<'static> {
+        match self {
+            NumericValue::Integer(i) => XpathItem::AnyAtomicType(AnyAtomicType::Integer(i)),
+            NumericValue::Decimal(d) => XpathItem::AnyAtomicType(AnyAtomicType::Decimal(d)),
+            NumericValue::Double(d) => XpathItem::AnyAtomicType(AnyAtomicType::Double(d)),
+        }
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            NumericValue::Integer(i) => i as f64,
+            NumericValue::Decimal(d) | NumericValue::Double(d) => d,
+        }
+    }
+
+    fn negate(self) -> NumericValue {
+        match self {
+            NumericValue::Integer(i) => NumericValue::Integer(-i),
+            NumericValue::Decimal(d) => NumericValue::Decimal(-d),
+            NumericValue::Double(d) => NumericValue::Double(-d),
+        }
+    }
+
+    /// Apply `int_op`/`float_op` at the promoted type of `self` and `other`.
+    fn combine(
+        self,
+        other: NumericValue,
+        int_op: impl Fn(i64, i64) -> i64,
+        float_op: impl Fn(f64, f64) -> f64,
+    ) -> NumericValue {
+        match (self, other) {
+            (NumericValue::Integer(a), NumericValue::Integer(b)) => {
+                NumericValue::Integer(int_op(a, b))
+            }
+            (NumericValue::Double(_), _) | (_, NumericValue::Double(_)) => {
+                NumericValue::Double(float_op(self.as_f64(), other.as_f64()))
+            }
+            _ => NumericValue::Decimal(float_op(self.as_f64(), other.as_f64())),
+        }
+    }
+}
+
 pub fn additive_expr(input: &str) -> Res<&str, AdditiveExpr> {
     // https://www.w3.org/TR/2017/REC-xpath-31-20170321/#prod-xpath31-AdditiveExpr
 
@@ -46,6 +134,32 @@ pub struct AdditiveExpr {
     pub items: Vec<AdditiveExprPair>,
 }
 
+impl AdditiveExpr {
+    pub(crate) fn eval<'tree>(
+        &self,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let mut acc = NumericValue::from_items(&self.expr.eval(context)?, "left operand of +/-")?;
+
+        for pair in &self.items {
+            let rhs = NumericValue::from_items(&pair.1.eval(context)?, "right operand of +/-")?;
+
+            acc = match (acc, rhs) {
+                (Some(a), Some(b)) => Some(match pair.0 {
+                    AdditiveExprOperator::Plus => a.combine(b, |x, y| x + y, |x, y| x + y),
+                    AdditiveExprOperator::Minus => a.combine(b, |x, y| x - y, |x, y| x - y),
+                }),
+                _ => None,
+            };
+        }
+
+        Ok(match acc {
+            Some(value) => xpath_item_set![value.into_item()],
+            None => XpathItemSet::new(),
+        })
+    }
+}
+
 impl Display for AdditiveExpr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.expr)?;
@@ -122,6 +236,69 @@ pub struct MultiplicativeExpr {
     pub items: Vec<MultiplicativeExprPair>,
 }
 
+impl MultiplicativeExpr {
+    pub(crate) fn eval<'tree>(
+        &self,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let mut acc = NumericValue::from_items(&self.expr.eval(context)?, "left operand of */div/idiv/mod")?;
+
+        for pair in &self.items {
+            let rhs =
+                NumericValue::from_items(&pair.1.eval(context)?, "right operand of */div/idiv/mod")?;
+
+            acc = match (acc, rhs) {
+                (Some(a), Some(b)) => Some(apply_multiplicative_operator(&pair.0, a, b)?),
+                _ => None,
+            };
+        }
+
+        Ok(match acc {
+            Some(value) => xpath_item_set![value.into_item()],
+            None => XpathItemSet::new(),
+        })
+    }
+}
+
+fn apply_multiplicative_operator(
+    operator: &MultiplicativeExprOperator,
+    a: NumericValue,
+    b: NumericValue,
+) -> Result<NumericValue, ExpressionApplyError> {
+    match operator {
+        MultiplicativeExprOperator::Star => Ok(a.combine(b, |x, y| x * y, |x, y| x * y)),
+        MultiplicativeExprOperator::Div => match (a, b) {
+            (NumericValue::Double(_), _) | (_, NumericValue::Double(_)) => {
+                Ok(NumericValue::Double(a.as_f64() / b.as_f64()))
+            }
+            _ => {
+                if b.as_f64() == 0.0 {
+                    return Err(ExpressionApplyError {
+                        msg: "division by zero in 'div' expression".to_string(),
+                    });
+                }
+                Ok(NumericValue::Decimal(a.as_f64() / b.as_f64()))
+            }
+        },
+        MultiplicativeExprOperator::IntegerDiv => {
+            if b.as_f64() == 0.0 {
+                return Err(ExpressionApplyError {
+                    msg: "division by zero in 'idiv' expression".to_string(),
+                });
+            }
+            Ok(NumericValue::Integer((a.as_f64() / b.as_f64()).trunc() as i64))
+        }
+        MultiplicativeExprOperator::Modulus => {
+            if b.as_f64() == 0.0 {
+                return Err(ExpressionApplyError {
+                    msg: "division by zero in 'mod' expression".to_string(),
+                });
+            }
+            Ok(a.combine(b, |x, y| x % y, |x, y| x % y))
+        }
+    }
+}
+
 impl Display for MultiplicativeExpr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.expr)?;
@@ -189,6 +366,34 @@ pub struct UnaryExpr {
     pub expr: ValueExpr,
 }
 
+impl UnaryExpr {
+    pub(crate) fn eval<'tree>(
+        &self,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let items = self.expr.eval(context)?;
+
+        // An even number of leading `-` symbols cancels out; an odd number
+        // negates the operand once.
+        let is_negative = self
+            .leading_symbols
+            .iter()
+            .filter(|symbol| matches!(symbol, UnarySymbol::Minus))
+            .count()
+            % 2
+            == 1;
+
+        if !is_negative {
+            return Ok(items);
+        }
+
+        match NumericValue::from_items(&items, "operand of unary -")? {
+            Some(value) => Ok(xpath_item_set![value.negate().into_item()]),
+            None => Ok(XpathItemSet::new()),
+        }
+    }
+}
+
 impl Display for UnaryExpr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for x in &self.leading_symbols {
@@ -223,6 +428,15 @@ fn value_expr(input: &str) -> Res<&str, ValueExpr> {
 #[derive(PartialEq, Debug)]
 pub struct ValueExpr(pub SimpleMapExpr);
 
+impl ValueExpr {
+    pub(crate) fn eval<'tree>(
+        &self,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        self.0.eval(context)
+    }
+}
+
 impl Display for ValueExpr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
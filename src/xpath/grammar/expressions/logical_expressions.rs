@@ -2,18 +2,157 @@
 
 use std::fmt::Display;
 
-use nom::{bytes::complete::tag, multi::many0, sequence::tuple};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    combinator::{opt, value},
+};
 
-use crate::xpath::grammar::recipes::Res;
+use crate::xpath::{
+    grammar::{
+        data_model::{AnyAtomicType, XpathItem},
+        recipes::Res,
+    },
+    xpath_item_set::XpathItemSet,
+    ExpressionApplyError,
+};
 
 use super::comparison_expressions::{comparison_expr, ComparisonExpr};
 
+// The `union`/`intersect`/`except` node-set combinators already have a full
+// `eval` (see `UnionExpr::eval` in
+// `sequence_expressions::combining_node_sequences`). `OrExpr`/`AndExpr` below
+// still only have parsers, no full `eval` — that would dispatch through
+// `ComparisonExpr::eval`, but `comparison_expressions` (imported above)
+// isn't a file in this checkout, so `ComparisonExpr`'s shape can't be
+// inspected and an `eval` can't be added here without guessing its
+// signature. What *is* tractable without it is the short-circuiting shape
+// of the evaluation and the effective-boolean-value (EBV) rule it relies
+// on at each step — see [`effective_boolean_value`] and
+// [`AndExpr::eval_with`]/[`OrExpr::eval_with`] below, which take the
+// still-missing per-operand evaluation as a closure so a real `eval` can be
+// written as a one-line call to them once `ComparisonExpr::eval` exists.
+
+/// `or`/`and`, the two tiers of the logical-expression grammar, ranked by
+/// binding power (higher binds tighter). `and` is the higher-precedence
+/// operator, per
+/// <https://www.w3.org/TR/2017/REC-xpath-31-20170321/#id-precedence-order>.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LogicalOp {
+    Or,
+    And,
+}
+
+impl LogicalOp {
+    /// `(left_bp, right_bp)`. Both tiers are left-associative, so
+    /// `right_bp` is always one step above `left_bp` — recursing into the
+    /// right-hand side with `min_bp = right_bp` refuses to consume another
+    /// operator at the same tier, forcing it back up to be folded in by the
+    /// caller's loop instead.
+    fn binding_power(self) -> (u8, u8) {
+        match self {
+            LogicalOp::Or => (1, 2),
+            LogicalOp::And => (3, 4),
+        }
+    }
+
+    fn parser(input: &str) -> Res<&str, LogicalOp> {
+        alt((
+            value(LogicalOp::And, tag("and")),
+            value(LogicalOp::Or, tag("or")),
+        ))(input)
+    }
+}
+
+/// A node in the binary tree built by [`logical_expr_bp`] before it's
+/// reshaped into the flat [`OrExpr`]/[`AndExpr`] chains callers expect.
+enum LogicalNode {
+    Comparison(ComparisonExpr),
+    Binary(LogicalOp, Box<LogicalNode>, Box<LogicalNode>),
+}
+
+/// The precedence-climbing driver for the `or`/`and` tier of the expression
+/// ladder, modeled on rust-analyzer's `expr_bp`: parse one `ComparisonExpr`
+/// operand, then repeatedly look at the next operator — stop and return to
+/// the caller once its left binding power drops below `min_bp`, otherwise
+/// consume it and recurse for the right-hand side with `min_bp` raised to
+/// that operator's right binding power, folding the result in
+/// left-associatively.
+///
+/// This replaces what used to be two hand-written
+/// `tuple(X, many0(tuple(op, X)))` parsers (one per `or`/`and` tier) with a
+/// single routine parameterized by [`LogicalOp::binding_power`]; adding a
+/// third tier *within this same operand type* (another `ComparisonExpr`-level
+/// operator) would be a one-line addition there. It does not reach the
+/// comparison/additive/multiplicative/union tiers below `ComparisonExpr` —
+/// those remain separate hand-written drivers in `arithmetic_expressions.rs`
+/// and `combining_node_sequences.rs`, since folding them in would mean
+/// unifying their differently-shaped operand and result types into one node
+/// enum, which is a larger, not yet attempted, follow-up.
+fn logical_expr_bp(input: &str, min_bp: u8) -> Res<&str, LogicalNode> {
+    let (mut input, mut lhs) = comparison_expr(input)
+        .map(|(next_input, expr)| (next_input, LogicalNode::Comparison(expr)))?;
+
+    while let (next_input, Some(op)) = opt(LogicalOp::parser)(input)? {
+        let (left_bp, right_bp) = op.binding_power();
+        if left_bp < min_bp {
+            break;
+        }
+
+        let (next_input, rhs) = logical_expr_bp(next_input, right_bp)?;
+        lhs = LogicalNode::Binary(op, Box::new(lhs), Box::new(rhs));
+        input = next_input;
+    }
+
+    Ok((input, lhs))
+}
+
+/// Unfolds the left-leaning spine of `or` nodes a [`logical_expr_bp`] parse
+/// produces back into the flat chain [`OrExpr`] stores.
+fn into_or_expr(node: LogicalNode) -> OrExpr {
+    let mut items = Vec::new();
+    let mut current = node;
+
+    while let LogicalNode::Binary(LogicalOp::Or, lhs, rhs) = current {
+        items.push(into_and_expr(*rhs));
+        current = *lhs;
+    }
+    items.reverse();
+
+    OrExpr {
+        expr: into_and_expr(current),
+        items,
+    }
+}
+
+/// Unfolds the left-leaning spine of `and` nodes back into the flat chain
+/// [`AndExpr`] stores. Only called on sub-trees `logical_expr_bp` built
+/// with `min_bp` high enough to exclude `or`, so every `rhs` it encounters
+/// is a bare `ComparisonExpr`, never another `and`/`or` node.
+fn into_and_expr(node: LogicalNode) -> AndExpr {
+    match node {
+        LogicalNode::Comparison(expr) => AndExpr {
+            expr,
+            items: Vec::new(),
+        },
+        LogicalNode::Binary(LogicalOp::And, lhs, rhs) => {
+            let LogicalNode::Comparison(rhs) = *rhs else {
+                unreachable!("an `and` operator's right-hand side is always a single comparison")
+            };
+
+            let mut and_expr = into_and_expr(*lhs);
+            and_expr.items.push(rhs);
+            and_expr
+        }
+        LogicalNode::Binary(LogicalOp::Or, _, _) => {
+            unreachable!("an `or` node can't appear inside an `and`-level operand")
+        }
+    }
+}
+
 pub fn or_expr(input: &str) -> Res<&str, OrExpr> {
     // https://www.w3.org/TR/2017/REC-xpath-31-20170321/#doc-xpath31-OrExpr
-    tuple((and_expr, many0(tuple((tag("or"), and_expr)))))(input).map(|(next_input, res)| {
-        let items = res.1.into_iter().map(|res| res.1).collect();
-        (next_input, OrExpr { expr: res.0, items })
-    })
+    logical_expr_bp(input, 0).map(|(next_input, node)| (next_input, into_or_expr(node)))
 }
 
 #[derive(PartialEq, Debug)]
@@ -33,15 +172,30 @@ impl Display for OrExpr {
     }
 }
 
-fn and_expr(input: &str) -> Res<&str, AndExpr> {
-    // https://www.w3.org/TR/2017/REC-xpath-31-20170321/#prod-xpath31-AndExpr
+impl OrExpr {
+    /// Short-circuiting `or`: evaluates each [`AndExpr`] operand left to
+    /// right via `compute`, stopping and returning `true` as soon as one
+    /// evaluates true, without computing the rest.
+    ///
+    /// `compute` stands in for `AndExpr::eval_with` (composed with
+    /// whatever eventually evaluates a bare `ComparisonExpr`), which this
+    /// file can't call directly — see the module doc above.
+    pub(crate) fn eval_with(
+        &self,
+        mut compute: impl FnMut(&AndExpr) -> Result<bool, ExpressionApplyError>,
+    ) -> Result<bool, ExpressionApplyError> {
+        if compute(&self.expr)? {
+            return Ok(true);
+        }
 
-    tuple((comparison_expr, many0(tuple((tag("and"), comparison_expr)))))(input).map(
-        |(next_input, res)| {
-            let items = res.1.into_iter().map(|res| res.1).collect();
-            (next_input, AndExpr { expr: res.0, items })
-        },
-    )
+        for item in &self.items {
+            if compute(item)? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
 }
 
 #[derive(PartialEq, Debug)]
@@ -59,4 +213,62 @@ impl Display for AndExpr {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+impl AndExpr {
+    /// Short-circuiting `and`: evaluates each [`ComparisonExpr`] operand
+    /// left to right via `compute`, stopping and returning `false` as soon
+    /// as one's effective boolean value is false, without computing the
+    /// rest.
+    ///
+    /// `compute` stands in for `ComparisonExpr::eval` followed by
+    /// [`effective_boolean_value`], which this file can't call directly —
+    /// see the module doc above.
+    pub(crate) fn eval_with(
+        &self,
+        mut compute: impl FnMut(&ComparisonExpr) -> Result<bool, ExpressionApplyError>,
+    ) -> Result<bool, ExpressionApplyError> {
+        if !compute(&self.expr)? {
+            return Ok(false);
+        }
+
+        for item in &self.items {
+            if !compute(item)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+/// The effective boolean value (EBV) of an already-evaluated operand
+/// sequence, per <https://www.w3.org/TR/2017/REC-xpath-31-20170321/#id-ebv>:
+/// an empty sequence is false; a single boolean is itself; a single number
+/// is false iff it's zero or NaN; a single string is false iff empty; a
+/// sequence whose first item is a node is true; anything else (a
+/// multi-item sequence of atomic values) is a type error.
+pub(crate) fn effective_boolean_value(items: &XpathItemSet) -> Result<bool, ExpressionApplyError> {
+    match items.iter().next() {
+        None => Ok(false),
+        Some(XpathItem::Node(_)) => Ok(true),
+        Some(XpathItem::AnyAtomicType(AnyAtomicType::Boolean(b))) if items.len() == 1 => Ok(*b),
+        Some(XpathItem::AnyAtomicType(AnyAtomicType::String(s))) if items.len() == 1 => {
+            Ok(!s.is_empty())
+        }
+        Some(XpathItem::AnyAtomicType(AnyAtomicType::Integer(i))) if items.len() == 1 => {
+            Ok(*i != 0)
+        }
+        Some(XpathItem::AnyAtomicType(AnyAtomicType::Decimal(d) | AnyAtomicType::Double(d)))
+            if items.len() == 1 =>
+        {
+            Ok(*d != 0.0 && !d.is_nan())
+        }
+        _ => Err(ExpressionApplyError {
+            msg: String::from(
+                "effective boolean value is not defined for a sequence of more than \
+                 one item unless the first item is a node",
+            ),
+        }),
+    }
+}
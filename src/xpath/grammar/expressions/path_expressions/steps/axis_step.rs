@@ -74,29 +74,44 @@ impl AxisStep {
         &self,
         context: &XPathExpressionContext<'tree>,
     ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
-        let nodes = self.step_type.eval(context)?;
-        let items: XpathItemSet<'tree> = nodes.into_iter().map(XpathItem::Node).collect();
+        // Evaluate (and predicate-filter) this step once per originating
+        // context node rather than over their flattened union. Positional
+        // predicates are defined relative to the candidates produced from a
+        // single parent: `//p[1]` selects every `p` that is first among its
+        // own parent's matching children (many results), not just the first
+        // `p` in document order overall (one result).
+        let mut filtered_items = XpathItemSet::new();
 
-        // If there are no predicates, return expression result.
-        if self.predicates.is_empty() {
-            return Ok(items);
-        }
+        for origin in context.all_items.iter() {
+            let mut origin_set = XpathItemSet::new();
+            origin_set.insert(origin.clone());
+            let origin_context = XPathExpressionContext::new(context.item_tree, &origin_set, 1);
 
-        // Otherwise, filter using predicates.
-        let mut filtered_items = XpathItemSet::new();
-        for (i, item) in items.iter().enumerate() {
-            // All predicates must match for a node to be selected.
-            let mut is_match = true;
-
-            let predicate_context = XPathExpressionContext::new(context.item_tree, &items, i + 1);
-            for predicate in self.predicates.iter() {
-                if !predicate.is_match(&predicate_context)? {
-                    is_match = false;
+            let nodes = self.step_type.eval(&origin_context)?;
+            let items: XpathItemSet<'tree> = nodes.into_iter().map(XpathItem::Node).collect();
+
+            if self.predicates.is_empty() {
+                for item in items {
+                    filtered_items.insert(item);
                 }
+                continue;
             }
 
-            if is_match {
-                filtered_items.insert(item.clone());
+            for (i, item) in items.iter().enumerate() {
+                // All predicates must match for a node to be selected.
+                let mut is_match = true;
+
+                let predicate_context =
+                    XPathExpressionContext::new(context.item_tree, &items, i + 1);
+                for predicate in self.predicates.iter() {
+                    if !predicate.is_match(&predicate_context)? {
+                        is_match = false;
+                    }
+                }
+
+                if is_match {
+                    filtered_items.insert(item.clone());
+                }
             }
         }
 
@@ -104,6 +119,14 @@ impl AxisStep {
     }
 }
 
+// NOTE: the full named-axis family (`following`, `preceding`,
+// `ancestor(-or-self)`, `descendant(-or-self)`, `namespace`, etc.) belongs on
+// `ForwardStep`/`ReverseStep` themselves — the modules that define those
+// types and the `forward_axis`/`reverse_axis` axis tables this file already
+// imports (`steps::axes::{forward_axis, reverse_axis}`) aren't present in
+// this checkout, so that work can't be done here without guessing at their
+// shape. `AxisStepType` only distinguishes the two step directions; it has
+// no axis-specific logic of its own to extend.
 #[derive(PartialEq, Debug, Clone)]
 pub enum AxisStepType {
     ReverseStep(ReverseStep),
@@ -6,13 +6,16 @@ use nom::{
     branch::alt, bytes::complete::tag, character::complete::char, error::context, sequence::tuple,
 };
 
-use crate::xpath::grammar::{
-    data_model::{Node, XpathItem},
-    recipes::Res,
-    terminal_symbols::braced_uri_literal,
-    types::{eq_name, kind_test, EQName, KindTest},
-    xml_names::{nc_name, QName},
-    XpathItemTreeNodeData,
+use crate::xpath::{
+    grammar::{
+        data_model::{Node, XpathItem},
+        recipes::Res,
+        terminal_symbols::braced_uri_literal,
+        types::{eq_name, kind_test, EQName, KindTest},
+        xml_names::{nc_name, QName},
+        XpathItemTreeNodeData,
+    },
+    XpathExpressionContext,
 };
 
 pub fn node_test(input: &str) -> Res<&str, NodeTest> {
@@ -45,10 +48,17 @@ impl Display for NodeTest {
 }
 
 impl NodeTest {
-    pub(crate) fn is_match(&self, node: &Node) -> bool {
+    // `text()`/`comment()`/`processing-instruction()`/`node()` are dispatched
+    // here via `KindTest::is_match`, but `KindTest` itself (and the
+    // `kind_test` parser this file's `node_test` wires in above) is defined
+    // in `types::kind_test`, a module not present in this checkout — only
+    // `types::sequence_type`'s unrelated `ItemType` kind-test parser exists
+    // here. Extending the kind-test set belongs in that missing module, not
+    // in this dispatch, so there's no safe change to make from this file.
+    pub(crate) fn is_match(&self, node: &Node, context: &XpathExpressionContext) -> bool {
         match self {
             NodeTest::KindTest(test) => test.is_match(&XpathItem::Node(node.clone())),
-            NodeTest::NameTest(test) => test.is_match(node),
+            NodeTest::NameTest(test) => test.is_match(node, context),
         }
     }
 }
@@ -83,7 +93,7 @@ impl Display for NameTest {
 }
 
 impl NameTest {
-    pub(crate) fn is_match(&self, node: &Node) -> bool {
+    pub(crate) fn is_match(&self, node: &Node, context: &XpathExpressionContext) -> bool {
         // Name test only works on element nodes
         let element = if let Node::TreeNode(tree_node) = node {
             if let XpathItemTreeNodeData::ElementNode(element) = &tree_node.data {
@@ -98,12 +108,30 @@ impl NameTest {
         match self {
             NameTest::Name(name) => match name {
                 EQName::QName(qname) => match qname {
-                    QName::PrefixedName(_) => todo!("NameTest::is_match PrefixedName"),
-                    QName::UnprefixedName(unprefixed_name) => unprefixed_name == &element.name,
+                    // A prefixed name resolves its prefix against the in-scope namespace
+                    // bindings declared on the evaluation context, then compares the
+                    // resolved URI and local name against the element's own.
+                    QName::PrefixedName(prefixed_name) => {
+                        let Some(uri) = context.resolve_prefix(&prefixed_name.prefix) else {
+                            return false;
+                        };
+
+                        element.namespace_uri.as_deref() == Some(uri.as_str())
+                            && prefixed_name.local_part == element.local_name
+                    }
+                    // An unprefixed name only matches elements that are not in any namespace.
+                    QName::UnprefixedName(unprefixed_name) => {
+                        element.namespace_uri.is_none() && unprefixed_name == &element.name
+                    }
                 },
-                EQName::UriQualifiedName(_) => todo!("NameTest::is_match UriQualifiedName"),
+                // A URI-qualified name carries its namespace URI directly, so no prefix
+                // resolution is needed.
+                EQName::UriQualifiedName(uri_qualified_name) => {
+                    element.namespace_uri.as_deref() == Some(uri_qualified_name.uri.as_str())
+                        && uri_qualified_name.local_part == element.local_name
+                }
             },
-            NameTest::Wildcard(wildcard) => wildcard.is_match(node),
+            NameTest::Wildcard(wildcard) => wildcard.is_match(node, context),
         }
     }
 }
@@ -161,12 +189,34 @@ impl Display for Wildcard {
 }
 
 impl Wildcard {
-    pub(crate) fn is_match(&self, node: &Node) -> bool {
+    pub(crate) fn is_match(&self, node: &Node, context: &XpathExpressionContext) -> bool {
+        if let Wildcard::Simple = self {
+            return true;
+        }
+
+        // The remaining wildcard forms only make sense against element nodes.
+        let element = if let Node::TreeNode(tree_node) = node {
+            if let XpathItemTreeNodeData::ElementNode(element) = &tree_node.data {
+                element
+            } else {
+                return false;
+            }
+        } else {
+            return false;
+        };
+
         match self {
-            Wildcard::Simple => true,
-            Wildcard::PrefixedName(_) => todo!("Wildcard::is_match PrefixedName"),
-            Wildcard::SuffixedName(_) => todo!("Wildcard::is_match SuffixedName"),
-            Wildcard::BracedUri(_) => todo!("Wildcard::is_match BracedUri"),
+            Wildcard::Simple => unreachable!(),
+            // `*:foo` matches any namespace, as long as the local name is `foo`.
+            Wildcard::PrefixedName(local_part) => &element.local_name == local_part,
+            // `p:*` matches any local name, as long as the element's namespace URI is the
+            // one bound to `p` in the current evaluation context.
+            Wildcard::SuffixedName(prefix) => match context.resolve_prefix(prefix) {
+                Some(uri) => element.namespace_uri.as_deref() == Some(uri.as_str()),
+                None => false,
+            },
+            // `Q{uri}*` matches any local name in the given namespace URI.
+            Wildcard::BracedUri(uri) => element.namespace_uri.as_deref() == Some(uri.as_str()),
         }
     }
 }
@@ -4,19 +4,15 @@ use std::fmt::Display;
 
 use nom::{error::context, sequence::tuple};
 
-use crate::{
-    xpath::{
-        grammar::{
-            data_model::{Node, XpathItem},
-            expressions::common::{argument_list, ArgumentList},
-            recipes::Res,
-            types::{eq_name, EQName},
-            xml_names::QName,
-        },
-        xpath_item_set::XpathItemSet,
-        ExpressionApplyError, XpathExpressionContext,
+use crate::xpath::{
+    grammar::{
+        expressions::common::{argument_list, ArgumentList},
+        recipes::Res,
+        types::{eq_name, EQName},
+        xml_names::QName,
     },
-    xpath_item_set,
+    xpath_item_set::XpathItemSet,
+    ExpressionApplyError, XpathExpressionContext,
 };
 
 pub fn function_call(input: &str) -> Res<&str, FunctionCall> {
@@ -45,30 +41,617 @@ impl Display for FunctionCall {
     }
 }
 
+/// The namespace URI of the standard XPath/XQuery function library.
+///
+/// Unprefixed function names resolve here, matching the default function
+/// namespace defined by the spec.
+pub const FN_NAMESPACE: &str = "http://www.w3.org/2005/xpath-functions";
+
+/// The evaluated arguments of a call: one already-evaluated sequence per
+/// position in a [`FunctionCall`]'s argument list, in argument order.
+type FunctionArgs<'tree> = Vec<XpathItemSet<'tree>>;
+
+/// The signature a function implementation is dispatched through.
+///
+/// Implementations receive the already-evaluated argument sequences (in
+/// argument order) and the expression context, and return the resulting
+/// sequence.
+pub(crate) type FunctionImpl =
+    for<'tree> fn(FunctionArgs<'tree>, &XpathExpressionContext<'tree>) -> Result<XpathItemSet<'tree>, ExpressionApplyError>;
+
+/// How many arguments a [`FunctionSignature`] accepts.
+#[derive(Clone, Copy)]
+enum Arity {
+    /// Accepts exactly this many arguments.
+    Exact(usize),
+    /// Accepts this many arguments or more, e.g. the variadic `fn:concat`.
+    AtLeast(usize),
+}
+
+impl Arity {
+    fn matches(self, arity: usize) -> bool {
+        match self {
+            Arity::Exact(n) => n == arity,
+            Arity::AtLeast(n) => arity >= n,
+        }
+    }
+}
+
+/// A function known to the registry, keyed by namespace URI, local name and
+/// arity (the three things a call site provides).
+struct FunctionSignature {
+    namespace_uri: &'static str,
+    local_name: &'static str,
+    arity: Arity,
+    implementation: FunctionImpl,
+}
+
+/// The built-in function library.
+///
+/// New functions are registered by adding an entry here; [`FunctionCall::eval`]
+/// looks a call up by `(namespace_uri, local_name, arity)` and invokes the
+/// matching implementation. This is intentionally a flat table rather than a
+/// `HashMap` since the library is small and fixed at compile time.
+const REGISTRY: &[FunctionSignature] = &[
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "count",
+        arity: Arity::Exact(1),
+        implementation: functions::count,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "position",
+        arity: Arity::Exact(0),
+        implementation: functions::position,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "last",
+        arity: Arity::Exact(0),
+        implementation: functions::last,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "string",
+        arity: Arity::Exact(0),
+        implementation: functions::string_zero_args,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "string",
+        arity: Arity::Exact(1),
+        implementation: functions::string,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "string-length",
+        arity: Arity::Exact(0),
+        implementation: functions::string_length_zero_args,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "string-length",
+        arity: Arity::Exact(1),
+        implementation: functions::string_length,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "contains",
+        arity: Arity::Exact(2),
+        implementation: functions::contains,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "starts-with",
+        arity: Arity::Exact(2),
+        implementation: functions::starts_with,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "ends-with",
+        arity: Arity::Exact(2),
+        implementation: functions::ends_with,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "substring",
+        arity: Arity::Exact(2),
+        implementation: functions::substring,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "substring",
+        arity: Arity::Exact(3),
+        implementation: functions::substring_with_length,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "concat",
+        // `fn:concat` is variadic, taking two or more arguments.
+        arity: Arity::AtLeast(2),
+        implementation: functions::concat,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "normalize-space",
+        arity: Arity::Exact(0),
+        implementation: functions::normalize_space_zero_args,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "normalize-space",
+        arity: Arity::Exact(1),
+        implementation: functions::normalize_space,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "not",
+        arity: Arity::Exact(1),
+        implementation: functions::not,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "boolean",
+        arity: Arity::Exact(1),
+        implementation: functions::boolean,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "name",
+        arity: Arity::Exact(0),
+        implementation: functions::name,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "local-name",
+        arity: Arity::Exact(0),
+        implementation: functions::local_name,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "text",
+        arity: Arity::Exact(0),
+        implementation: functions::text,
+    },
+    FunctionSignature {
+        namespace_uri: FN_NAMESPACE,
+        local_name: "root",
+        arity: Arity::Exact(0),
+        implementation: functions::root,
+    },
+];
+
 impl FunctionCall {
     pub(crate) fn eval<'tree>(
         &self,
         context: &XpathExpressionContext<'tree>,
     ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
-        match &self.name {
+        let (namespace_uri, local_name) = match &self.name {
             EQName::QName(qname) => match qname {
+                // A prefixed name not bound to `fn` is user/library namespace; we only
+                // ship the standard library so anything else is unknown for now.
                 QName::PrefixedName(prefixed_name) => {
-                    if prefixed_name.prefix == "fn" {
-                        // Root function selects the root node of the tree.
-                        if prefixed_name.local_part == "root" {
-                            return Ok(xpath_item_set![XpathItem::Node(Node::TreeNode(
-                                context.item_tree.root(),
-                            ))]);
-                        }
+                    if prefixed_name.prefix != "fn" {
+                        return Err(ExpressionApplyError {
+                            msg: format!("Unknown function {}", self.name),
+                        });
                     }
-
-                    Err(ExpressionApplyError {
-                        msg: format!("Unknown function {}", self.name.to_string()),
-                    })
+                    (FN_NAMESPACE.to_string(), prefixed_name.local_part.clone())
                 }
-                QName::UnprefixedName(_) => todo!("FunctionCall::eval UnprefixedName"),
+                // Unprefixed names resolve against the default function namespace.
+                QName::UnprefixedName(local_name) => (FN_NAMESPACE.to_string(), local_name.clone()),
             },
-            EQName::UriQualifiedName(_) => todo!("FunctionCall::eval UriQualifiedName"),
+            EQName::UriQualifiedName(uri_qualified_name) => (
+                uri_qualified_name.uri.clone(),
+                uri_qualified_name.local_part.clone(),
+            ),
+        };
+
+        let arity = self.argument_list.arguments.len();
+
+        // Built-ins take precedence; fall through to functions the caller
+        // registered on the context (see `XpathExpressionContext::register_function`).
+        let implementation = REGISTRY
+            .iter()
+            .find(|s| {
+                s.namespace_uri == namespace_uri
+                    && s.local_name == local_name
+                    && s.arity.matches(arity)
+            })
+            .map(|s| s.implementation)
+            .or_else(|| {
+                context
+                    .custom_functions
+                    .get(&(namespace_uri.clone(), local_name.clone(), arity))
+                    .copied()
+            })
+            .ok_or_else(|| ExpressionApplyError {
+                msg: format!("Unknown function {}", self.name),
+            })?;
+
+        let args = self
+            .argument_list
+            .arguments
+            .iter()
+            .map(|arg| arg.eval(context))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        implementation(args, context)
+    }
+}
+
+/// Implementations of the standard function library functions registered in
+/// [`REGISTRY`].
+mod functions {
+    use crate::{
+        xpath::{
+            grammar::data_model::{AnyAtomicType, Node, XpathItem},
+            xpath_item_set::XpathItemSet,
+            ExpressionApplyError, XpathExpressionContext,
+        },
+        xpath_item_set,
+    };
+
+    use super::FunctionArgs;
+
+    /// Computes the string-value of a single item, per
+    /// <https://www.w3.org/TR/2017/REC-xpath-31-20170321/#dt-string-value>.
+    fn item_string_value(item: &XpathItem, context: &XpathExpressionContext) -> String {
+        match item {
+            XpathItem::Node(Node::TreeNode(node)) => node.all_text(context.item_tree),
+            XpathItem::Node(node) => node.to_string(),
+            XpathItem::AnyAtomicType(atomic) => atomic.to_string(),
+        }
+    }
+
+    /// The string-value of the first item in a sequence, or the empty string
+    /// for an empty sequence (this is the zero-argument `fn:string` default).
+    fn sequence_string_value(items: &XpathItemSet, context: &XpathExpressionContext) -> String {
+        items
+            .iter()
+            .next()
+            .map(|item| item_string_value(item, context))
+            .unwrap_or_default()
+    }
+
+    pub(super) fn root<'tree>(
+        _args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        Ok(xpath_item_set![XpathItem::Node(Node::TreeNode(
+            context.item_tree.root(),
+        ))])
+    }
+
+    pub(super) fn count<'tree>(
+        mut args: FunctionArgs<'tree>,
+        _context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let sequence = args.remove(0);
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::Integer(sequence.len() as i64)
+        )])
+    }
+
+    // `context.position`/`context.all_items` are scoped per originating
+    // parent by `AxisStep::eval`, so these fall out correct for free inside
+    // predicates like `p[position() = last()]`.
+    pub(super) fn position<'tree>(
+        _args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::Integer(context.position as i64)
+        )])
+    }
+
+    pub(super) fn last<'tree>(
+        _args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::Integer(context.all_items.len() as i64)
+        )])
+    }
+
+    pub(super) fn string<'tree>(
+        args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let value = sequence_string_value(&args[0], context);
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::String(value)
+        )])
+    }
+
+    pub(super) fn string_zero_args<'tree>(
+        _args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let value = sequence_string_value(context.all_items, context);
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::String(value)
+        )])
+    }
+
+    pub(super) fn string_length<'tree>(
+        args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let value = sequence_string_value(&args[0], context);
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::Integer(value.chars().count() as i64)
+        )])
+    }
+
+    pub(super) fn string_length_zero_args<'tree>(
+        _args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let value = sequence_string_value(context.all_items, context);
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::Integer(value.chars().count() as i64)
+        )])
+    }
+
+    pub(super) fn contains<'tree>(
+        args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let haystack = sequence_string_value(&args[0], context);
+        let needle = sequence_string_value(&args[1], context);
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::Boolean(haystack.contains(&needle))
+        )])
+    }
+
+    pub(super) fn starts_with<'tree>(
+        args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let value = sequence_string_value(&args[0], context);
+        let prefix = sequence_string_value(&args[1], context);
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::Boolean(value.starts_with(&prefix))
+        )])
+    }
+
+    pub(super) fn ends_with<'tree>(
+        args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let value = sequence_string_value(&args[0], context);
+        let suffix = sequence_string_value(&args[1], context);
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::Boolean(value.ends_with(&suffix))
+        )])
+    }
+
+    pub(super) fn substring<'tree>(
+        args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let value = sequence_string_value(&args[0], context);
+        let start = sequence_string_value(&args[1], context)
+            .parse::<f64>()
+            .unwrap_or(1.0);
+
+        // XPath substring positions are 1-based and may be fractional; round to the
+        // nearest integer per the spec's numeric-to-integer conversion rules.
+        let start_index = (start.round().max(1.0) as usize).saturating_sub(1);
+        let result = value.chars().skip(start_index).collect::<String>();
+
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::String(result)
+        )])
+    }
+
+    /// The 3-argument form of `fn:substring`, bounding the result to
+    /// `$length` characters.
+    pub(super) fn substring_with_length<'tree>(
+        args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let value = sequence_string_value(&args[0], context);
+        let start = sequence_string_value(&args[1], context)
+            .parse::<f64>()
+            .unwrap_or(1.0)
+            .round();
+        let length = sequence_string_value(&args[2], context)
+            .parse::<f64>()
+            .unwrap_or(0.0)
+            .round();
+
+        // Characters are 1-indexed; keep those whose position satisfies
+        // start <= position < start + length.
+        let end = start + length;
+        let result: String = value
+            .chars()
+            .enumerate()
+            .filter_map(|(i, c)| {
+                let position = (i + 1) as f64;
+                (position >= start && position < end).then_some(c)
+            })
+            .collect();
+
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::String(result)
+        )])
+    }
+
+    pub(super) fn concat<'tree>(
+        args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let joined: String = args
+            .iter()
+            .map(|sequence| sequence_string_value(sequence, context))
+            .collect();
+
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::String(joined)
+        )])
+    }
+
+    pub(super) fn normalize_space<'tree>(
+        args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let value = sequence_string_value(&args[0], context);
+        let normalized = value.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::String(normalized)
+        )])
+    }
+
+    pub(super) fn normalize_space_zero_args<'tree>(
+        _args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let value = sequence_string_value(context.all_items, context);
+        let normalized = value.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::String(normalized)
+        )])
+    }
+
+    /// The effective boolean value of a sequence, per
+    /// <https://www.w3.org/TR/2017/REC-xpath-31-20170321/#id-ebv>. Where the
+    /// spec raises a type error instead (several atomic values, or one
+    /// atomic value of another type), this returns `false` for several items
+    /// and `true` for one.
+    fn effective_boolean_value(items: &XpathItemSet, context: &XpathExpressionContext) -> bool {
+        match items.iter().next() {
+            None => false,
+            Some(XpathItem::AnyAtomicType(AnyAtomicType::Boolean(b))) if items.len() == 1 => *b,
+            Some(XpathItem::AnyAtomicType(AnyAtomicType::String(_))) if items.len() == 1 => {
+                !sequence_string_value(items, context).is_empty()
+            }
+            Some(XpathItem::AnyAtomicType(AnyAtomicType::Integer(i))) if items.len() == 1 => {
+                *i != 0
+            }
+            Some(XpathItem::AnyAtomicType(AnyAtomicType::Decimal(d) | AnyAtomicType::Double(d)))
+                if items.len() == 1 =>
+            {
+                *d != 0.0 && !d.is_nan()
+            }
+            Some(XpathItem::Node(_)) => true,
+            _ => items.len() == 1,
+        }
+    }
+
+    pub(super) fn not<'tree>(
+        args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let value = effective_boolean_value(&args[0], context);
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::Boolean(!value)
+        )])
+    }
+
+    pub(super) fn boolean<'tree>(
+        args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let value = effective_boolean_value(&args[0], context);
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::Boolean(value)
+        )])
+    }
+
+    fn current_element_name<'tree>(
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<String, ExpressionApplyError> {
+        let item = context.all_items.iter().next().ok_or_else(|| ExpressionApplyError {
+            msg: String::from("fn:name/fn:local-name called with no context item"),
+        })?;
+
+        match item {
+            XpathItem::Node(Node::TreeNode(node)) => Ok(node.to_string()),
+            _ => Err(ExpressionApplyError {
+                msg: String::from("fn:name/fn:local-name called on a non-element context item"),
+            }),
         }
     }
+
+    pub(super) fn name<'tree>(
+        _args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let name = current_element_name(context)?;
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::String(name)
+        )])
+    }
+
+    pub(super) fn local_name<'tree>(
+        _args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let name = current_element_name(context)?;
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::String(name)
+        )])
+    }
+
+    pub(super) fn text<'tree>(
+        _args: FunctionArgs<'tree>,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let value = sequence_string_value(context.all_items, context);
+        Ok(xpath_item_set![XpathItem::AnyAtomicType(
+            AnyAtomicType::String(value)
+        )])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn exact_arity_only_matches_that_count() {
+        // arrange
+        let arity = Arity::Exact(2);
+
+        // act / assert
+        assert!(!arity.matches(1));
+        assert!(arity.matches(2));
+        assert!(!arity.matches(3));
+    }
+
+    #[test]
+    fn at_least_arity_matches_the_floor_and_above() {
+        // arrange
+        let arity = Arity::AtLeast(2);
+
+        // act / assert
+        assert!(!arity.matches(1));
+        assert!(arity.matches(2));
+        assert!(arity.matches(3));
+        assert!(arity.matches(10));
+    }
+
+    #[test]
+    fn registry_has_a_variadic_concat_and_a_three_arg_substring() {
+        // arrange / act
+        let concat_arities: Vec<bool> = (2..=5)
+            .map(|arity| {
+                REGISTRY
+                    .iter()
+                    .any(|s| s.local_name == "concat" && s.arity.matches(arity))
+            })
+            .collect();
+        let has_substring_three = REGISTRY
+            .iter()
+            .any(|s| s.local_name == "substring" && s.arity.matches(3));
+
+        // assert
+        assert!(concat_arities.iter().all(|&matched| matched));
+        assert!(has_substring_three);
+    }
 }
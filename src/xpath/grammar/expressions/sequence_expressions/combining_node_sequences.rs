@@ -0,0 +1,286 @@
+//! https://www.w3.org/TR/2017/REC-xpath-31-20170321/#id-combining-node-sequences
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use indextree::NodeId;
+use nom::{branch::alt, bytes::complete::tag, multi::many0, sequence::tuple};
+
+use crate::xpath::{
+    grammar::{
+        data_model::{Node, XpathItem},
+        expressions::path_expressions::steps::axis_step::{axis_step, AxisStep},
+        recipes::Res,
+        XpathItemTree, XpathItemTreeNode,
+    },
+    xpath_item_set::XpathItemSet,
+    ExpressionApplyError, XpathExpressionContext,
+};
+
+pub fn union_expr(input: &str) -> Res<&str, UnionExpr> {
+    // https://www.w3.org/TR/2017/REC-xpath-31-20170321/#prod-xpath31-UnionExpr
+
+    fn union(input: &str) -> Res<&str, UnionExprOperator> {
+        alt((tag("union"), tag("|")))(input)
+            .map(|(next_input, _res)| (next_input, UnionExprOperator::Union))
+    }
+
+    fn intersect(input: &str) -> Res<&str, UnionExprOperator> {
+        tag("intersect")(input).map(|(next_input, _res)| (next_input, UnionExprOperator::Intersect))
+    }
+
+    fn except(input: &str) -> Res<&str, UnionExprOperator> {
+        tag("except")(input).map(|(next_input, _res)| (next_input, UnionExprOperator::Except))
+    }
+
+    tuple((
+        axis_step,
+        many0(tuple((alt((union, intersect, except)), axis_step))),
+    ))(input)
+    .map(|(next_input, res)| {
+        let items = res
+            .1
+            .into_iter()
+            .map(|res| UnionExprPair(res.0, res.1))
+            .collect();
+        (next_input, UnionExpr { expr: res.0, items })
+    })
+}
+
+#[derive(PartialEq, Debug)]
+pub struct UnionExpr {
+    pub expr: AxisStep,
+    pub items: Vec<UnionExprPair>,
+}
+
+impl Display for UnionExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.expr)?;
+        for x in &self.items {
+            write!(f, " {}", x)?
+        }
+
+        Ok(())
+    }
+}
+
+impl UnionExpr {
+    /// Evaluate this expression, combining the node sequences produced by
+    /// each operand per the expression's `union`/`intersect`/`except`
+    /// operators, applied left to right.
+    ///
+    /// The result is always sorted in document order and free of duplicate
+    /// node ids, per the invariant node-returning expressions must uphold.
+    pub(crate) fn eval<'tree>(
+        &self,
+        context: &XpathExpressionContext<'tree>,
+    ) -> Result<XpathItemSet<'tree>, ExpressionApplyError> {
+        let mut acc = self.expr.eval(context)?;
+
+        for pair in &self.items {
+            let rhs = pair.1.eval(context)?;
+            acc = match pair.0 {
+                UnionExprOperator::Union => union_node_sets(context.item_tree, acc, rhs),
+                UnionExprOperator::Intersect => intersect_node_sets(context.item_tree, acc, rhs),
+                UnionExprOperator::Except => except_node_sets(context.item_tree, acc, rhs),
+            };
+        }
+
+        Ok(sort_in_document_order(context.item_tree, acc))
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct UnionExprPair(pub UnionExprOperator, pub AxisStep);
+
+impl Display for UnionExprPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.0, self.1)
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub enum UnionExprOperator {
+    Union,
+    Intersect,
+    Except,
+}
+
+impl Display for UnionExprOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnionExprOperator::Union => write!(f, "union"),
+            UnionExprOperator::Intersect => write!(f, "intersect"),
+            UnionExprOperator::Except => write!(f, "except"),
+        }
+    }
+}
+
+/// Merge two node sequences, keeping every distinct node from either side.
+fn union_node_sets<'tree>(
+    tree: &'tree XpathItemTree,
+    a: XpathItemSet<'tree>,
+    b: XpathItemSet<'tree>,
+) -> XpathItemSet<'tree> {
+    sort_in_document_order(tree, a.into_iter().chain(b).collect())
+}
+
+/// Keep only the nodes present in both sequences.
+fn intersect_node_sets<'tree>(
+    tree: &'tree XpathItemTree,
+    a: XpathItemSet<'tree>,
+    b: XpathItemSet<'tree>,
+) -> XpathItemSet<'tree> {
+    let b_node_ids: HashSet<NodeId> = tree_node_ids(&b);
+
+    let filtered: XpathItemSet<'tree> = a
+        .into_iter()
+        .filter(|item| match item {
+            XpathItem::Node(Node::TreeNode(node)) => b_node_ids.contains(&node.id()),
+            other => b.iter().any(|b_item| b_item == other),
+        })
+        .collect();
+
+    sort_in_document_order(tree, filtered)
+}
+
+/// Keep only the nodes in `a` that are not also present in `b`.
+fn except_node_sets<'tree>(
+    tree: &'tree XpathItemTree,
+    a: XpathItemSet<'tree>,
+    b: XpathItemSet<'tree>,
+) -> XpathItemSet<'tree> {
+    let b_node_ids: HashSet<NodeId> = tree_node_ids(&b);
+
+    let filtered: XpathItemSet<'tree> = a
+        .into_iter()
+        .filter(|item| match item {
+            XpathItem::Node(Node::TreeNode(node)) => !b_node_ids.contains(&node.id()),
+            other => !b.iter().any(|b_item| b_item == other),
+        })
+        .collect();
+
+    sort_in_document_order(tree, filtered)
+}
+
+fn tree_node_ids(items: &XpathItemSet) -> HashSet<NodeId> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            XpathItem::Node(Node::TreeNode(node)) => Some(node.id()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Assign each node in `tree` a position by a single pre-order traversal
+/// from the root, so any two nodes can be compared by document order in
+/// constant time afterwards.
+fn document_order_indices(tree: &XpathItemTree) -> HashMap<NodeId, usize> {
+    fn visit(
+        node: XpathItemTreeNode,
+        tree: &XpathItemTree,
+        indices: &mut HashMap<NodeId, usize>,
+        next_index: &mut usize,
+    ) {
+        indices.insert(node.id(), *next_index);
+        *next_index += 1;
+        for child in node.children(tree) {
+            visit(child, tree, indices, next_index);
+        }
+    }
+
+    let mut indices = HashMap::new();
+    let mut next_index = 0;
+    visit(tree.root(), tree, &mut indices, &mut next_index);
+    indices
+}
+
+/// Sort a sequence of items into document order, removing duplicate tree
+/// nodes (by [`NodeId`]) and duplicate non-tree items (by equality).
+/// Non-tree items (attribute/namespace nodes) have no document-order
+/// position of their own, so they're kept in encounter order after all tree
+/// nodes.
+fn sort_in_document_order<'tree>(
+    tree: &'tree XpathItemTree,
+    items: XpathItemSet<'tree>,
+) -> XpathItemSet<'tree> {
+    let order = document_order_indices(tree);
+
+    let mut seen_node_ids = HashSet::new();
+    let mut tree_items = Vec::new();
+    let mut other_items: Vec<XpathItem<'tree>> = Vec::new();
+
+    for item in items {
+        match &item {
+            XpathItem::Node(Node::TreeNode(node)) => {
+                if seen_node_ids.insert(node.id()) {
+                    tree_items.push(item);
+                }
+            }
+            _ => {
+                if !other_items.contains(&item) {
+                    other_items.push(item);
+                }
+            }
+        }
+    }
+
+    tree_items.sort_by_key(|item| match item {
+        XpathItem::Node(Node::TreeNode(node)) => *order.get(&node.id()).unwrap_or(&usize::MAX),
+        _ => usize::MAX,
+    });
+
+    tree_items.into_iter().chain(other_items).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{html, xpath};
+
+    #[test]
+    fn union_combines_and_dedupes_nodes_in_document_order() {
+        // arrange
+        let document = html::parse("<html><div>a</div><span>b</span><div>c</div></html>").unwrap();
+        let tree = xpath::XpathItemTree::from(&document);
+        let expr = xpath::parse("//div | //span | //div").unwrap();
+
+        // act
+        let items = expr.apply(&tree).unwrap();
+
+        // assert
+        assert_eq!(items.len(), 3);
+    }
+
+    #[test]
+    fn intersect_keeps_only_nodes_present_in_both_operands() {
+        // arrange
+        let document =
+            html::parse("<html><div class=\"a\">x</div><div class=\"b\">y</div></html>").unwrap();
+        let tree = xpath::XpathItemTree::from(&document);
+        let expr = xpath::parse("//div intersect //div[@class='a']").unwrap();
+
+        // act
+        let items = expr.apply(&tree).unwrap();
+
+        // assert
+        assert_eq!(items.len(), 1);
+    }
+
+    #[test]
+    fn except_removes_nodes_present_in_the_right_operand() {
+        // arrange
+        let document =
+            html::parse("<html><div class=\"a\">x</div><div class=\"b\">y</div></html>").unwrap();
+        let tree = xpath::XpathItemTree::from(&document);
+        let expr = xpath::parse("//div except //div[@class='a']").unwrap();
+
+        // act
+        let items = expr.apply(&tree).unwrap();
+
+        // assert
+        assert_eq!(items.len(), 1);
+    }
+}
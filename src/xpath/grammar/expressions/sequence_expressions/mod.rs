@@ -0,0 +1,3 @@
+//! https://www.w3.org/TR/2017/REC-xpath-31-20170321/#id-sequence-types-types
+
+pub mod combining_node_sequences;
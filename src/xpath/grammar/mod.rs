@@ -6,12 +6,16 @@
 
 pub mod data_model;
 mod expressions;
+pub mod mutate;
 mod recipes;
+pub mod serialize;
 mod terminal_symbols;
 mod types;
 mod xml_names;
 
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::ops::Range;
 
 use enum_extract_macro::EnumExtract;
 pub(crate) use expressions::xpath;
@@ -147,6 +151,83 @@ impl<'a> XpathItemTreeNode<'a> {
         self.text_internal(tree, false)
     }
 
+    /// Get the namespace bindings in scope at this node, for the XPath
+    /// `namespace::` axis.
+    ///
+    /// This always includes the permanently-bound `xml` prefix, plus every
+    /// `xmlns`/`xmlns:prefix` declaration on this element and its ancestors
+    /// (closer declarations shadow farther ones).
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - The tree that this node is a part of.
+    pub fn in_scope_namespaces(&self, tree: &'a XpathItemTree) -> Vec<NamespaceNode> {
+        let mut bindings: HashMap<String, String> = HashMap::new();
+        bindings.insert(String::from("xml"), XML_NAMESPACE_URI.to_string());
+
+        // Walk from the root down to `self` so closer declarations overwrite
+        // the ones inherited from farther ancestors.
+        let mut ancestors = Vec::new();
+        let mut current = Some(self.clone());
+        while let Some(node) = current {
+            ancestors.push(node.clone());
+            current = node.parent(tree);
+        }
+
+        for ancestor in ancestors.into_iter().rev() {
+            if let XpathItemTreeNodeData::ElementNode(element) = ancestor.data {
+                for attribute in &element.attributes {
+                    if attribute.name == "xmlns" {
+                        bindings.insert(String::new(), attribute.value.clone());
+                    } else if let Some(prefix) = attribute.name.strip_prefix("xmlns:") {
+                        bindings.insert(prefix.to_string(), attribute.value.clone());
+                    }
+                }
+            }
+        }
+
+        bindings
+            .into_iter()
+            .map(|(prefix, namespace_uri)| NamespaceNode { prefix, namespace_uri })
+            .collect()
+    }
+
+    /// Get the byte range of this node's source markup, if the tree it
+    /// belongs to was built with source text.
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - The tree that this node is a part of.
+    ///
+    /// # Returns
+    ///
+    /// The byte offsets into the original source text spanned by this node,
+    /// or `None` if the tree was built without a source (e.g. programmatically
+    /// via [`crate::html::grammar::document_builder::DocumentBuilder`]).
+    pub fn span(&self, tree: &'a XpathItemTree) -> Option<Range<usize>> {
+        tree.spans.get(&self.id).cloned()
+    }
+
+    /// Get the 1-based line/column position of the start of this node's span.
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - The tree that this node is a part of.
+    ///
+    /// # Returns
+    ///
+    /// The [`TextPos`] of the start of this node, or `None` if no span is
+    /// recorded for this node.
+    pub fn text_pos(&self, tree: &'a XpathItemTree) -> Option<TextPos> {
+        let span = self.span(tree)?;
+        Some(tree.text_pos(span.start))
+    }
+
+    /// Alias for [`XpathItemTreeNode::span`].
+    pub fn byte_range(&self, tree: &'a XpathItemTree) -> Option<Range<usize>> {
+        self.span(tree)
+    }
+
     fn text_internal(&self, tree: &'a XpathItemTree, recurse: bool) -> String {
         fn get_all_text_nodes(
             tree: &XpathItemTree,
@@ -192,6 +273,42 @@ impl<'a> XpathItemTreeNode<'a> {
     }
 }
 
+/// A 1-based line/column position within a source text, as produced by
+/// [`XpathItemTree::text_pos`].
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct TextPos {
+    /// The 1-based line number.
+    pub line: usize,
+
+    /// The 1-based column number, counted in chars.
+    pub col: usize,
+}
+
+impl Display for TextPos {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.col)
+    }
+}
+
+/// The standards-vs-quirks rendering mode of a parsed HTML document,
+/// computed from its `<!DOCTYPE>`.
+///
+/// <https://html.spec.whatwg.org/multipage/parsing.html#the-initial-insertion-mode>
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum QuirksMode {
+    /// No `<!DOCTYPE>`, or one that doesn't trigger (limited) quirks mode.
+    #[default]
+    NoQuirks,
+
+    /// A `<!DOCTYPE>` that triggers limited quirks mode, e.g. an XHTML 1.0
+    /// transitional/frameset public identifier.
+    LimitedQuirks,
+
+    /// A missing, malformed, or legacy `<!DOCTYPE>` that triggers full
+    /// quirks mode.
+    Quirks,
+}
+
 /// A tree of [`XpathItemTreeNode`]s.
 pub struct XpathItemTree {
     /// The index tree that stores the nodes.
@@ -199,6 +316,27 @@ pub struct XpathItemTree {
 
     /// The root node of the document.
     root_node: NodeId,
+
+    /// The quirks mode this document was parsed in. [`QuirksMode::NoQuirks`]
+    /// for documents that weren't parsed from a `<!DOCTYPE>`-bearing source,
+    /// e.g. ones built via `DocumentBuilder`.
+    quirks_mode: QuirksMode,
+
+    /// The original source text this tree was parsed from, if any.
+    ///
+    /// `None` when the tree was built programmatically (e.g. via
+    /// `DocumentBuilder`) rather than parsed from markup.
+    source_text: Option<String>,
+
+    /// Byte ranges into `source_text` for nodes that have them.
+    ///
+    /// Not every node is guaranteed an entry; nodes created without source
+    /// information (or before this tracking existed) are simply absent.
+    spans: HashMap<NodeId, Range<usize>>,
+
+    /// Byte offset of the start of each line in `source_text`, used to turn a
+    /// byte offset into a line/column pair without rescanning on every call.
+    line_starts: Vec<usize>,
 }
 
 impl XpathItemTree {
@@ -215,9 +353,85 @@ impl XpathItemTree {
         XpathItemTreeNode { id, data }
     }
 
-    fn root(&self) -> XpathItemTreeNode<'_> {
+    pub(crate) fn root(&self) -> XpathItemTreeNode<'_> {
         self.get(self.root_node)
     }
+
+    /// Compute the line-start offsets for a source text, used by
+    /// [`XpathItemTree::text_pos`].
+    fn compute_line_starts(source_text: &str) -> Vec<usize> {
+        let mut line_starts = vec![0];
+        line_starts.extend(
+            source_text
+                .char_indices()
+                .filter(|(_, c)| *c == '\n')
+                .map(|(i, _)| i + 1),
+        );
+        line_starts
+    }
+
+    /// Attach source text and per-node byte spans to this tree.
+    ///
+    /// Intended for parsers that track source positions as they build the
+    /// tree; see [`XpathItemTreeNode::span`] and [`XpathItemTreeNode::text_pos`].
+    pub(crate) fn with_source(
+        mut self,
+        source_text: String,
+        spans: HashMap<NodeId, Range<usize>>,
+    ) -> Self {
+        self.line_starts = Self::compute_line_starts(&source_text);
+        self.source_text = Some(source_text);
+        self.spans = spans;
+        self
+    }
+
+    /// Convert a byte offset into `source_text` to a 1-based line/column.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this tree was not built with source text.
+    pub fn text_pos(&self, offset: usize) -> TextPos {
+        let source_text = self
+            .source_text
+            .as_deref()
+            .expect("text_pos called on a tree with no source text");
+
+        // Binary search for the last line start at or before `offset`.
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        };
+        let line_start = self.line_starts[line];
+        let col = source_text[line_start..offset].chars().count();
+
+        TextPos {
+            line: line + 1,
+            col: col + 1,
+        }
+    }
+
+    /// Alias for [`XpathItemTree::text_pos`].
+    pub fn text_pos_at(&self, offset: usize) -> TextPos {
+        self.text_pos(offset)
+    }
+
+    /// The quirks mode this document was parsed in.
+    pub fn quirks_mode(&self) -> QuirksMode {
+        self.quirks_mode
+    }
+}
+
+/// The namespace URI permanently bound to the `xml` prefix, per
+/// <https://www.w3.org/TR/xml-names/#ns-decl>.
+const XML_NAMESPACE_URI: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Split a tag/attribute name of the form `prefix:local` into its prefix (if
+/// any) and local part.
+fn split_qualified_name(name: &str) -> (Option<&str>, &str) {
+    match name.split_once(':') {
+        Some((prefix, local)) => (Some(prefix), local),
+        None => (None, name),
+    }
 }
 
 impl From<&HtmlDocument> for XpathItemTree {
@@ -226,6 +440,9 @@ impl From<&HtmlDocument> for XpathItemTree {
             current_html_node: &DocumentNode,
             html_document: &HtmlDocument,
             item_arena: &mut Arena<XpathItemTreeNodeData>,
+            // In-scope prefix -> URI bindings inherited from ancestors. The
+            // empty string is the key for the default (no-prefix) namespace.
+            namespaces_in_scope: &HashMap<String, String>,
         ) -> NodeId {
             let html_node = html_document
                 .get_html_node(&current_html_node)
@@ -233,7 +450,7 @@ impl From<&HtmlDocument> for XpathItemTree {
 
             let root_item = match html_node {
                 HtmlNode::Tag(tag) => {
-                    let attributes = tag
+                    let attributes: Vec<AttributeNode> = tag
                         .attributes
                         .iter()
                         .map(|a| AttributeNode {
@@ -241,10 +458,41 @@ impl From<&HtmlDocument> for XpathItemTree {
                             value: a.1.to_string(),
                         })
                         .collect();
-                    XpathItemTreeNodeData::ElementNode(ElementNode {
+
+                    // Collect this element's own `xmlns`/`xmlns:prefix` declarations
+                    // and layer them over the bindings inherited from its ancestors.
+                    let mut own_namespaces = namespaces_in_scope.clone();
+                    for attribute in &attributes {
+                        if attribute.name == "xmlns" {
+                            own_namespaces.insert(String::new(), attribute.value.clone());
+                        } else if let Some(prefix) = attribute.name.strip_prefix("xmlns:") {
+                            own_namespaces.insert(prefix.to_string(), attribute.value.clone());
+                        }
+                    }
+
+                    let (prefix, local_name) = split_qualified_name(&tag.name);
+                    let namespace_uri = match prefix {
+                        Some("xml") => Some(XML_NAMESPACE_URI.to_string()),
+                        Some(prefix) => own_namespaces.get(prefix).cloned(),
+                        None => own_namespaces.get("").cloned(),
+                    };
+
+                    let element = ElementNode {
                         name: tag.name.to_string(),
+                        local_name: local_name.to_string(),
+                        namespace_uri,
                         attributes,
-                    })
+                    };
+
+                    let element_id = item_arena.new_node(XpathItemTreeNodeData::ElementNode(element));
+
+                    for child in current_html_node.children(&html_document) {
+                        let child_node =
+                            internal_from(&child, html_document, item_arena, &own_namespaces);
+                        element_id.append(child_node, item_arena);
+                    }
+
+                    return element_id;
                 }
                 HtmlNode::Text(text) => XpathItemTreeNodeData::TextNode(TextNode {
                     content: text.value.to_string(),
@@ -255,7 +503,8 @@ impl From<&HtmlDocument> for XpathItemTree {
             let root_item_id = item_arena.new_node(root_item);
 
             for child in current_html_node.children(&html_document) {
-                let child_node = internal_from(&child, html_document, item_arena);
+                let child_node =
+                    internal_from(&child, html_document, item_arena, namespaces_in_scope);
                 root_item_id.append(child_node, item_arena);
             }
 
@@ -265,12 +514,25 @@ impl From<&HtmlDocument> for XpathItemTree {
         let mut item_arena = Arena::<XpathItemTreeNodeData>::new();
         let root_node_id =
             item_arena.new_node(XpathItemTreeNodeData::DocumentNode(XpathDocumentNode {}));
-        let first_child = internal_from(&html_document.root_node, &html_document, &mut item_arena);
+        let first_child = internal_from(
+            &html_document.root_node,
+            &html_document,
+            &mut item_arena,
+            &HashMap::new(),
+        );
         root_node_id.append(first_child, &mut item_arena);
 
+        // The `HtmlDocument` -> `XpathItemTree` conversion has no access to the
+        // original source text, so this tree carries no spans. Parsers that do
+        // have the source text should build the tree via a path that calls
+        // `with_source` instead.
         XpathItemTree {
             arena: item_arena,
             root_node: root_node_id,
+            quirks_mode: QuirksMode::NoQuirks,
+            source_text: None,
+            spans: HashMap::new(),
+            line_starts: Vec::new(),
         }
     }
 }
@@ -0,0 +1,236 @@
+//! Mutation API for [`XpathItemTree`].
+//!
+//! The read-only traversal methods on [`XpathItemTreeNode`] borrow the tree
+//! they came from, which rules out taking `&mut XpathItemTree` alongside
+//! them. Mutation therefore goes through [`NodeId`] handles instead: get one
+//! from [`XpathItemTreeNode::id`], then call these methods on the tree.
+
+use indextree::NodeId;
+
+use super::{
+    data_model::{AttributeNode, ElementNode, TextNode},
+    XpathItemTree, XpathItemTreeNode, XpathItemTreeNodeData,
+};
+
+impl<'a> XpathItemTreeNode<'a> {
+    /// The arena-internal identifier of this node, for use with the
+    /// mutation methods on [`XpathItemTree`].
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+}
+
+impl XpathItemTree {
+    /// Append a new element node as the last child of `parent`.
+    pub fn append_element_child(&mut self, parent: NodeId, name: impl Into<String>) -> NodeId {
+        let name = name.into();
+        let element = ElementNode {
+            name: name.clone(),
+            local_name: name,
+            namespace_uri: None,
+            attributes: Vec::new(),
+        };
+        let child = self
+            .arena
+            .new_node(XpathItemTreeNodeData::ElementNode(element));
+        parent.append(child, &mut self.arena);
+        child
+    }
+
+    /// Append a new text node as the last child of `parent`.
+    pub fn append_text_child(&mut self, parent: NodeId, content: impl Into<String>) -> NodeId {
+        let content = content.into();
+        let text = TextNode {
+            only_whitespace: content.trim().is_empty(),
+            content,
+        };
+        let child = self.arena.new_node(XpathItemTreeNodeData::TextNode(text));
+        parent.append(child, &mut self.arena);
+        child
+    }
+
+    /// Detach `node`, and everything below it, from the tree entirely.
+    pub fn remove_node(&mut self, node: NodeId) {
+        node.remove_subtree(&mut self.arena);
+    }
+
+    /// Detach `node` (and its descendants) from its current parent without
+    /// dropping it, so it can be moved elsewhere with
+    /// [`XpathItemTree::append_existing_child`].
+    pub fn detach(&mut self, node: NodeId) {
+        node.detach(&mut self.arena);
+    }
+
+    /// Reattach a previously-detached node as the last child of `parent`.
+    pub fn append_existing_child(&mut self, parent: NodeId, node: NodeId) {
+        parent.append(node, &mut self.arena);
+    }
+
+    /// Reattach a previously-detached node as the first child of `parent`.
+    pub fn prepend_existing_child(&mut self, parent: NodeId, node: NodeId) {
+        parent.prepend(node, &mut self.arena);
+    }
+
+    /// Insert `node` as the immediately preceding sibling of `before`.
+    pub fn insert_before(&mut self, before: NodeId, node: NodeId) {
+        before.insert_before(node, &mut self.arena);
+    }
+
+    /// Insert `node` as the immediately following sibling of `after`.
+    pub fn insert_after(&mut self, after: NodeId, node: NodeId) {
+        after.insert_after(node, &mut self.arena);
+    }
+
+    /// Detach `node` and everything below it, putting `replacement` in its
+    /// former position among its siblings.
+    pub fn replace_with(&mut self, node: NodeId, replacement: NodeId) {
+        node.insert_before(replacement, &mut self.arena);
+        node.remove_subtree(&mut self.arena);
+    }
+
+    /// Replace the text content of a text node in place.
+    ///
+    /// Returns `false`, and does nothing, if `node` is not a
+    /// [`XpathItemTreeNodeData::TextNode`].
+    pub fn set_text(&mut self, node: NodeId, content: impl Into<String>) -> bool {
+        match self.arena.get_mut(node).map(|n| n.get_mut()) {
+            Some(XpathItemTreeNodeData::TextNode(text)) => {
+                text.content = content.into();
+                text.only_whitespace = text.content.trim().is_empty();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Set an attribute on an element node, inserting it if it isn't already
+    /// present or overwriting its value if it is.
+    ///
+    /// Returns `false`, and does nothing, if `node` is not an
+    /// [`XpathItemTreeNodeData::ElementNode`].
+    pub fn set_attribute(&mut self, node: NodeId, name: &str, value: impl Into<String>) -> bool {
+        match self.arena.get_mut(node).map(|n| n.get_mut()) {
+            Some(XpathItemTreeNodeData::ElementNode(element)) => {
+                let value = value.into();
+                match element.attributes.iter_mut().find(|a| a.name == name) {
+                    Some(attribute) => attribute.value = value,
+                    None => element.attributes.push(AttributeNode {
+                        name: name.to_string(),
+                        value,
+                    }),
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove an attribute from an element node.
+    ///
+    /// Returns whether the attribute was present.
+    pub fn remove_attribute(&mut self, node: NodeId, name: &str) -> bool {
+        match self.arena.get_mut(node).map(|n| n.get_mut()) {
+            Some(XpathItemTreeNodeData::ElementNode(element)) => {
+                let before = element.attributes.len();
+                element.attributes.retain(|a| a.name != name);
+                element.attributes.len() != before
+            }
+            _ => false,
+        }
+    }
+
+    /// Rename an attribute in place, preserving its value and position among
+    /// the element's other attributes.
+    ///
+    /// Returns `false`, and does nothing, if `node` has no attribute named
+    /// `from`.
+    pub fn rename_attribute(&mut self, node: NodeId, from: &str, to: &str) -> bool {
+        match self.arena.get_mut(node).map(|n| n.get_mut()) {
+            Some(XpathItemTreeNodeData::ElementNode(element)) => {
+                match element.attributes.iter_mut().find(|a| a.name == from) {
+                    Some(attribute) => {
+                        attribute.name = to.to_string();
+                        true
+                    }
+                    None => false,
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::html;
+
+    use super::*;
+
+    fn tree_from(html: &str) -> XpathItemTree {
+        let document = html::parse(html).unwrap();
+        XpathItemTree::from(&document)
+    }
+
+    #[test]
+    fn append_and_detach_move_a_node_between_parents() {
+        // arrange
+        let mut tree = tree_from("<html><div id=\"a\"></div><div id=\"b\"></div></html>");
+        let html_id = tree.root().children(&tree).next().unwrap().id();
+        let a_id = html_id.children(&tree.arena).next().unwrap();
+        let b_id = html_id.children(&tree.arena).nth(1).unwrap();
+        let text_id = tree.append_text_child(a_id, "moved");
+
+        // act
+        tree.detach(text_id);
+        tree.append_existing_child(b_id, text_id);
+
+        // assert
+        assert!(a_id.children(&tree.arena).next().is_none());
+        assert_eq!(b_id.children(&tree.arena).next(), Some(text_id));
+    }
+
+    fn class_attribute(tree: &XpathItemTree, node: NodeId) -> Option<String> {
+        match tree.get(node).data {
+            XpathItemTreeNodeData::ElementNode(element) => element
+                .attributes
+                .iter()
+                .find(|a| a.name == "class")
+                .map(|a| a.value.clone()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn set_attribute_inserts_then_overwrites() {
+        // arrange
+        let mut tree = tree_from("<html><div></div></html>");
+        let html_id = tree.root().children(&tree).next().unwrap().id();
+        let div_id = html_id.children(&tree.arena).next().unwrap();
+
+        // act
+        tree.set_attribute(div_id, "class", "one");
+        let inserted = class_attribute(&tree, div_id);
+        tree.set_attribute(div_id, "class", "two");
+        let overwritten = class_attribute(&tree, div_id);
+
+        // assert
+        assert_eq!(inserted, Some("one".to_string()));
+        assert_eq!(overwritten, Some("two".to_string()));
+    }
+
+    #[test]
+    fn remove_attribute_reports_whether_it_was_present() {
+        // arrange
+        let mut tree = tree_from("<html><div class=\"here\"></div></html>");
+        let html_id = tree.root().children(&tree).next().unwrap().id();
+        let div_id = html_id.children(&tree.arena).next().unwrap();
+
+        // act
+        let first = tree.remove_attribute(div_id, "class");
+        let second = tree.remove_attribute(div_id, "class");
+
+        // assert
+        assert!(first);
+        assert!(!second);
+    }
+}
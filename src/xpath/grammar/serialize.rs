@@ -0,0 +1,379 @@
+//! Serialize an [`XpathItemTree`] back into HTML/XML markup.
+//!
+//! This is the inverse of parsing: walk the tree and write elements,
+//! attributes, text (escaped), and comments back out as markup, re-emitting
+//! `xmlns`/`xmlns:prefix` declarations only where a namespace first comes
+//! into scope.
+
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+
+use super::{XpathItemTree, XpathItemTreeNode, XpathItemTreeNodeData};
+
+/// The set of elements that never have children and are written without a
+/// closing tag in [`SerializeMode::Html`].
+const HTML_VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Elements whose text content is written out verbatim, without `&`/`<`/`>`
+/// escaping, because the HTML parser never interprets markup inside them.
+const HTML_RAW_TEXT_ELEMENTS: &[&str] = &[
+    "script",
+    "style",
+    "xmp",
+    "iframe",
+    "noembed",
+    "noframes",
+    "plaintext",
+    "textarea",
+    "title",
+];
+
+/// Controls how elements without children are closed.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum SerializeMode {
+    /// HTML void elements (`<br>`, `<img>`, ...) are written without a closing
+    /// tag or self-closing slash.
+    Html,
+
+    /// Every element is always explicitly closed, e.g. `<br></br>` or, when
+    /// it has no children, `<br/>`.
+    Xml,
+}
+
+/// Options controlling [`XpathItemTree::serialize`].
+#[derive(Debug, Clone)]
+pub struct SerializeOptions {
+    /// Whether to treat this document as HTML or XML when closing elements.
+    pub mode: SerializeMode,
+
+    /// If `Some`, indent children by this many spaces per nesting level and
+    /// place each element on its own line. If `None`, markup is written
+    /// without added whitespace.
+    pub indent: Option<usize>,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        Self {
+            mode: SerializeMode::Html,
+            indent: None,
+        }
+    }
+}
+
+impl XpathItemTree {
+    /// Serialize this tree back into markup, writing into `out` instead of
+    /// allocating a `String`.
+    pub fn serialize_to(&self, options: &SerializeOptions, out: &mut impl Write) -> fmt::Result {
+        let mut namespaces_in_scope: HashMap<String, String> = HashMap::new();
+
+        for child in self.root().children(self) {
+            serialize_node(
+                &child,
+                self,
+                options,
+                0,
+                &mut namespaces_in_scope,
+                false,
+                out,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Serialize this tree back into markup.
+    pub fn serialize(&self, options: &SerializeOptions) -> String {
+        let mut out = String::new();
+        self.serialize_to(options, &mut out)
+            .expect("writing to a String can't fail");
+        out
+    }
+
+    /// Serialize this tree back into an HTML string, using
+    /// [`SerializeOptions::default`].
+    ///
+    /// Shorthand for `tree.serialize(&SerializeOptions::default())`, e.g. to
+    /// re-emit a document after editing it with the
+    /// [`crate::xpath::grammar::mutate`] API.
+    pub fn to_html_string(&self) -> String {
+        self.serialize(&SerializeOptions::default())
+    }
+
+    /// Serialize this tree back into an XML string, i.e. every element
+    /// explicitly closed rather than relying on HTML's void-element list.
+    ///
+    /// Shorthand for `tree.serialize(&SerializeOptions { mode: SerializeMode::Xml, ..Default::default() })`.
+    pub fn to_xml_string(&self) -> String {
+        self.serialize(&SerializeOptions {
+            mode: SerializeMode::Xml,
+            ..Default::default()
+        })
+    }
+}
+
+impl<'a> XpathItemTreeNode<'a> {
+    /// Serialize this node and its descendants back into markup, i.e.
+    /// `outerHTML`.
+    pub fn serialize_outer(&self, tree: &'a XpathItemTree, options: &SerializeOptions) -> String {
+        let mut out = String::new();
+        let mut namespaces_in_scope = self.in_scope_namespaces(tree).into_iter().fold(
+            HashMap::new(),
+            |mut map, namespace| {
+                map.insert(namespace.prefix, namespace.namespace_uri);
+                map
+            },
+        );
+
+        serialize_node(
+            self,
+            tree,
+            options,
+            0,
+            &mut namespaces_in_scope,
+            false,
+            &mut out,
+        )
+        .expect("writing to a String can't fail");
+
+        out
+    }
+
+    /// Serialize only this node's children back into markup, i.e.
+    /// `innerHTML`.
+    pub fn serialize_inner(&self, tree: &'a XpathItemTree, options: &SerializeOptions) -> String {
+        let mut out = String::new();
+        let mut namespaces_in_scope = self.in_scope_namespaces(tree).into_iter().fold(
+            HashMap::new(),
+            |mut map, namespace| {
+                map.insert(namespace.prefix, namespace.namespace_uri);
+                map
+            },
+        );
+        let in_raw_text_element = matches!(
+            self.data,
+            XpathItemTreeNodeData::ElementNode(element)
+                if HTML_RAW_TEXT_ELEMENTS.contains(&element.name.as_str())
+        );
+
+        for child in self.children(tree) {
+            serialize_node(
+                &child,
+                tree,
+                options,
+                0,
+                &mut namespaces_in_scope,
+                in_raw_text_element,
+                &mut out,
+            )
+            .expect("writing to a String can't fail");
+        }
+
+        out
+    }
+}
+
+fn write_indent(out: &mut impl Write, options: &SerializeOptions, depth: usize) -> fmt::Result {
+    if let Some(indent) = options.indent {
+        write!(out, "{}", " ".repeat(indent * depth))?;
+    }
+
+    Ok(())
+}
+
+fn write_newline_if_pretty(out: &mut impl Write, options: &SerializeOptions) -> fmt::Result {
+    if options.indent.is_some() {
+        out.write_char('\n')?;
+    }
+
+    Ok(())
+}
+
+fn serialize_node(
+    node: &XpathItemTreeNode,
+    tree: &XpathItemTree,
+    options: &SerializeOptions,
+    depth: usize,
+    namespaces_in_scope: &mut HashMap<String, String>,
+    in_raw_text_element: bool,
+    out: &mut impl Write,
+) -> fmt::Result {
+    match node.data {
+        XpathItemTreeNodeData::ElementNode(element) => {
+            write_indent(out, options, depth)?;
+
+            write!(out, "<{}", element.name)?;
+
+            // Emit a namespace declaration only if this element introduces a
+            // binding that isn't already in scope from an ancestor.
+            let mut child_namespaces = namespaces_in_scope.clone();
+            if let Some(uri) = &element.namespace_uri {
+                let prefix = String::new();
+                if namespaces_in_scope.get(&prefix) != Some(uri) {
+                    // An element's own `xmlns` attribute is written by the
+                    // attribute loop below; don't declare it a second time.
+                    if !element.attributes.iter().any(|a| a.name == "xmlns") {
+                        write!(out, " xmlns=\"{}\"", escape_attribute(uri))?;
+                    }
+                    child_namespaces.insert(prefix, uri.clone());
+                }
+            }
+
+            for attribute in &element.attributes {
+                write!(
+                    out,
+                    " {}=\"{}\"",
+                    attribute.name,
+                    escape_attribute(&attribute.value)
+                )?;
+            }
+
+            let children: Vec<_> = node.children(tree).collect();
+            let is_void = options.mode == SerializeMode::Html
+                && HTML_VOID_ELEMENTS.contains(&element.name.as_str());
+            let child_in_raw_text_element = options.mode == SerializeMode::Html
+                && HTML_RAW_TEXT_ELEMENTS.contains(&element.name.as_str());
+
+            if children.is_empty() && is_void {
+                out.write_char('>')?;
+            } else if children.is_empty() && options.mode == SerializeMode::Xml {
+                out.write_str("/>")?;
+            } else {
+                out.write_char('>')?;
+                write_newline_if_pretty(out, options)?;
+
+                for child in &children {
+                    serialize_node(
+                        child,
+                        tree,
+                        options,
+                        depth + 1,
+                        &mut child_namespaces,
+                        child_in_raw_text_element,
+                        out,
+                    )?;
+                }
+
+                write_indent(out, options, depth)?;
+                write!(out, "</{}>", element.name)?;
+            }
+
+            write_newline_if_pretty(out, options)?;
+        }
+        XpathItemTreeNodeData::TextNode(text) => {
+            write_indent(out, options, depth)?;
+
+            if in_raw_text_element {
+                out.write_str(&text.content)?;
+            } else {
+                out.write_str(&escape_text(&text.content))?;
+            }
+
+            write_newline_if_pretty(out, options)?;
+        }
+        XpathItemTreeNodeData::CommentNode(comment) => {
+            write_indent(out, options, depth)?;
+            write!(out, "<!--{}-->", comment.content)?;
+            write_newline_if_pretty(out, options)?;
+        }
+        XpathItemTreeNodeData::PINode(pi) => {
+            write_indent(out, options, depth)?;
+
+            write!(out, "<?{}", pi.target)?;
+            if !pi.content.is_empty() {
+                write!(out, " {}", pi.content)?;
+            }
+            out.write_str("?>")?;
+
+            write_newline_if_pretty(out, options)?;
+        }
+        XpathItemTreeNodeData::DocumentNode(_) => {
+            for child in node.children(tree) {
+                serialize_node(
+                    &child,
+                    tree,
+                    options,
+                    depth,
+                    namespaces_in_scope,
+                    in_raw_text_element,
+                    out,
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Escape text content: the inverse of the existing character-reference
+/// unescape logic used by the parser.
+fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Escape an attribute value, additionally escaping `"` since attribute
+/// values are always written double-quoted.
+fn escape_attribute(value: &str) -> String {
+    escape_text(value).replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::html;
+
+    use super::*;
+
+    fn tree_from(html: &str) -> XpathItemTree {
+        let document = html::parse(html).unwrap();
+        XpathItemTree::from(&document)
+    }
+
+    #[test]
+    fn to_html_string_omits_void_element_closing_tags() {
+        // arrange
+        let tree = tree_from(r#"<html><img src="a.png"><p>text &amp; more</p></html>"#);
+
+        // act
+        let out = tree.to_html_string();
+
+        // assert
+        assert!(out.contains(r#"<img src="a.png">"#));
+        assert!(!out.contains("</img>"));
+        assert!(out.contains("<p>text &amp; more</p>"));
+    }
+
+    #[test]
+    fn to_xml_string_always_closes_elements() {
+        // arrange
+        let tree = tree_from(r#"<html><br></html>"#);
+
+        // act
+        let out = tree.to_xml_string();
+
+        // assert
+        assert!(out.contains("<br/>") || out.contains("<br></br>"));
+    }
+
+    #[test]
+    fn serialize_outer_includes_the_node_itself_serialize_inner_does_not() {
+        // arrange
+        let tree = tree_from("<html><div><span>a</span></div></html>");
+        let html_node = tree.root().children(&tree).next().unwrap();
+        let div_node = html_node.children(&tree).next().unwrap();
+        let options = SerializeOptions::default();
+
+        // act
+        let outer = div_node.serialize_outer(&tree, &options);
+        let inner = div_node.serialize_inner(&tree, &options);
+
+        // assert
+        assert!(outer.contains("<div>"));
+        assert!(!inner.contains("<div>"));
+        assert!(inner.contains("<span>a</span>"));
+    }
+}
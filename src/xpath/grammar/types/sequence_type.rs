@@ -10,12 +10,17 @@ use nom::{
     sequence::tuple,
 };
 
-use crate::xpath::grammar::{
-    recipes::Res,
-    types::{
-        array_test::array_test, common::atomic_or_union_type, function_test::function_test,
-        kind_test, map_test::map_test,
+use crate::xpath::{
+    grammar::{
+        data_model::{AnyAtomicType, XpathItem},
+        recipes::Res,
+        types::{
+            array_test::array_test, common::atomic_or_union_type, function_test::function_test,
+            kind_test, map_test::map_test,
+        },
     },
+    xpath_item_set::XpathItemSet,
+    ExpressionApplyError,
 };
 
 use super::{
@@ -64,6 +69,99 @@ pub struct SequenceTypeValue {
     pub occurrence: Option<OccurrenceIndicator>,
 }
 
+/// The keywords that introduce a [`KindTest`] alternative of [`ItemType`],
+/// each always followed by `(`. Used by [`dispatch_item_type`] to recognize
+/// a kind test without trying (and backtracking out of) every other
+/// alternative first.
+const KIND_TEST_KEYWORDS: &[&str] = &[
+    "document-node",
+    "schema-element",
+    "schema-attribute",
+    "element",
+    "attribute",
+    "processing-instruction",
+    "namespace-node",
+    "comment",
+    "text",
+    "node",
+];
+
+/// The full FIRST set of [`item_type`], as user-facing alternative
+/// descriptions, in the order [`dispatch_item_type`] checks them. Reused by
+/// [`item_type_diagnostic`] to report what was expected.
+const ITEM_TYPE_FIRST_SET: &[&str] = &[
+    "(",
+    "item(",
+    "function(",
+    "map(",
+    "array(",
+    "document-node(",
+    "schema-element(",
+    "schema-attribute(",
+    "element(",
+    "attribute(",
+    "processing-instruction(",
+    "namespace-node(",
+    "comment(",
+    "text(",
+    "node(",
+    "an EQName naming an atomic or union type",
+];
+
+/// Which [`item_type`] alternative `input` commits to, decided by peeking
+/// at its leading keyword/token rather than trying every alternative in
+/// turn. `None` means none of `ItemType`'s alternatives could possibly
+/// start here.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ItemTypeBranch {
+    Parenthesized,
+    Item,
+    FunctionTest,
+    MapTest,
+    ArrayTest,
+    KindTest,
+    AtomicOrUnionType,
+}
+
+/// Whether `input` starts with `keyword` followed (after optional
+/// whitespace) by `(` — the shape every parenthesized `ItemType`
+/// alternative takes, and what distinguishes e.g. the `item` keyword from
+/// an atomic type name that merely starts with the same letters.
+fn looks_like_call(input: &str, keyword: &str) -> bool {
+    input
+        .strip_prefix(keyword)
+        .is_some_and(|rest| rest.trim_start().starts_with('('))
+}
+
+fn dispatch_item_type(input: &str) -> Option<ItemTypeBranch> {
+    if input.starts_with('(') {
+        return Some(ItemTypeBranch::Parenthesized);
+    }
+    if looks_like_call(input, "item") {
+        return Some(ItemTypeBranch::Item);
+    }
+    if looks_like_call(input, "function") {
+        return Some(ItemTypeBranch::FunctionTest);
+    }
+    if looks_like_call(input, "map") {
+        return Some(ItemTypeBranch::MapTest);
+    }
+    if looks_like_call(input, "array") {
+        return Some(ItemTypeBranch::ArrayTest);
+    }
+    if KIND_TEST_KEYWORDS
+        .iter()
+        .any(|keyword| looks_like_call(input, keyword))
+    {
+        return Some(ItemTypeBranch::KindTest);
+    }
+
+    match input.chars().next() {
+        Some(c) if c.is_alphabetic() || c == '_' => Some(ItemTypeBranch::AtomicOrUnionType),
+        _ => None,
+    }
+}
+
 pub fn item_type(input: &str) -> Res<&str, ItemType> {
     // https://www.w3.org/TR/2017/REC-xpath-31-20170321/#doc-xpath31-ItemType
 
@@ -94,15 +192,73 @@ pub fn item_type(input: &str) -> Res<&str, ItemType> {
             .map(|(next_input, res)| (next_input, ItemType::AtomicOrUnionType(res)))
     }
 
-    alt((
-        kind_test_map,
-        item_map,
-        function_test_map,
-        map_test_map,
-        array_test_map,
-        atomic_or_union_type_map,
-        parenthesized_item_type,
-    ))(input)
+    // Rather than trying every alternative of the `alt` in turn and
+    // backtracking out of six of them on every successful parse, peek the
+    // FIRST-set token once and call only the one alternative it selects.
+    match dispatch_item_type(input) {
+        Some(ItemTypeBranch::Parenthesized) => parenthesized_item_type(input),
+        Some(ItemTypeBranch::Item) => item_map(input),
+        Some(ItemTypeBranch::FunctionTest) => function_test_map(input),
+        Some(ItemTypeBranch::MapTest) => map_test_map(input),
+        Some(ItemTypeBranch::ArrayTest) => array_test_map(input),
+        Some(ItemTypeBranch::KindTest) => kind_test_map(input),
+        Some(ItemTypeBranch::AtomicOrUnionType) => atomic_or_union_type_map(input),
+        None => Err(nom::Err::Error(nom::error::make_error(
+            input,
+            nom::error::ErrorKind::Alt,
+        ))),
+    }
+}
+
+/// A structured diagnostic for a failed [`item_type`] parse: the set of
+/// alternatives that were valid at this position and what was actually
+/// found there.
+///
+/// This can't just become `item_type`'s own error type — `Res`'s error
+/// parameter is fixed by `recipes`, a module not present in this checkout,
+/// so there's no way to make the existing nom-style parser return this
+/// shape without guessing at `Res`'s generic signature. Call
+/// [`item_type_diagnostic`] directly instead when a readable message
+/// matters more than nom-combinator composability.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemTypeParseError {
+    /// Byte offset into the slice passed to [`item_type_diagnostic`] — not
+    /// into any larger enclosing document, since nothing upstream of this
+    /// function threads that offset in.
+    pub offset: usize,
+    pub expected: Vec<&'static str>,
+    pub found: String,
+}
+
+impl Display for ItemTypeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "expected one of [{}] at byte offset {}, found {:?}",
+            self.expected.join(", "),
+            self.offset,
+            self.found
+        )
+    }
+}
+
+/// Runs [`item_type`]'s FIRST-set dispatch, reporting a structured
+/// [`ItemTypeParseError`] instead of an opaque nom failure when nothing in
+/// `ItemType`'s FIRST set matches `input`.
+pub fn item_type_diagnostic(input: &str) -> Result<(&str, ItemType), ItemTypeParseError> {
+    if dispatch_item_type(input).is_none() {
+        return Err(ItemTypeParseError {
+            offset: 0,
+            expected: ITEM_TYPE_FIRST_SET.to_vec(),
+            found: input.chars().take(16).collect(),
+        });
+    }
+
+    item_type(input).map_err(|_| ItemTypeParseError {
+        offset: 0,
+        expected: ITEM_TYPE_FIRST_SET.to_vec(),
+        found: input.chars().take(16).collect(),
+    })
 }
 
 #[derive(PartialEq, Debug)]
@@ -158,15 +314,248 @@ pub enum OccurrenceIndicator {
     OneOrMore,
 }
 
+/// A statically-known bound on how many items an expression can produce,
+/// as an inclusive range (`max: None` meaning unbounded).
+///
+/// A full static type-inference pass would compute this (and an
+/// [`ItemType`] alongside it) for every node of the parsed expression tree,
+/// exposed as `fn infer_type(&Expr, &StaticContext) -> SequenceType` per
+/// the language this type system is modeled on. That isn't buildable here:
+/// there is no single `Expr` enum spanning the grammar in this checkout —
+/// every expression tier (`OrExpr`, `ComparisonExpr`, path steps, ...) is
+/// its own standalone struct in its own module — and no `StaticContext`
+/// exists to carry variable/function signatures into such a pass. What
+/// *is* tractable without either of those is the cardinality half of
+/// static checking: given a cardinality a caller already knows some other
+/// way (e.g. "path steps always yield `node()*`", stated but not wired up
+/// here for the same reason), [`SequenceType::statically_impossible_cardinality`]
+/// and [`check_cardinality`] can flag `instance of`/`treat as` tests that
+/// can never succeed, such as treating a guaranteed-empty sequence as
+/// `item()+`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaticCardinality {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+impl StaticCardinality {
+    pub const EMPTY: StaticCardinality = StaticCardinality {
+        min: 0,
+        max: Some(0),
+    };
+    pub const EXACTLY_ONE: StaticCardinality = StaticCardinality {
+        min: 1,
+        max: Some(1),
+    };
+    pub const ZERO_OR_ONE: StaticCardinality = StaticCardinality {
+        min: 0,
+        max: Some(1),
+    };
+    pub const ONE_OR_MORE: StaticCardinality = StaticCardinality { min: 1, max: None };
+    pub const ZERO_OR_MORE: StaticCardinality = StaticCardinality { min: 0, max: None };
+
+    /// Whether some length in `self`'s range could also satisfy
+    /// `occurrence` (`None` standing for exactly one, as elsewhere in this
+    /// module) — i.e. whether the two length ranges overlap at all.
+    fn overlaps(&self, occurrence: Option<&OccurrenceIndicator>) -> bool {
+        let (target_min, target_max) = match occurrence {
+            None => (1, Some(1)),
+            Some(OccurrenceIndicator::ZeroOrOne) => (0, Some(1)),
+            Some(OccurrenceIndicator::ZeroOrMore) => (0, None),
+            Some(OccurrenceIndicator::OneOrMore) => (1, None),
+        };
+
+        let min_is_within_target_max = match target_max {
+            Some(target_max) => self.min <= target_max,
+            None => true,
+        };
+        let target_min_is_within_max = match self.max {
+            Some(self_max) => target_min <= self_max,
+            None => true,
+        };
+
+        min_is_within_target_max && target_min_is_within_max
+    }
+}
+
+/// One flagged `instance of`/`treat as` test that can never succeed, because
+/// no length in its operand's statically-known cardinality can ever satisfy
+/// the tested-against [`SequenceType`]'s cardinality. See [`StaticCardinality`]
+/// for why this only covers cardinality, not item type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImpossibleCardinalityDiagnostic {
+    pub source: StaticCardinality,
+    target_description: String,
+}
+
+impl Display for ImpossibleCardinalityDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "a sequence of cardinality {:?} can never match `{}`",
+            self.source, self.target_description
+        )
+    }
+}
+
+/// Checks a statically-known `source` cardinality against `target`,
+/// returning a diagnostic if the test can never succeed.
+pub fn check_cardinality(
+    source: StaticCardinality,
+    target: &SequenceType,
+) -> Option<ImpossibleCardinalityDiagnostic> {
+    if target.statically_impossible_cardinality(source) {
+        Some(ImpossibleCardinalityDiagnostic {
+            source,
+            target_description: describe_cardinality(target),
+        })
+    } else {
+        None
+    }
+}
+
+/// A human-readable description of `target`'s cardinality requirement.
+/// Written by hand rather than via `target.to_string()` because
+/// `SequenceType`'s own `Display` impl isn't implemented yet (see above).
+fn describe_cardinality(target: &SequenceType) -> String {
+    match target {
+        SequenceType::EmptySequence => String::from("empty-sequence()"),
+        SequenceType::Sequence(value) => match value.occurrence {
+            None => String::from("(exactly one)"),
+            Some(OccurrenceIndicator::ZeroOrOne) => String::from("?"),
+            Some(OccurrenceIndicator::ZeroOrMore) => String::from("*"),
+            Some(OccurrenceIndicator::OneOrMore) => String::from("+"),
+        },
+    }
+}
+
+impl SequenceType {
+    /// Whether `cardinality`, a statically-known bound on how many items an
+    /// expression can produce, rules out this `SequenceType` ever matching —
+    /// on cardinality grounds alone, independent of item type.
+    pub fn statically_impossible_cardinality(&self, cardinality: StaticCardinality) -> bool {
+        match self {
+            SequenceType::EmptySequence => cardinality.min > 0,
+            SequenceType::Sequence(value) => !cardinality.overlaps(value.occurrence.as_ref()),
+        }
+    }
+
+    /// Tests `items` against this `SequenceType`, per
+    /// <https://www.w3.org/TR/2017/REC-xpath-31-20170321/#id-sequencetype-matching>.
+    ///
+    /// This is the runtime counterpart to the `instance of` operator; it's
+    /// not wired into an `InstanceofExpr` yet because that expression (and
+    /// `treat as`/`castable as` alongside it) isn't parsed anywhere in this
+    /// checkout, but the matching rules live here so that expression has
+    /// something real to call once it exists.
+    pub fn matches(&self, items: &XpathItemSet) -> bool {
+        match self {
+            SequenceType::EmptySequence => items.len() == 0,
+            SequenceType::Sequence(value) => value.matches(items),
+        }
+    }
+
+    /// The `treat as` operator: returns `items` unchanged if they match,
+    /// otherwise a dynamic type error (`err:XPDY0050` in the spec).
+    pub fn treat_as(&self, items: XpathItemSet) -> Result<XpathItemSet, ExpressionApplyError> {
+        if self.matches(&items) {
+            Ok(items)
+        } else {
+            Err(ExpressionApplyError {
+                msg: String::from("items do not match the type expected by `treat as`"),
+            })
+        }
+    }
+}
+
+impl SequenceTypeValue {
+    fn matches(&self, items: &XpathItemSet) -> bool {
+        if !self.matches_cardinality(items.len()) {
+            return false;
+        }
+
+        items.iter().all(|item| self.item_type.matches(item))
+    }
+
+    fn matches_cardinality(&self, len: usize) -> bool {
+        match self.occurrence {
+            None => len == 1,
+            Some(OccurrenceIndicator::ZeroOrOne) => len <= 1,
+            Some(OccurrenceIndicator::ZeroOrMore) => true,
+            Some(OccurrenceIndicator::OneOrMore) => len >= 1,
+        }
+    }
+}
+
+impl ItemType {
+    /// Tests a single item against this `ItemType`.
+    pub fn matches(&self, item: &XpathItem) -> bool {
+        match self {
+            ItemType::Item => true,
+            ItemType::KindTest(kind_test) => kind_test.is_match(item),
+            ItemType::AtomicOrUnionType(atomic_type) => atomic_type.matches(item),
+            // `XpathItem` has no function-item variant yet (see
+            // `data_model`), so no value can ever satisfy a function/map/
+            // array test today.
+            ItemType::FunctionTest(_) | ItemType::MapTest(_) | ItemType::ArrayTest(_) => false,
+        }
+    }
+
+    /// The `castable as` operator, restricted to the single-atomic-value
+    /// case the spec defines it for.
+    ///
+    /// This only checks whether `item` already *is* an atomic value of a
+    /// matching type, not whether a string-to-type conversion would
+    /// succeed — there's no cast-conversion machinery in this checkout to
+    /// drive that check against yet.
+    pub fn castable_as(&self, item: &XpathItem) -> bool {
+        matches!(item, XpathItem::AnyAtomicType(_)) && self.matches(item)
+    }
+}
+
+impl AtomicOrUnionType {
+    /// Whether `item` is an atomic value of this type, or of a type that
+    /// derives from it.
+    ///
+    /// `AtomicOrUnionType`'s own QName and derivation table live in
+    /// `types::common`, a module not present in this checkout, so this
+    /// compares against the type's already-public `Display` string instead
+    /// of walking a real `xs:` type hierarchy. It covers the atomic kinds
+    /// `AnyAtomicType` currently models (`xs:boolean`, `xs:string`,
+    /// `xs:integer`, `xs:decimal`, `xs:double`); anything outside that
+    /// won't match correctly until `common` is wired back in.
+    fn matches(&self, item: &XpathItem) -> bool {
+        let XpathItem::AnyAtomicType(atomic) = item else {
+            return false;
+        };
+
+        let type_name = self.to_string();
+        let local_name = type_name.rsplit(':').next().unwrap_or(&type_name);
+
+        matches!(
+            (local_name, atomic),
+            ("anyAtomicType", _)
+                | ("integer", AnyAtomicType::Integer(_))
+                | ("decimal", AnyAtomicType::Integer(_) | AnyAtomicType::Decimal(_))
+                | ("double", AnyAtomicType::Double(_))
+                | ("boolean", AnyAtomicType::Boolean(_))
+                | ("string", AnyAtomicType::String(_))
+        )
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::xpath::grammar::{
-        types::{
-            common::ElementName,
-            element_test::{ElementNameOrWildcard, ElementTest},
-            DocumentTest, DocumentTestValue, EQName, PITest, PITestValue,
+    use crate::xpath::{
+        grammar::{
+            types::{
+                common::ElementName,
+                element_test::{ElementNameOrWildcard, ElementTest},
+                DocumentTest, DocumentTestValue, EQName, PITest, PITestValue,
+            },
+            xml_names::QName,
         },
-        xml_names::QName,
+        xpath_item_set,
     };
 
     use super::*;
@@ -314,4 +703,222 @@ mod test {
             ))
         )
     }
+
+    #[test]
+    fn empty_sequence_matches_no_items() {
+        // arrange
+        let sequence_type = SequenceType::EmptySequence;
+
+        // act & assert
+        assert!(sequence_type.matches(&xpath_item_set![]));
+    }
+
+    #[test]
+    fn empty_sequence_does_not_match_items() {
+        // arrange
+        let sequence_type = SequenceType::EmptySequence;
+        let items = xpath_item_set![XpathItem::AnyAtomicType(AnyAtomicType::Integer(1))];
+
+        // act & assert
+        assert!(!sequence_type.matches(&items));
+    }
+
+    #[test]
+    fn no_occurrence_indicator_requires_exactly_one_item() {
+        // arrange
+        let sequence_type = SequenceType::Sequence(SequenceTypeValue {
+            item_type: ItemType::Item,
+            occurrence: None,
+        });
+        let one = xpath_item_set![XpathItem::AnyAtomicType(AnyAtomicType::Integer(1))];
+        let two = xpath_item_set![
+            XpathItem::AnyAtomicType(AnyAtomicType::Integer(1)),
+            XpathItem::AnyAtomicType(AnyAtomicType::Integer(2))
+        ];
+
+        // act & assert
+        assert!(sequence_type.matches(&one));
+        assert!(!sequence_type.matches(&two));
+        assert!(!sequence_type.matches(&xpath_item_set![]));
+    }
+
+    #[test]
+    fn zero_or_more_matches_any_cardinality() {
+        // arrange
+        let sequence_type = SequenceType::Sequence(SequenceTypeValue {
+            item_type: ItemType::Item,
+            occurrence: Some(OccurrenceIndicator::ZeroOrMore),
+        });
+        let many = xpath_item_set![
+            XpathItem::AnyAtomicType(AnyAtomicType::Integer(1)),
+            XpathItem::AnyAtomicType(AnyAtomicType::Integer(2))
+        ];
+
+        // act & assert
+        assert!(sequence_type.matches(&xpath_item_set![]));
+        assert!(sequence_type.matches(&many));
+    }
+
+    #[test]
+    fn item_type_item_matches_any_value() {
+        // arrange
+        let item = XpathItem::AnyAtomicType(AnyAtomicType::String(String::from("hi")));
+
+        // act & assert
+        assert!(ItemType::Item.matches(&item));
+    }
+
+    #[test]
+    fn treat_as_passes_through_matching_items() {
+        // arrange
+        let sequence_type = SequenceType::Sequence(SequenceTypeValue {
+            item_type: ItemType::Item,
+            occurrence: None,
+        });
+        let items = xpath_item_set![XpathItem::AnyAtomicType(AnyAtomicType::Integer(1))];
+
+        // act
+        let result = sequence_type.treat_as(items);
+
+        // assert
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn treat_as_errors_on_cardinality_mismatch() {
+        // arrange
+        let sequence_type = SequenceType::Sequence(SequenceTypeValue {
+            item_type: ItemType::Item,
+            occurrence: None,
+        });
+
+        // act
+        let result = sequence_type.treat_as(xpath_item_set![]);
+
+        // assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn dispatch_item_type_picks_kind_test_for_keyword_call() {
+        // arrange & act
+        let branch = dispatch_item_type("comment()");
+
+        // assert
+        assert_eq!(Some(ItemTypeBranch::KindTest), branch);
+    }
+
+    #[test]
+    fn dispatch_item_type_picks_atomic_or_union_type_for_a_bare_name() {
+        // arrange & act
+        let branch = dispatch_item_type("xs:integer");
+
+        // assert
+        assert_eq!(Some(ItemTypeBranch::AtomicOrUnionType), branch);
+    }
+
+    #[test]
+    fn dispatch_item_type_does_not_confuse_a_type_name_with_a_keyword_call() {
+        // arrange & act
+        // `itemize` starts with the `item` keyword but isn't `item(...)`, so
+        // it must be dispatched as a type name, not the `item()` test.
+        let branch = dispatch_item_type("itemize");
+
+        // assert
+        assert_eq!(Some(ItemTypeBranch::AtomicOrUnionType), branch);
+    }
+
+    #[test]
+    fn dispatch_item_type_returns_none_for_unrecognizable_input() {
+        // arrange & act
+        let branch = dispatch_item_type("42");
+
+        // assert
+        assert_eq!(None, branch);
+    }
+
+    #[test]
+    fn item_type_diagnostic_reports_expected_set_on_unrecognizable_input() {
+        // arrange & act
+        let result = item_type_diagnostic("42");
+
+        // assert
+        let err = result.unwrap_err();
+        assert_eq!(String::from("42"), err.found);
+        assert!(err.expected.contains(&"item("));
+    }
+
+    #[test]
+    fn item_type_diagnostic_succeeds_like_item_type() {
+        // arrange & act
+        let result = item_type_diagnostic("item()").unwrap();
+
+        // assert
+        assert_eq!(("", ItemType::Item), result);
+    }
+
+    #[test]
+    fn empty_cardinality_cannot_match_one_or_more() {
+        // arrange
+        let target = SequenceType::Sequence(SequenceTypeValue {
+            item_type: ItemType::Item,
+            occurrence: Some(OccurrenceIndicator::OneOrMore),
+        });
+
+        // act & assert
+        assert!(target.statically_impossible_cardinality(StaticCardinality::EMPTY));
+    }
+
+    #[test]
+    fn empty_cardinality_matches_empty_sequence() {
+        // arrange & act & assert
+        assert!(!SequenceType::EmptySequence
+            .statically_impossible_cardinality(StaticCardinality::EMPTY));
+    }
+
+    #[test]
+    fn nonempty_cardinality_cannot_match_empty_sequence() {
+        // arrange & act & assert
+        assert!(SequenceType::EmptySequence
+            .statically_impossible_cardinality(StaticCardinality::EXACTLY_ONE));
+    }
+
+    #[test]
+    fn zero_or_more_cardinality_always_overlaps() {
+        // arrange
+        let target = SequenceType::Sequence(SequenceTypeValue {
+            item_type: ItemType::Item,
+            occurrence: None,
+        });
+
+        // act & assert
+        assert!(!target.statically_impossible_cardinality(StaticCardinality::ZERO_OR_MORE));
+    }
+
+    #[test]
+    fn check_cardinality_reports_impossible_test() {
+        // arrange
+        let target = SequenceType::Sequence(SequenceTypeValue {
+            item_type: ItemType::Item,
+            occurrence: Some(OccurrenceIndicator::OneOrMore),
+        });
+
+        // act
+        let diagnostic = check_cardinality(StaticCardinality::EMPTY, &target);
+
+        // assert
+        assert_eq!(Some(StaticCardinality::EMPTY), diagnostic.map(|d| d.source));
+    }
+
+    #[test]
+    fn check_cardinality_is_silent_on_possible_test() {
+        // arrange
+        let target = SequenceType::Sequence(SequenceTypeValue {
+            item_type: ItemType::Item,
+            occurrence: Some(OccurrenceIndicator::ZeroOrMore),
+        });
+
+        // act & assert
+        assert_eq!(None, check_cardinality(StaticCardinality::EMPTY, &target));
+    }
 }
\ No newline at end of file